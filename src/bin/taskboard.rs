@@ -5,8 +5,9 @@ use tasklib::{
     jsonrpc::{
         request::{Request, RequestType},
         response::{Response, ResponseResult, ResponseType},
-        short_process::ShortProcess,
+        short_process::{ShortProcess, State},
     },
+    run::proc::resources::format_bytes,
     termios::{change_to_raw_mode, reset_to_termios},
 };
 use tokio::{
@@ -25,6 +26,13 @@ enum Content {
     Empty,
 }
 
+/// A line of the process list. The colour of fatal processes is only added when
+/// printing, so the escape codes neither count towards the width nor affect the order.
+struct Row {
+    line: String,
+    fatal: bool,
+}
+
 struct TaskBoard {
     content: Content,
     scrolled_lines_down: usize,
@@ -95,31 +103,42 @@ impl TaskBoard {
                     self.scrolled_lines_down = processes.len().saturating_sub(height);
                 }
 
-                let mut lines = Vec::new();
+                let mut rows = Vec::new();
 
                 for p in processes.iter() {
-                    lines.push(format!("State: {}, Name: {}", p.state(), p.name()));
+                    // Fatal processes are no longer restarted and need someone to look at them.
+                    let fatal = matches!(p.state(), State::Fatal(_));
+                    let mut line = format!("State: {}, Name: {}", p.state(), p.name());
+                    if let Some(resources) = p.resources() {
+                        line.push_str(&format!(
+                            ", CPU: {:.1}%, RSS: {}, Threads: {}, FDs: {}",
+                            resources.cpu_percent(),
+                            format_bytes(resources.rss_bytes()),
+                            resources.threads(),
+                            resources.fds()
+                        ));
+                    }
+                    rows.push(Row { line, fatal });
                 }
 
-                lines.sort();
-
-                let max_line = lines.iter().max_by(|a, b| a.len().cmp(&b.len()));
+                rows.sort_by(|a, b| a.line.cmp(&b.line));
 
-                let max_line = match max_line {
+                let max_line_length = match rows.iter().map(|row| row.line.len()).max() {
                     Some(l) => l,
                     None => return,
                 };
 
-                let max_line_length = max_line.len();
-
                 if max_line_length > self.terminal_width {
                     println!("terminal not big enough to display processes | {} width needed {} given", max_line_length, self.terminal_width);
                     return;
                 }
 
-                for (_, line) in lines.iter_mut().enumerate().skip(self.scrolled_lines_down).take(height) {
-                    line.push(13 as char);
-                    println!("{}", line);
+                for row in rows.iter_mut().skip(self.scrolled_lines_down).take(height) {
+                    row.line.push(13 as char);
+                    match row.fatal {
+                        true => println!("\x1b[1;31m{}\x1b[0m", row.line),
+                        false => println!("{}", row.line),
+                    }
                 }
             }
         }
@@ -181,11 +200,7 @@ impl TaskBoard {
         match entry {
             b'q' => return Err(()),
             27 => self.command_started = true,
-            91 => {
-                if self.command_started {
-                    self.command_arrow = true
-                }
-            }
+            91 if self.command_started => self.command_arrow = true,
             b'A' => {
                 self.scrolled_lines_down = self.scrolled_lines_down.saturating_sub(1);
                 self.command_arrow = false;
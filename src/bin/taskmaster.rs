@@ -1,12 +1,13 @@
 use std::{
     env::{self},
     error::Error,
-    fs::remove_file,
 };
 
-use tasklib::{conf::Config, log, log_info, run::daemon::Daemon};
-
-pub const PID_FILE_PATH: &str = "/tmp/taskmaster.pid";
+use tasklib::{
+    conf::Config,
+    log, log_info,
+    run::daemon::{Daemon, pidfile::PidFile},
+};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
@@ -32,6 +33,13 @@ async fn main() -> Result<(), Box<dyn Error>> {
         }
     };
 
+    // Checked before initializing the logger so a second instance does not truncate
+    // the logfile of the running one. The lock itself is taken in `Daemon::run`.
+    if PidFile::is_locked(conf.pidfile()) {
+        eprintln!("another taskmaster instance is already running (pid file: {})", conf.pidfile());
+        std::process::exit(1)
+    }
+
     log::init(conf.logfile())?;
 
     let mut daemon = Daemon::from_config(conf, arg);
@@ -40,7 +48,15 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     daemon.run().await?;
 
-    let _ = remove_file(PID_FILE_PATH);
+    // As a container's entrypoint, the container's exit code reflects how the
+    // supervised processes did.
+    if daemon.init() {
+        let code = daemon.exit_code();
+        log_info!("exiting",; code = code);
+        // `exit` skips destructors, the pid file is released the same way as below.
+        drop(daemon);
+        std::process::exit(code);
+    }
 
     Ok(())
 }
@@ -1,12 +1,15 @@
+use serde::Deserialize;
 use std::{
     env::args,
     fs,
     io::Read,
-    path::Path,
     process::{Command, Stdio, exit},
-    sync::{atomic::AtomicU32, mpsc},
+    sync::{
+        atomic::{AtomicI32, AtomicU32, Ordering},
+        mpsc,
+    },
     thread,
-    time::Duration,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use tokio::{
     io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader},
@@ -14,14 +17,20 @@ use tokio::{
 };
 
 use tasklib::{
-    conf::PID_FILE_PATH,
+    conf::defaults::dflt_pidfile,
     jsonrpc::{
+        daemon_info::DaemonInfo,
+        reload_report::ReloadReport,
         request::{AttachFile, RequestType},
-        response::{Response, ResponseType},
+        response::{Response, ResponseResult, ResponseType},
+        rolling_restart_progress::{InstanceStatus, RollingRestartProgress},
+        run_history::RunStarted,
+        short_process::{ShortProcess, compact_duration},
     },
+    run::{daemon::pidfile::PidFile, proc::resources::format_bytes},
     shell::{
         self,
-        args::{Args, EngineSubcommand, ShellCommand, help},
+        args::{Args, EngineSubcommand, RollingRestartOptions, ShellCommand, help},
     },
     termios::{change_to_raw_mode, reset_to_termios},
 };
@@ -48,8 +57,33 @@ async fn write_request(unix_stream: &mut UnixStream, request: &[u8]) -> Result<(
 
 static ID_COUNTER: AtomicU32 = AtomicU32::new(1);
 
-fn build_request_reload() -> Request {
-    Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_reload())
+/// Exit code of `taskshell` when not running interactively, set by `run --wait`.
+static EXIT_CODE: AtomicI32 = AtomicI32::new(0);
+
+fn build_request_reload(dry_run: bool) -> Request {
+    Request::new(
+        ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+        match dry_run {
+            true => RequestType::new_reload_dry_run(),
+            false => RequestType::new_reload(),
+        },
+    )
+}
+
+fn build_request_reset(name: &Option<String>) -> Request {
+    Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_reset(name.as_deref()))
+}
+
+fn build_request_reset_failed(name: &Option<String>) -> Request {
+    Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_reset_failed(name.as_deref()))
+}
+
+fn build_request_rollback(steps: usize) -> Request {
+    Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_rollback(steps))
+}
+
+fn build_request_info() -> Request {
+    Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_info())
 }
 
 fn build_request_halt() -> Request {
@@ -66,6 +100,10 @@ fn build_request_status(name: &Option<String>) -> Request {
     )
 }
 
+fn build_request_describe(name: &str) -> Request {
+    Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_describe(name))
+}
+
 fn build_request_start(name: &str) -> Request {
     Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_start(name))
 }
@@ -78,6 +116,29 @@ fn build_request_stop(name: &str) -> Request {
     Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_stop(name))
 }
 
+fn build_request_run(name: &str) -> Request {
+    Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_run(name))
+}
+
+fn build_request_history(name: &str) -> Request {
+    Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_history(name))
+}
+
+fn build_request_rolling_restart(name: &str, options: &RollingRestartOptions) -> Request {
+    Request::new(
+        ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+        RequestType::new_rolling_restart(name, options.batch_size, options.max_unavailable, options.abort_on_failure),
+    )
+}
+
+fn build_request_rolling_restart_status(name: &str) -> Request {
+    Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_rolling_restart_status(name))
+}
+
+fn build_request_scale(name: &str, processes: u16) -> Request {
+    Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_scale(name, processes))
+}
+
 fn build_request_attach(name: &str, to: &AttachFile) -> Request {
     Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_attach(name, to))
 }
@@ -89,31 +150,29 @@ enum BuildRequestResult {
     Exit,
 }
 
-fn engine_running() -> bool {
-    use std::path::Path;
-
-    let mut pid_file = match std::fs::File::open("/tmp/taskmaster.pid").map_err(|e| e.to_string()) {
-        Ok(file) => file,
-        Err(_) => return false,
-    };
-
-    let mut pid = String::new();
+/// Only the fields of the config `taskshell` needs to know about. Parsing the full
+/// `Config` would run its path validation, which has side effects on the filesystem.
+#[derive(Deserialize)]
+struct EngineConfig {
+    #[serde(default = "dflt_pidfile")]
+    pidfile: String,
+}
 
-    if pid_file.read_to_string(&mut pid).is_err() {
-        return true;
-    }
+fn pidfile_from_config(config_path: &str) -> String {
+    fs::read_to_string(config_path)
+        .ok()
+        .and_then(|conf| toml::from_str::<EngineConfig>(&conf).ok())
+        .map(|conf| conf.pidfile)
+        .unwrap_or_else(dflt_pidfile)
+}
 
-    match Path::new(&format!("/proc/{pid}")).exists() {
-        true => true,
-        false => {
-            let _ = fs::remove_file(PID_FILE_PATH);
-            false
-        }
-    }
+fn engine_running(pidfile: &str) -> bool {
+    PidFile::is_locked(pidfile)
 }
 
 fn start_engine(config_path: &str) -> Result<String, String> {
-    if engine_running() {
+    let pidfile = pidfile_from_config(config_path);
+    if engine_running(&pidfile) {
         return Ok("The Taskmaster is already running".to_string());
     }
 
@@ -142,18 +201,16 @@ fn start_engine(config_path: &str) -> Result<String, String> {
         let _ = tx.send(output);
     });
 
-    let pid_file = Path::new(PID_FILE_PATH);
     for backoff in [5, 10, 20, 40, 80] {
-        if pid_file.exists() {
+        if engine_running(&pidfile) {
             return Ok("Started Taskmaster engine".to_string());
         }
 
-        if let Ok(stderr_output) = rx.try_recv() {
-            if let Ok(Some(status)) = child.try_wait() {
-                if status.code() != Some(0) {
-                    return Err(format!("Could not start Taskmaster engine:\n\n{stderr_output}"));
-                }
-            }
+        if let Ok(stderr_output) = rx.try_recv()
+            && let Ok(Some(status)) = child.try_wait()
+            && status.code() != Some(0)
+        {
+            return Err(format!("Could not start Taskmaster engine:\n\n{stderr_output}"));
         }
 
         thread::sleep(Duration::from_millis(backoff * 100));
@@ -166,12 +223,21 @@ fn start_engine(config_path: &str) -> Result<String, String> {
 
 fn build_request(command: &ShellCommand) -> BuildRequestResult {
     match command {
-        ShellCommand::Status { process } => BuildRequestResult::RequestToEngine(build_request_status(process)),
+        ShellCommand::Status { process, .. } => BuildRequestResult::RequestToEngine(build_request_status(process)),
+        ShellCommand::Describe { process } => BuildRequestResult::RequestToEngine(build_request_describe(process)),
         ShellCommand::Start { process } => BuildRequestResult::RequestToEngine(build_request_start(process)),
         ShellCommand::Restart { process } => BuildRequestResult::RequestToEngine(build_request_restart(process)),
         ShellCommand::Stop { process } => BuildRequestResult::RequestToEngine(build_request_stop(process)),
         ShellCommand::Attach { process, fd } => BuildRequestResult::RequestToEngine(build_request_attach(process, fd)),
-        ShellCommand::Reload => BuildRequestResult::RequestToEngine(build_request_reload()),
+        ShellCommand::Reload { dry_run } => BuildRequestResult::RequestToEngine(build_request_reload(*dry_run)),
+        ShellCommand::Reset { process } => BuildRequestResult::RequestToEngine(build_request_reset(process)),
+        ShellCommand::ResetFailed { process } => BuildRequestResult::RequestToEngine(build_request_reset_failed(process)),
+        ShellCommand::Rollback { steps } => BuildRequestResult::RequestToEngine(build_request_rollback(*steps)),
+        ShellCommand::Run { process, .. } => BuildRequestResult::RequestToEngine(build_request_run(process)),
+        ShellCommand::History { process } => BuildRequestResult::RequestToEngine(build_request_history(process)),
+        ShellCommand::RollingRestart { process, options } => BuildRequestResult::RequestToEngine(build_request_rolling_restart(process, options)),
+        ShellCommand::Scale { process, processes } => BuildRequestResult::RequestToEngine(build_request_scale(process, *processes)),
+        ShellCommand::Info => BuildRequestResult::RequestToEngine(build_request_info()),
         ShellCommand::Exit => BuildRequestResult::Exit,
         ShellCommand::Engine { subcommand } => match subcommand {
             EngineSubcommand::Start { config_path } => BuildRequestResult::StartEngine {
@@ -230,23 +296,238 @@ async fn attach(name: &str, socket_path: &str, to: &str, mut orig: Option<&mut l
     "".to_string()
 }
 
-async fn response_to_str(response: &Response, orig: Option<&mut libc::termios>) -> String {
+/// Polls the progress of a rolling restart and prints every instance whose status
+/// changed, until the rolling restart finished or the user detaches with Ctrl-C.
+async fn follow_rolling_restart(progress: &RollingRestartProgress, socketpath: &str, mut orig: Option<&mut libc::termios>) -> String {
+    if let Some(o) = orig.as_mut() {
+        reset_to_termios(o);
+    }
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<()>(1);
+    tokio::spawn(async move {
+        if (tokio::signal::ctrl_c().await).is_ok() {
+            let _ = tx.send(()).await;
+        }
+    });
+
+    let mut progress = progress.to_owned();
+    let mut printed: Vec<InstanceStatus> = vec![];
+    print_raw_mode(&format!("{progress}\n"));
+    let summary = loop {
+        for (idx, instance) in progress.instances().iter().enumerate() {
+            if printed.get(idx) != Some(instance.status()) && *instance.status() != InstanceStatus::Pending {
+                print_raw_mode(&format!("  {instance}\n"));
+            }
+        }
+        printed = progress.instances().iter().map(|i| i.status().to_owned()).collect();
+
+        if progress.finished() {
+            break progress.to_string();
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_millis(500)) => {},
+            _ = rx.recv() => break format!("detached, the rolling restart of {} continues in the background", progress.program()),
+        }
+
+        let request = build_request_rolling_restart_status(progress.program());
+        match send_request(socketpath, &request).await {
+            Ok(response) => match response.response_type() {
+                ResponseType::Result(ResponseResult::RollingRestartStatus(update)) => progress = update.to_owned(),
+                ResponseType::Error(err) => break err.message.to_string(),
+                _ => break "unexpected response to rolling restart status request".to_string(),
+            },
+            Err(e) => break e,
+        }
+    };
+
+    if let Some(orig) = orig {
+        *orig = change_to_raw_mode();
+    }
+    summary
+}
+
+/// Polls the history of a `oneshot` process until the run which was just started shows
+/// up in it, setting `EXIT_CODE` to the run's exit code. Anything keeping the outcome
+/// from being known (the daemon not responding, detaching, ...) exits with `1`, the run
+/// must not pass for a successful one.
+async fn wait_for_run(started: &RunStarted, socketpath: &str, mut orig: Option<&mut libc::termios>) -> String {
+    if let Some(o) = orig.as_mut() {
+        reset_to_termios(o);
+    }
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<()>(1);
+    tokio::spawn(async move {
+        if (tokio::signal::ctrl_c().await).is_ok() {
+            let _ = tx.send(()).await;
+        }
+    });
+
+    print_raw_mode(&format!("{started}\n"));
+    EXIT_CODE.store(1, Ordering::Relaxed);
+    let summary = loop {
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_millis(500)) => {},
+            _ = rx.recv() => break format!("detached, run #{} of {} continues in the background", started.run(), started.name()),
+        }
+
+        let request = build_request_history(started.name());
+        let history = match send_request(socketpath, &request).await {
+            Ok(response) => match response.response_type() {
+                ResponseType::Result(ResponseResult::History(history)) => history.to_owned(),
+                ResponseType::Error(err) => break err.message.to_string(),
+                _ => break "unexpected response to history request".to_string(),
+            },
+            Err(e) => break e,
+        };
+
+        if let Some(run) = history.runs().iter().find(|run| run.id() == started.run()) {
+            EXIT_CODE.store(run.outcome().code(), Ordering::Relaxed);
+            break run.to_string();
+        }
+        // Newer runs push it out of the history, or the process was stopped before it
+        // was spawned.
+        if history.runs().iter().any(|run| run.id() > started.run()) || !history.pending() {
+            break format!("run #{} of {} did not complete", started.run(), started.name());
+        }
+    };
+
+    if let Some(orig) = orig {
+        *orig = change_to_raw_mode();
+    }
+    summary
+}
+
+fn daemon_info_to_str(info: &DaemonInfo) -> String {
+    let mut lines = vec![
+        format!("pid: {}", info.pid()),
+        format!("config: {}", info.config_path()),
+        format!("socket: {}", info.socketpath()),
+        format!("pidfile: {}", info.pidfile()),
+        format!("processes: {}", info.processes()),
+    ];
+
+    if info.shutting_down() {
+        lines.push("shutting down".to_string());
+    }
+
+    match info.last_failed_reload() {
+        Some(failed) => {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+            lines.push(format!(
+                "last reload of {} failed {} seconds ago, running on the last valid configuration:\n{}",
+                failed.config_path(),
+                now.saturating_sub(failed.timestamp()),
+                failed.error()
+            ));
+        }
+        None => lines.push("last reload: ok".to_string()),
+    }
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    lines.push("configurations (rollback N):".to_string());
+    for (steps, snapshot) in info.snapshots().iter().rev().enumerate() {
+        lines.push(format!(
+            "  {steps}: {:016x}, applied {} seconds ago{}",
+            snapshot.hash(),
+            now.saturating_sub(snapshot.timestamp()),
+            if steps == 0 { " (active)" } else { "" }
+        ));
+    }
+
+    lines.join("\n")
+}
+
+fn status_long_to_str(processes: &[ShortProcess]) -> String {
+    let rows = processes.iter().map(|p| {
+        [
+            p.name().to_string(),
+            p.pid().map(|pid| pid.to_string()).unwrap_or("-".to_string()),
+            p.uptime().map(compact_duration).unwrap_or("-".to_string()),
+            p.resources()
+                .map(|r| format!("{:.1}%", r.cpu_percent()))
+                .unwrap_or("-".to_string()),
+            p.resources().map(|r| format_bytes(r.rss_bytes())).unwrap_or("-".to_string()),
+            p.restarts().to_string(),
+            p.last_exit().map(|exit| exit.to_string()).unwrap_or("-".to_string()),
+            p.state().to_string(),
+        ]
+    });
+    let header = ["NAME", "PID", "UPTIME", "CPU", "RSS", "RESTARTS", "LAST EXIT", "STATE"].map(str::to_string);
+    let rows = std::iter::once(header).chain(rows).collect::<Vec<_>>();
+
+    let mut widths = [0; 8];
+    for row in rows.iter() {
+        for (width, column) in widths.iter_mut().zip(row) {
+            *width = (*width).max(column.len());
+        }
+    }
+
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .zip(widths)
+                .map(|(column, width)| format!("{column:width$}"))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn reload_report_to_str(action: &str, report: &ReloadReport) -> String {
+    let mut lines = vec![match report.dry_run() {
+        true => "dry run, nothing was applied".to_string(),
+        false => action.to_string(),
+    }];
+
+    if report.is_empty() {
+        lines.push("no changes".to_string());
+    }
+    for name in report.added() {
+        lines.push(format!("  add: {name}"));
+    }
+    for name in report.removed() {
+        lines.push(format!("  remove: {name}"));
+    }
+    for changed in report.restarted() {
+        lines.push(format!("  restart: {} ({})", changed.name(), changed.fields().join(", ")));
+    }
+    for changed in report.updated() {
+        lines.push(format!("  update: {} ({})", changed.name(), changed.fields().join(", ")));
+    }
+    for name in report.unchanged() {
+        lines.push(format!("  unchanged: {name}"));
+    }
+
+    lines.join("\n")
+}
+
+async fn response_to_str(response: &Response, socketpath: &str, orig: Option<&mut libc::termios>) -> String {
     match response.response_type() {
         ResponseType::Result(res) => {
             use tasklib::jsonrpc::response::ResponseResult::*;
             match res {
-                Status(items) => items
-                    .iter()
-                    .map(|sp| format!("{}: {}", sp.name(), sp.state()))
-                    .collect::<Vec<String>>()
-                    .join("\n"),
-                StatusSingle(item) => format!("{}: {}", item.name(), item.state()),
+                Status(items) => items.iter().map(|sp| sp.to_string()).collect::<Vec<String>>().join("\n"),
+                StatusSingle(item) => item.to_string(),
                 Start(name) => format!("starting: {name}"),
                 Stop(name) => format!("stopping: {name}"),
                 Restart(name) => format!("restarting: {name}"),
-                Reload => "reloading configuration".to_string(),
+                Reset(msg) => msg.to_string(),
+                ResetFailed(msg) => msg.to_string(),
+                Scale(msg) => msg.to_string(),
+                Reload(report) => reload_report_to_str("reloading configuration", report),
                 Halt => "shutting down taskmaster".to_string(),
                 Attach { name, socketpath, to } => attach(name, socketpath, to, orig).await,
+                Info(info) => daemon_info_to_str(info),
+                Rollback(report) => reload_report_to_str("rolling back configuration", report),
+                RollingRestart(progress) => follow_rolling_restart(progress, socketpath, orig).await,
+                RollingRestartStatus(progress) => progress.to_string(),
+                History(history) => history.to_string(),
+                Run(started) => started.to_string(),
+                Describe(details) => details.to_string(),
             }
         }
         ResponseType::Error(err) => err.message.to_string(),
@@ -263,7 +544,29 @@ async fn handle_input(input: Vec<String>, orig: Option<&mut libc::termios>) -> R
         BuildRequestResult::RequestToEngine(request) => request,
     };
 
-    let mut unix_stream: UnixStream = match UnixStream::connect(arguments.socketpath()).await {
+    let response = send_request(arguments.socketpath(), &request).await?;
+
+    if let ShellCommand::Status { long: true, .. } = arguments.command() {
+        match response.response_type() {
+            ResponseType::Result(ResponseResult::Status(processes)) => return Ok(status_long_to_str(processes)),
+            ResponseType::Result(ResponseResult::StatusSingle(process)) => return Ok(status_long_to_str(std::slice::from_ref(process.as_ref()))),
+            _ => {}
+        }
+    }
+
+    if let ShellCommand::Run { wait: true, .. } = arguments.command() {
+        match response.response_type() {
+            ResponseType::Result(ResponseResult::Run(started)) => return Ok(wait_for_run(started, arguments.socketpath(), orig).await),
+            ResponseType::Error(_) => EXIT_CODE.store(1, Ordering::Relaxed),
+            _ => {}
+        }
+    }
+
+    Ok(response_to_str(&response, arguments.socketpath(), orig).await)
+}
+
+async fn send_request(socketpath: &str, request: &Request) -> Result<Response, String> {
+    let mut unix_stream: UnixStream = match UnixStream::connect(socketpath).await {
         Ok(s) => s,
         Err(e) => {
             return Err(format!(
@@ -272,7 +575,7 @@ async fn handle_input(input: Vec<String>, orig: Option<&mut libc::termios>) -> R
         }
     };
 
-    let request_str = serde_json::to_string(&request).unwrap(); // unwrap because this should never fail
+    let request_str = serde_json::to_string(request).unwrap(); // unwrap because this should never fail
 
     if let Err(e) = write_request(&mut unix_stream, request_str.as_bytes()).await {
         return Err(format!("error while writing request: {e}"));
@@ -290,7 +593,7 @@ async fn handle_input(input: Vec<String>, orig: Option<&mut libc::termios>) -> R
     };
     response.set_response_result(request.request_type());
 
-    Ok(response_to_str(&response, orig).await)
+    Ok(response)
 }
 
 fn print_raw_mode(string: &str) {
@@ -331,7 +634,10 @@ async fn main() {
     match args.len() {
         0 => shell().await,
         _ => match handle_input(args, None).await {
-            Ok(data) => print_raw_mode(&format!("{data}\n")),
+            Ok(data) => {
+                print_raw_mode(&format!("{data}\n"));
+                exit(EXIT_CODE.load(Ordering::Relaxed));
+            }
             Err(e) => {
                 print_raw_mode(&e.to_string());
                 exit(1);
@@ -2,15 +2,14 @@ use std::{
     collections::{HashMap, HashSet},
     error::Error,
     fs,
+    hash::{DefaultHasher, Hash, Hasher},
 };
 
-use defaults::{dflt_authgroup, dflt_logfile, dflt_socketpath};
+use defaults::{dflt_authgroup, dflt_logfile, dflt_pidfile, dflt_snapshots, dflt_socketpath, dflt_statefile};
 
 use proc::ProcessConfig;
 use serde::Deserialize;
 
-pub const PID_FILE_PATH: &str = "/tmp/taskmaster.pid";
-
 pub mod defaults;
 pub mod proc;
 mod tests;
@@ -46,6 +45,46 @@ pub struct Config {
     #[serde(skip)]
     logfile: String,
 
+    /// Path to the PID file. The daemon holds an exclusive lock on it for its
+    /// whole lifetime, refusing to start if another live instance holds it.
+    ///
+    /// Default:
+    /// ```toml
+    /// pidfile = "/tmp/taskmaster.pid"
+    /// ```
+    #[serde(default = "dflt_pidfile")]
+    pidfile: String,
+
+    /// Path to the file operator overrides (`start`/`stop` requests) and failure counters
+    /// are persisted to, so they survive a restart of the daemon.
+    ///
+    /// Default:
+    /// ```toml
+    /// statefile = "/tmp/taskmaster.state"
+    /// ```
+    #[serde(default = "dflt_statefile")]
+    statefile: String,
+
+    /// Reload the configuration automatically whenever the config file changes, as
+    /// if the daemon received `SIGHUP`. Turning it on or off is applied by a reload.
+    ///
+    /// Default:
+    /// ```toml
+    /// watch_config = false
+    /// ```
+    #[serde(default)]
+    watch_config: bool,
+
+    /// Number of successfully applied configurations kept for `rollback`, including
+    /// the active one. Must be at least `1`.
+    ///
+    /// Default:
+    /// ```toml
+    /// snapshots = 10
+    /// ```
+    #[serde(default = "dflt_snapshots")]
+    snapshots: usize,
+
     /// Map of processes to configure individually. For process-level configuration,
     /// see [`crate::conf::proc::ProcessConfig`].
     ///
@@ -58,6 +97,10 @@ pub struct Config {
     /// At least one process must be defined for `taskmaster`to run.
     #[serde(default)]
     processes: HashMap<String, ProcessConfig>,
+
+    /// Hash of the raw config file content this was parsed from.
+    #[serde(skip)]
+    hash: u64,
 }
 
 impl Config {
@@ -73,6 +116,7 @@ impl Config {
     }
 
     #[cfg(test)]
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(config: &str) -> Result<Self, Box<dyn Error>> {
         Config::parse(config)
     }
@@ -89,10 +133,18 @@ impl Config {
         conf.socketpath = dflt_socketpath();
         conf.logfile = dflt_logfile();
 
+        let mut hasher = DefaultHasher::new();
+        config_str.hash(&mut hasher);
+        conf.hash = hasher.finish();
+
         if conf.processes.is_empty() {
             return Err("taskmaster expects at least one process to be defined to operate".into());
         }
 
+        if conf.snapshots == 0 {
+            return Err("snapshots must be at least 1, the active configuration is always kept".into());
+        }
+
         let mut seen = HashSet::new();
         let duplicates = conf
             .processes
@@ -137,6 +189,26 @@ impl Config {
     pub fn logfile(&self) -> &str {
         &self.logfile
     }
+
+    pub fn pidfile(&self) -> &str {
+        &self.pidfile
+    }
+
+    pub fn statefile(&self) -> &str {
+        &self.statefile
+    }
+
+    pub fn watch_config(&self) -> bool {
+        self.watch_config
+    }
+
+    pub fn snapshots(&self) -> usize {
+        self.snapshots
+    }
+
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
 }
 
 #[cfg(test)]
@@ -146,7 +218,12 @@ impl Default for Config {
             socketpath: dflt_socketpath(),
             authgroup: dflt_authgroup(),
             logfile: dflt_logfile(),
+            pidfile: dflt_pidfile(),
+            statefile: dflt_statefile(),
+            watch_config: false,
+            snapshots: dflt_snapshots(),
             processes: HashMap::new(),
+            hash: 0,
         }
     }
 }
@@ -163,6 +240,16 @@ impl Config {
         self
     }
 
+    pub fn set_pidfile(&mut self, pidfile: &str) -> &mut Self {
+        self.pidfile = pidfile.to_string();
+        self
+    }
+
+    pub fn set_statefile(&mut self, statefile: &str) -> &mut Self {
+        self.statefile = statefile.to_string();
+        self
+    }
+
     pub fn add_process(&mut self, name: &str, process: ProcessConfig) -> &mut Self {
         self.processes.insert(name.to_string(), process);
         self
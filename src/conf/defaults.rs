@@ -9,3 +9,15 @@ pub fn dflt_authgroup() -> String {
 pub fn dflt_logfile() -> String {
     "/tmp/taskmaster.log".to_string()
 }
+
+pub fn dflt_pidfile() -> String {
+    "/tmp/taskmaster.pid".to_string()
+}
+
+pub fn dflt_statefile() -> String {
+    "/tmp/taskmaster.state".to_string()
+}
+
+pub fn dflt_snapshots() -> usize {
+    10
+}
@@ -25,6 +25,8 @@ mod defaults;
 /// allowing them to be directly deserialized into `ProcessConfig`.
 pub mod types;
 
+use std::collections::BTreeMap;
+
 use serde::Deserialize;
 
 #[allow(unused)]
@@ -84,9 +86,9 @@ pub struct ProcessConfig {
     /// processes = 4
     /// ```
     ///
-    /// Defaults to `1`.
+    /// Defaults to `1`, max `65535`.
     #[serde(default = "defaults::dflt_processes")]
-    processes: u8,
+    processes: u16,
 
     /// Mask for files created by the process.
     ///
@@ -112,6 +114,20 @@ pub struct ProcessConfig {
     /// Required.
     workingdir: types::AccessibleDirectory,
 
+    /// Whether the process is kept running (`service`) or runs to completion
+    /// (`oneshot`), see [`types::ProcessType`].
+    ///
+    /// ```toml
+    /// [processes.migrate]
+    /// cmd = "/usr/local/bin/migrate"
+    /// workingdir = "/srv/api"
+    /// type = "oneshot"
+    /// ```
+    ///
+    /// Defaults to `service`.
+    #[serde(default, rename = "type")]
+    process_type: types::ProcessType,
+
     /// Whether to start the process automatically when starting `taskmaster`.
     ///
     /// ```toml
@@ -142,6 +158,84 @@ pub struct ProcessConfig {
     #[serde(default = "defaults::dflt_autorestart")]
     autorestart: types::AutoRestart,
 
+    /// How `restart` replaces the running process, options are:
+    /// - `stop-first`: Stop it, then start a new instance.
+    /// - `start-first`: Start a new instance next to it, and only stop the old one once
+    ///   the new one passed its healthcheck. If the new instance fails, the old one
+    ///   keeps running.
+    ///
+    /// ```toml
+    /// [processes.nginx]
+    /// cmd = "/usr/sbin/nginx"
+    /// workingdir = "/var/www"
+    /// restart_strategy = "start-first"
+    /// ```
+    ///
+    /// Defaults to `stop-first`.
+    #[serde(default = "defaults::dflt_restart_strategy")]
+    restart_strategy: types::RestartStrategy,
+
+    /// Stop restarting the process once it failed more than `burst` times within
+    /// `interval`, see [`types::StartLimit`]. It then stays `fatal` until started
+    /// again or cleared with `reset-failed`.
+    ///
+    /// ```toml
+    /// [processes.nginx]
+    /// cmd = "/usr/sbin/nginx"
+    /// workingdir = "/var/www"
+    /// autorestart = "always"
+    /// startlimit = { burst = 5, interval = "60s" }
+    /// ```
+    ///
+    /// Defaults to no limit.
+    #[serde(default)]
+    startlimit: Option<types::StartLimit>,
+
+    /// Run the process to completion periodically instead of keeping it up, see
+    /// [`types::Schedule`]. Each run moves it from `idle`/`completed`/`stopped` to
+    /// `ready`, a process stopped by an operator is left alone until started again.
+    ///
+    /// ```toml
+    /// [processes.backup]
+    /// cmd = "/usr/local/bin/backup"
+    /// workingdir = "/var/backups"
+    /// schedule = { cron = "0 3 * * *", timezone = "Europe/Berlin", overlap = "skip" }
+    /// ```
+    ///
+    /// Defaults to no schedule.
+    #[serde(default)]
+    schedule: Option<types::Schedule>,
+
+    /// Restart the process once it used too much memory, CPU or file descriptors for a
+    /// while, or ran for too long, see [`types::RestartIf`]. The reason is logged and
+    /// shown in `status`.
+    ///
+    /// ```toml
+    /// [processes.worker]
+    /// cmd = "/usr/local/bin/worker"
+    /// workingdir = "/srv/worker"
+    /// restart_if = { rss_above = "2GiB", for = "2m", max_lifetime = "24h" }
+    /// ```
+    ///
+    /// Defaults to no limits.
+    #[serde(default)]
+    restart_if: Option<types::RestartIf>,
+
+    /// Restart the process (or send it a signal) whenever one of the watched files
+    /// changes, see [`types::Watch`].
+    ///
+    /// ```toml
+    /// [processes.api]
+    /// cmd = "/usr/bin/cargo"
+    /// args = ["run"]
+    /// workingdir = "/home/dev/api"
+    /// watch = { paths = ["./src", "./config"], ignore = ["*.swp"], debounce = "500ms" }
+    /// ```
+    ///
+    /// Defaults to not watching anything.
+    #[serde(default)]
+    watch: Option<types::Watch>,
+
     /// List of exit codes to be interpreted as successful.
     ///
     /// ```toml
@@ -167,7 +261,7 @@ pub struct ProcessConfig {
     /// - Uptime HealthCheck:
     ///
     /// ```toml
-    /// starttime = <int>
+    /// starttime = <duration>
     /// ```
     ///
     /// Defaults to `{starttime = 5, retries = 5, backoff = 5}`
@@ -188,19 +282,54 @@ pub struct ProcessConfig {
     #[serde(default = "defaults::dflt_stopsignals")]
     stopsignals: Vec<types::StopSignal>,
 
-    /// Time (in seconds) to wait for the process to stop. If it does not stop within
-    /// this time, it will be killed forcibly.
+    /// Time to wait for the process to stop, see [`types::Duration`]. If it does not stop
+    /// within this time, it will be killed forcibly. Also used for the steps of `stop`
+    /// which do not set their own `wait`.
     ///
     /// ```toml
     /// [processes.nginx]
     /// cmd = "/usr/sbin/nginx"
     /// workingdir = "/var/www"
-    /// stoptime = 20
+    /// stoptime = "1m30s"
     /// ```
     ///
-    /// Defaults to `5`, max `255`.
+    /// Defaults to `5`.
     #[serde(default = "defaults::dflt_stoptime")]
-    stoptime: u8,
+    stoptime: types::Duration,
+
+    /// Ordered steps taken to stop the process, each sending a signal and waiting for
+    /// the process to exit before escalating to the next one. See
+    /// [`types::StopSequence`].
+    ///
+    /// ```toml
+    /// [processes.nginx]
+    /// cmd = "/usr/sbin/nginx"
+    /// workingdir = "/var/www"
+    /// stop = [{ signal = "TERM", wait = "10s" }, { signal = "INT", wait = "5s" }, { signal = "KILL" }]
+    /// ```
+    ///
+    /// Takes precedence over `stopsignals`, which (along with `stoptime`) is shorthand
+    /// for sending all of them at once, then waiting for `stoptime`.
+    #[serde(default)]
+    stop: Option<types::StopSequence>,
+
+    /// Which processes to signal when stopping, options are:
+    /// - `process`: Only the process started by taskmaster.
+    /// - `group`: Its whole process group (every process is started in its own group).
+    /// - `tree`: Its process group and all of its descendants, including those which
+    ///   changed groups or were orphaned.
+    ///
+    /// ```toml
+    /// [processes.worker]
+    /// cmd = "/usr/bin/sh"
+    /// args = ["-c", "./worker & ./worker"]
+    /// workingdir = "/srv"
+    /// killmode = "tree"
+    /// ```
+    ///
+    /// Defaults to `group`.
+    #[serde(default = "defaults::dflt_killmode")]
+    killmode: types::KillMode,
 
     /// File the standard output of the process should be redirected to.
     ///
@@ -239,6 +368,44 @@ pub struct ProcessConfig {
     /// Defaults to an empty list.
     #[serde(default)]
     env: Vec<(String, String)>,
+
+    /// Listening sockets bound by taskmaster and passed to the process, see
+    /// [`types::SocketConfig`]. They stay open while the process restarts, so clients
+    /// queue up instead of being refused.
+    ///
+    /// The process inherits them starting at fd 3, ordered by name, with
+    /// `LISTEN_FDS`, `LISTEN_PID` and `LISTEN_FDNAMES` set as done by systemd.
+    ///
+    /// ```toml
+    /// [processes.web.sockets.http]
+    /// tcp = "0.0.0.0:8080"
+    ///
+    /// [processes.web.sockets.admin]
+    /// unix = "/run/web/admin.sock"
+    /// mode = "660"
+    /// ```
+    ///
+    /// Defaults to no sockets.
+    #[serde(default)]
+    sockets: BTreeMap<String, types::SocketConfig>,
+
+    /// Commands run at transitions of the process, see [`types::Hooks`]. A failing
+    /// `pre_start` hook keeps the process from being spawned, `pre_stop` delays the
+    /// first stop signal until it exited.
+    ///
+    /// ```toml
+    /// [processes.api.hooks.pre_start]
+    /// cmd = "/usr/local/bin/migrate"
+    /// timeout = "5m"
+    ///
+    /// [processes.api.hooks.pre_stop]
+    /// cmd = "/usr/local/bin/deregister"
+    /// args = ["api"]
+    /// ```
+    ///
+    /// Defaults to no hooks.
+    #[serde(default)]
+    hooks: types::Hooks,
 }
 
 #[allow(unused)]
@@ -255,10 +422,16 @@ impl ProcessConfig {
         &self.args
     }
 
-    pub fn processes(&self) -> u8 {
+    pub fn processes(&self) -> u16 {
         self.processes
     }
 
+    /// Changes the number of instances at runtime, see `scale`.
+    pub fn set_processes(&mut self, processes: u16) -> &mut Self {
+        self.processes = processes;
+        self
+    }
+
     pub fn umask(&self) -> u32 {
         self.umask.mask()
     }
@@ -267,6 +440,10 @@ impl ProcessConfig {
         &self.workingdir
     }
 
+    pub fn process_type(&self) -> types::ProcessType {
+        self.process_type
+    }
+
     pub fn autostart(&self) -> bool {
         self.autostart
     }
@@ -275,6 +452,26 @@ impl ProcessConfig {
         &self.autorestart
     }
 
+    pub fn restart_strategy(&self) -> types::RestartStrategy {
+        self.restart_strategy
+    }
+
+    pub fn startlimit(&self) -> Option<types::StartLimit> {
+        self.startlimit
+    }
+
+    pub fn schedule(&self) -> Option<&types::Schedule> {
+        self.schedule.as_ref()
+    }
+
+    pub fn restart_if(&self) -> Option<types::RestartIf> {
+        self.restart_if
+    }
+
+    pub fn watch(&self) -> Option<&types::Watch> {
+        self.watch.as_ref()
+    }
+
     pub fn exitcodes(&self) -> &Vec<i32> {
         &self.exitcodes
     }
@@ -287,10 +484,38 @@ impl ProcessConfig {
         &self.stopsignals
     }
 
-    pub fn stoptime(&self) -> u8 {
+    pub fn stoptime(&self) -> types::Duration {
         self.stoptime
     }
 
+    /// The steps taken to stop the process, from `stop` or its `stopsignals`/`stoptime`
+    /// shorthand, with every `wait` set.
+    pub fn stop_sequence(&self) -> Vec<types::StopStep> {
+        let stoptime = self.stoptime;
+
+        if let Some(stop) = &self.stop {
+            return stop
+                .steps()
+                .iter()
+                .map(|step| types::StopStep::new(step.signal(), step.wait().unwrap_or(stoptime)))
+                .collect();
+        }
+
+        let mut steps = self
+            .stopsignals
+            .iter()
+            .map(|signal| types::StopStep::new(Some(*signal), types::Duration::default()))
+            .collect::<Vec<types::StopStep>>();
+        // The last signal (if any) gets the whole `stoptime`.
+        let last = steps.pop().and_then(|step| step.signal());
+        steps.push(types::StopStep::new(last, stoptime));
+        steps
+    }
+
+    pub fn killmode(&self) -> types::KillMode {
+        self.killmode
+    }
+
     pub fn stdout(&self) -> &Option<WritableFile> {
         &self.stdout
     }
@@ -303,6 +528,121 @@ impl ProcessConfig {
         &self.env
     }
 
+    pub fn sockets(&self) -> &BTreeMap<String, types::SocketConfig> {
+        &self.sockets
+    }
+
+    pub fn hooks(&self) -> &types::Hooks {
+        &self.hooks
+    }
+
+    /// Fields which only affect future decisions (how to stop the process, whether to
+    /// restart it, ...) and can be applied to a running process without restarting it.
+    ///
+    /// `processes` is listed since the instances are added or removed individually.
+    pub const LIVE_FIELDS: &[&str] = &[
+        "processes",
+        "type",
+        "autostart",
+        "autorestart",
+        "restart_strategy",
+        "startlimit",
+        "schedule",
+        "restart_if",
+        "watch",
+        "exitcodes",
+        "healthcheck.retries",
+        "healthcheck.backoff",
+        "stopsignals",
+        "stoptime",
+        "stop",
+        "killmode",
+        "hooks",
+    ];
+
+    /// Whether a change of `field` (as returned by `changed_fields`) requires the
+    /// process to be restarted.
+    pub fn requires_restart(field: &str) -> bool {
+        !Self::LIVE_FIELDS.contains(&field)
+    }
+
+    /// Returns the names of the fields differing between `self` and `other`, as they
+    /// are spelled in the config file.
+    pub fn changed_fields(&self, other: &Self) -> Vec<&'static str> {
+        // Destructured so adding a field to `ProcessConfig` does not compile until it is
+        // compared here as well.
+        let Self {
+            user,
+            cmd,
+            args,
+            processes,
+            umask,
+            workingdir,
+            process_type,
+            autostart,
+            autorestart,
+            restart_strategy,
+            startlimit,
+            schedule,
+            restart_if,
+            watch,
+            exitcodes,
+            healthcheck,
+            stopsignals,
+            stoptime,
+            stop,
+            killmode,
+            stdout,
+            stderr,
+            env,
+            sockets,
+            hooks,
+        } = self;
+
+        let mut changed = vec![];
+        for (field, differs) in [
+            ("user", *user != other.user),
+            ("cmd", *cmd != other.cmd),
+            ("args", *args != other.args),
+            ("processes", *processes != other.processes),
+            ("umask", *umask != other.umask),
+            ("workingdir", *workingdir != other.workingdir),
+            ("type", *process_type != other.process_type),
+            ("autostart", *autostart != other.autostart),
+            ("autorestart", *autorestart != other.autorestart),
+            ("restart_strategy", *restart_strategy != other.restart_strategy),
+            ("startlimit", *startlimit != other.startlimit),
+            ("schedule", *schedule != other.schedule),
+            ("restart_if", *restart_if != other.restart_if),
+            ("watch", *watch != other.watch),
+            ("exitcodes", *exitcodes != other.exitcodes),
+        ] {
+            if differs {
+                changed.push(field);
+            }
+        }
+
+        changed.extend(healthcheck.changed_fields(&other.healthcheck));
+
+        for (field, differs) in [
+            ("stopsignals", *stopsignals != other.stopsignals),
+            ("stoptime", *stoptime != other.stoptime),
+            ("stop", *stop != other.stop),
+            ("killmode", *killmode != other.killmode),
+            ("stdout", *stdout != other.stdout),
+            ("stderr", *stderr != other.stderr),
+            ("env", *env != other.env),
+            ("sockets", *sockets != other.sockets),
+            ("hooks", *hooks != other.hooks),
+        ] {
+            if differs {
+                changed.push(field);
+            }
+        }
+
+        changed
+    }
+
     pub fn set_stdout(&mut self, path: &str) -> &mut Self {
         self.stdout = Some(types::WritableFile::from_path(path));
         self
@@ -332,15 +672,25 @@ impl Default for ProcessConfig {
             processes: 1,
             umask: types::Umask::default(),
             workingdir: types::AccessibleDirectory::default(),
+            process_type: types::ProcessType::default(),
             autostart: true,
             autorestart: types::AutoRestart::default(),
+            restart_strategy: types::RestartStrategy::default(),
+            startlimit: None,
+            schedule: None,
+            restart_if: None,
+            watch: None,
             exitcodes: vec![0],
             healthcheck: HealthCheck::default(),
             stopsignals: vec![types::StopSignal(SIGTERM)],
-            stoptime: 5,
+            stoptime: defaults::dflt_stoptime(),
+            stop: None,
+            killmode: types::KillMode::default(),
             stdout: None,
             stderr: None,
             env: Vec::new(),
+            sockets: BTreeMap::new(),
+            hooks: types::Hooks::default(),
         }
     }
 }
@@ -357,11 +707,6 @@ impl ProcessConfig {
         self
     }
 
-    pub fn set_processes(&mut self, processes: u8) -> &mut Self {
-        self.processes = processes;
-        self
-    }
-
     pub fn set_umask(&mut self, mask: u32) -> &mut Self {
         self.umask = Umask::new(mask);
         self
@@ -382,6 +727,26 @@ impl ProcessConfig {
         self
     }
 
+    pub fn set_process_type(&mut self, process_type: types::ProcessType) -> &mut Self {
+        self.process_type = process_type;
+        self
+    }
+
+    pub fn set_startlimit(&mut self, startlimit: types::StartLimit) -> &mut Self {
+        self.startlimit = Some(startlimit);
+        self
+    }
+
+    pub fn set_restart_if(&mut self, restart_if: types::RestartIf) -> &mut Self {
+        self.restart_if = Some(restart_if);
+        self
+    }
+
+    pub fn set_watch(&mut self, watch: types::Watch) -> &mut Self {
+        self.watch = Some(watch);
+        self
+    }
+
     pub fn set_exitcodes(&mut self, exitcodes: &Vec<i32>) -> &mut Self {
         self.exitcodes = exitcodes.to_owned();
         self
@@ -397,8 +762,38 @@ impl ProcessConfig {
         self
     }
 
-    pub fn set_stoptime(&mut self, stoptime: u8) -> &mut Self {
-        self.stoptime = stoptime;
+    pub fn set_stop(&mut self, stop: types::StopSequence) -> &mut Self {
+        self.stop = Some(stop);
+        self
+    }
+
+    pub fn set_schedule(&mut self, schedule: types::Schedule) -> &mut Self {
+        self.schedule = Some(schedule);
+        self
+    }
+
+    pub fn set_hook(&mut self, kind: types::HookKind, hook: types::Hook) -> &mut Self {
+        self.hooks.set(kind, hook);
+        self
+    }
+
+    pub fn set_socket(&mut self, name: &str, socket: types::SocketConfig) -> &mut Self {
+        self.sockets.insert(name.to_owned(), socket);
+        self
+    }
+
+    pub fn set_restart_strategy(&mut self, restart_strategy: types::RestartStrategy) -> &mut Self {
+        self.restart_strategy = restart_strategy;
+        self
+    }
+
+    pub fn set_killmode(&mut self, killmode: types::KillMode) -> &mut Self {
+        self.killmode = killmode;
+        self
+    }
+
+    pub fn set_stoptime(&mut self, stoptime: u64) -> &mut Self {
+        self.stoptime = types::Duration::from_secs(stoptime);
         self
     }
 
@@ -6,7 +6,7 @@ pub fn dflt_args() -> Vec<String> {
     vec![]
 }
 
-pub fn dflt_processes() -> u8 {
+pub fn dflt_processes() -> u16 {
     1
 }
 
@@ -30,6 +30,14 @@ pub fn dflt_stopsignals() -> Vec<types::StopSignal> {
     vec![types::StopSignal(SIGTERM)]
 }
 
-pub fn dflt_stoptime() -> u8 {
-    5
+pub fn dflt_stoptime() -> types::Duration {
+    types::Duration::from_secs(5)
+}
+
+pub fn dflt_killmode() -> types::KillMode {
+    types::KillMode::default()
+}
+
+pub fn dflt_restart_strategy() -> types::RestartStrategy {
+    types::RestartStrategy::default()
 }
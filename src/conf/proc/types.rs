@@ -1,13 +1,41 @@
 mod autorestart;
+mod backoff;
+mod byte_size;
+mod duration;
 mod healthcheck;
+mod hooks;
+mod killmode;
 mod path;
+mod process_type;
+mod restart_if;
+mod restart_strategy;
+mod schedule;
+mod socket;
+mod startlimit;
+mod stop;
 mod stopsignal;
+mod timezone;
 mod umask;
+mod watch;
 
 pub use self::{
     autorestart::AutoRestart,
+    backoff::Backoff,
+    byte_size::ByteSize,
+    duration::Duration,
     healthcheck::{CommandHealthCheck, HealthCheck, HealthCheckType, UptimeHealthCheck},
+    hooks::{Hook, HookKind, Hooks},
+    killmode::KillMode,
     path::{AccessibleDirectory, ExecutableFile, WritableFile},
+    process_type::ProcessType,
+    restart_if::RestartIf,
+    restart_strategy::RestartStrategy,
+    schedule::{CronExpr, OverlapPolicy, Schedule},
+    socket::{SocketConfig, SocketMode, TcpSocketConfig, UnixSocketConfig},
+    startlimit::StartLimit,
+    stop::{StopSequence, StopStep},
     stopsignal::StopSignal,
+    timezone::TimeZone,
     umask::Umask,
+    watch::Watch,
 };
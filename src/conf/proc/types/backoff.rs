@@ -0,0 +1,207 @@
+use rand::Rng;
+use serde::{Deserialize, Deserializer};
+
+use super::Duration;
+
+fn dflt_multiplier() -> f64 {
+    2.0
+}
+
+fn dflt_reset_after() -> Duration {
+    Duration::from_secs(60)
+}
+
+/// # `Backoff`
+/// `src/conf/proc/types/backoff.rs`
+///
+/// How long to wait before restarting a process which exited unexpectedly, or retrying
+/// a failed healthcheck. Either a fixed delay:
+/// ```toml
+/// backoff = 5
+/// ```
+/// or a delay growing with every consecutive failure:
+/// ```toml
+/// backoff = { initial = "1s", max = "5m", multiplier = 2.0, jitter = 0.2, reset_after = "1m" }
+/// ```
+///
+/// The n-th consecutive failure waits `initial * multiplier^(n - 1)`, capped at `max`.
+/// `jitter` randomly shortens or lengthens each delay by up to that fraction (`0.2`
+/// meaning ±20%, never above `max`), so a group of processes failing together does not
+/// retry in lockstep.
+///
+/// The failure counters are only reset once the process stayed healthy for
+/// `reset_after`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    multiplier: f64,
+    jitter: f64,
+    reset_after: Duration,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExponentialBackoff {
+    initial: Duration,
+    max: Duration,
+    #[serde(default = "dflt_multiplier")]
+    multiplier: f64,
+    #[serde(default)]
+    jitter: f64,
+    #[serde(default = "dflt_reset_after")]
+    reset_after: Duration,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BackoffConfig {
+    Fixed(Duration),
+    Exponential(ExponentialBackoff),
+}
+
+impl Backoff {
+    pub fn fixed(delay: Duration) -> Self {
+        Self {
+            initial: delay,
+            max: delay,
+            multiplier: 1.0,
+            jitter: 0.0,
+            reset_after: dflt_reset_after(),
+        }
+    }
+
+    pub fn initial(&self) -> Duration {
+        self.initial
+    }
+
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    pub fn reset_after(&self) -> Duration {
+        self.reset_after
+    }
+
+    /// Delay before the next attempt after `failures` consecutive failures, without
+    /// jitter.
+    pub fn delay(&self, failures: usize) -> std::time::Duration {
+        let exponent = failures.saturating_sub(1).min(i32::MAX as usize) as i32;
+        let delay = self.initial.get().as_secs_f64() * self.multiplier.powi(exponent);
+        std::time::Duration::from_secs_f64(delay.min(self.max.get().as_secs_f64()))
+    }
+
+    /// [`Backoff::delay`] with `jitter` applied.
+    pub fn jittered_delay(&self, failures: usize) -> std::time::Duration {
+        let delay = self.delay(failures).as_secs_f64();
+        if self.jitter == 0.0 {
+            return std::time::Duration::from_secs_f64(delay);
+        }
+
+        let factor = 1.0 + rand::rng().random_range(-self.jitter..=self.jitter);
+        std::time::Duration::from_secs_f64((delay * factor).clamp(0.0, self.max.get().as_secs_f64()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Backoff {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let exponential = match BackoffConfig::deserialize(deserializer)? {
+            BackoffConfig::Fixed(delay) => return Ok(Self::fixed(delay)),
+            BackoffConfig::Exponential(exponential) => exponential,
+        };
+
+        if exponential.max < exponential.initial {
+            return Err(serde::de::Error::custom(format!(
+                "invalid backoff: max ({}) is shorter than initial ({})",
+                exponential.max, exponential.initial
+            )));
+        }
+        if !(exponential.multiplier >= 1.0 && exponential.multiplier.is_finite()) {
+            return Err(serde::de::Error::custom(format!("invalid backoff multiplier: {}, expected at least 1.0", exponential.multiplier)));
+        }
+        if !(0.0..=1.0).contains(&exponential.jitter) {
+            return Err(serde::de::Error::custom(format!(
+                "invalid backoff jitter: {}, expected a fraction between 0.0 and 1.0",
+                exponential.jitter
+            )));
+        }
+
+        Ok(Self {
+            initial: exponential.initial,
+            max: exponential.max,
+            multiplier: exponential.multiplier,
+            jitter: exponential.jitter,
+            reset_after: exponential.reset_after,
+        })
+    }
+}
+
+#[cfg(test)]
+impl Backoff {
+    pub fn exponential(initial: Duration, max: Duration, multiplier: f64) -> Self {
+        Self {
+            initial,
+            max,
+            multiplier,
+            jitter: 0.0,
+            reset_after: dflt_reset_after(),
+        }
+    }
+
+    pub fn set_reset_after(&mut self, reset_after: Duration) -> &mut Self {
+        self.reset_after = reset_after;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Wrapper {
+        backoff: Backoff,
+    }
+
+    fn parse(toml: &str) -> Result<Backoff, toml::de::Error> {
+        toml::from_str::<Wrapper>(toml).map(|w| w.backoff)
+    }
+
+    #[test]
+    fn fixed() {
+        let backoff = parse("backoff = 5").unwrap();
+        assert_eq!(backoff, Backoff::fixed(Duration::from_secs(5)));
+        assert_eq!(backoff.delay(1), std::time::Duration::from_secs(5));
+        assert_eq!(backoff.jittered_delay(10), std::time::Duration::from_secs(5));
+    }
+
+    #[test]
+    fn exponential() {
+        let backoff = parse(r#"backoff = { initial = "1s", max = "5m", multiplier = 2.0 }"#).unwrap();
+        assert_eq!(backoff.delay(1), std::time::Duration::from_secs(1));
+        assert_eq!(backoff.delay(4), std::time::Duration::from_secs(8));
+        assert_eq!(backoff.delay(100), std::time::Duration::from_secs(300));
+        assert_eq!(backoff.reset_after(), dflt_reset_after());
+    }
+
+    #[test]
+    fn jitter_stays_in_bounds() {
+        let backoff = parse(r#"backoff = { initial = "10s", max = "40s", jitter = 0.2 }"#).unwrap();
+        for failures in 1..5 {
+            let delay = backoff.delay(failures).as_secs_f64();
+            let jittered = backoff.jittered_delay(failures).as_secs_f64();
+            assert!(jittered >= delay * 0.8 && jittered <= (delay * 1.2).min(40.0), "{jittered}");
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(parse(r#"backoff = { initial = "1m", max = "1s" }"#).is_err());
+        assert!(parse(r#"backoff = { initial = "1s", max = "1m", multiplier = 0.5 }"#).is_err());
+        assert!(parse(r#"backoff = { initial = "1s", max = "1m", jitter = 1.5 }"#).is_err());
+        assert!(parse(r#"backoff = { initial = "1s" }"#).is_err());
+    }
+}
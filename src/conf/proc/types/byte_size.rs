@@ -0,0 +1,115 @@
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, de};
+
+/// # `ByteSize`
+/// `src/conf/proc/types/byte_size.rs`
+///
+/// An amount of memory, either as a plain number of bytes or as a string with a
+/// decimal (`kB`, `MB`, `GB`, `TB`) or binary (`KiB`, `MiB`, `GiB`, `TiB`) unit:
+/// ```toml
+/// rss_above = 1073741824
+/// rss_above = "512MiB"
+/// rss_above = "2GB"
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ByteSize {
+    bytes: u64,
+}
+
+impl ByteSize {
+    pub const fn from_bytes(bytes: u64) -> Self {
+        Self { bytes }
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}B", self.bytes)
+    }
+}
+
+impl TryFrom<&str> for ByteSize {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let invalid = || format!("invalid size: '{value}', expected e.g. \"1048576\", \"512MiB\" or \"2GB\"");
+
+        let value = value.trim();
+        let digits = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+        let number = value[..digits].parse::<u64>().map_err(|_| invalid())?;
+        let factor: u64 = match value[digits..].trim_start() {
+            "" | "B" => 1,
+            "kB" | "KB" => 1_000,
+            "MB" => 1_000_000,
+            "GB" => 1_000_000_000,
+            "TB" => 1_000_000_000_000,
+            "KiB" => 1 << 10,
+            "MiB" => 1 << 20,
+            "GiB" => 1 << 30,
+            "TiB" => 1 << 40,
+            _ => return Err(invalid()),
+        };
+
+        number
+            .checked_mul(factor)
+            .map(Self::from_bytes)
+            .ok_or_else(|| format!("size out of range: '{value}'"))
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteSize {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ByteSizeVisitor;
+
+        impl de::Visitor<'_> for ByteSizeVisitor {
+            type Value = ByteSize;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a number of bytes or a size string like \"512MiB\"")
+            }
+
+            fn visit_u64<E: de::Error>(self, bytes: u64) -> Result<ByteSize, E> {
+                Ok(ByteSize::from_bytes(bytes))
+            }
+
+            fn visit_i64<E: de::Error>(self, bytes: i64) -> Result<ByteSize, E> {
+                match u64::try_from(bytes) {
+                    Ok(bytes) => Ok(ByteSize::from_bytes(bytes)),
+                    Err(_) => Err(E::custom(format!("invalid size: {bytes}, expected a positive number of bytes"))),
+                }
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<ByteSize, E> {
+                ByteSize::try_from(s).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(ByteSizeVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(ByteSize::try_from("1024"), Ok(ByteSize::from_bytes(1024)));
+        assert_eq!(ByteSize::try_from("512MiB"), Ok(ByteSize::from_bytes(512 << 20)));
+        assert_eq!(ByteSize::try_from("2GiB"), Ok(ByteSize::from_bytes(2 << 30)));
+        assert_eq!(ByteSize::try_from("2GB"), Ok(ByteSize::from_bytes(2_000_000_000)));
+        assert_eq!(ByteSize::try_from("100 kB"), Ok(ByteSize::from_bytes(100_000)));
+
+        for invalid in ["", "MiB", "1.5GiB", "-1", "10XB", "99999999999TiB"] {
+            assert!(ByteSize::try_from(invalid).is_err(), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, de};
+
+const MAX_SECS: u64 = 100 * 365 * 86_400;
+
+/// # `Duration`
+/// `src/conf/proc/types/duration.rs`
+///
+/// A span of time, either as a plain number of seconds or as a string made of
+/// `<number><unit>` pairs, with units `ms`, `s`, `m`, `h` and `d`:
+/// ```toml
+/// stoptime = 10
+/// stoptime = "250ms"
+/// stoptime = "1m30s"
+/// ```
+///
+/// At most 100 years, so adding a duration to an `Instant` cannot overflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Duration {
+    duration: std::time::Duration,
+}
+
+impl Duration {
+    pub const fn from_secs(secs: u64) -> Self {
+        Self {
+            duration: std::time::Duration::from_secs(secs),
+        }
+    }
+
+    pub const fn from_millis(millis: u64) -> Self {
+        Self {
+            duration: std::time::Duration::from_millis(millis),
+        }
+    }
+
+    /// `None` above the maximum of 100 years.
+    fn checked_from_secs(secs: u64) -> Option<Self> {
+        (secs <= MAX_SECS).then(|| Self::from_secs(secs))
+    }
+
+    pub fn get(&self) -> std::time::Duration {
+        self.duration
+    }
+}
+
+impl From<Duration> for std::time::Duration {
+    fn from(duration: Duration) -> Self {
+        duration.duration
+    }
+}
+
+impl TryFrom<&str> for Duration {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let invalid = || format!("invalid duration: '{value}', expected e.g. \"10\", \"250ms\" or \"1m30s\"");
+        let out_of_range = || format!("duration out of range: '{value}', expected at most 100 years");
+
+        if let Ok(secs) = value.parse::<u64>() {
+            return Self::checked_from_secs(secs).ok_or_else(out_of_range);
+        }
+
+        let mut millis: u64 = 0;
+        let mut rest = value.trim();
+        if rest.is_empty() {
+            return Err(invalid());
+        }
+        while !rest.is_empty() {
+            let digits = rest.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+            let number = rest[..digits].parse::<u64>().map_err(|_| invalid())?;
+            rest = &rest[digits..];
+
+            let unit = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+            let factor = match &rest[..unit] {
+                "ms" => 1,
+                "s" => 1_000,
+                "m" => 60_000,
+                "h" => 3_600_000,
+                "d" => 86_400_000,
+                _ => return Err(invalid()),
+            };
+            rest = &rest[unit..];
+
+            millis = number
+                .checked_mul(factor)
+                .and_then(|part| millis.checked_add(part))
+                .filter(|millis| *millis <= MAX_SECS * 1_000)
+                .ok_or_else(out_of_range)?;
+        }
+
+        Ok(Self::from_millis(millis))
+    }
+}
+
+impl<'de> Deserialize<'de> for Duration {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct DurationVisitor;
+
+        impl de::Visitor<'_> for DurationVisitor {
+            type Value = Duration;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a number of seconds or a duration string like \"1m30s\"")
+            }
+
+            fn visit_u64<E: de::Error>(self, secs: u64) -> Result<Duration, E> {
+                Duration::checked_from_secs(secs).ok_or_else(|| E::custom(format!("duration out of range: {secs}, expected at most 100 years")))
+            }
+
+            fn visit_i64<E: de::Error>(self, secs: i64) -> Result<Duration, E> {
+                match u64::try_from(secs) {
+                    Ok(secs) => self.visit_u64(secs),
+                    Err(_) => Err(E::custom(format!("invalid duration: {secs}, expected a positive number of seconds"))),
+                }
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Duration, E> {
+                Duration::try_from(s).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(DurationVisitor)
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Duration::try_from("10"), Ok(Duration::from_secs(10)));
+        assert_eq!(Duration::try_from("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(Duration::try_from("1m30s"), Ok(Duration::from_secs(90)));
+        assert_eq!(Duration::try_from("2h"), Ok(Duration::from_secs(7200)));
+        assert_eq!(Duration::try_from("1d1ms"), Ok(Duration::from_millis(86_400_001)));
+
+        for invalid in ["", "s", "10x", "1.5s", "-1s", "1m 30s", "m1"] {
+            assert!(Duration::try_from(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn out_of_range() {
+        assert_eq!(Duration::try_from("36500d"), Ok(Duration::from_secs(MAX_SECS)));
+        for invalid in ["36500d1ms", "3153600001", "18446744073709551615", "213503982334601d"] {
+            assert!(Duration::try_from(invalid).is_err(), "{invalid}");
+        }
+
+        assert!(Duration::deserialize(toml::Value::Integer(3_153_600_000)).is_ok());
+        assert!(Duration::deserialize(toml::Value::Integer(i64::MAX)).is_err());
+    }
+}
@@ -1,11 +1,13 @@
 use serde::Deserialize;
 
-fn dflt_timeout() -> usize {
-    10
+use super::{Backoff, Duration};
+
+fn dflt_timeout() -> Duration {
+    Duration::from_secs(10)
 }
 
-fn dflt_backoff() -> usize {
-    5
+fn dflt_backoff() -> Backoff {
+    Backoff::fixed(Duration::from_secs(5))
 }
 
 fn dflt_retries() -> usize {
@@ -44,27 +46,27 @@ pub struct CommandHealthCheck {
     #[serde(default)]
     pub args: Vec<String>,
 
-    /// Time (in seconds) to wait for the healthcheck to exit.
+    /// Time to wait for the healthcheck to exit, see [`Duration`].
     ///
     /// ```toml
     /// cmd = "/usr/bin/ping"
-    /// timeout = 5
+    /// timeout = "2500ms"
     /// ```
     ///
     /// Defaults to `10`.
     #[serde(default = "dflt_timeout")]
-    pub timeout: usize,
+    pub timeout: Duration,
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct UptimeHealthCheck {
-    /// Time (in seconds) after which the process will be deemed healthy.
+    /// Time after which the process will be deemed healthy, see [`Duration`].
     ///
-    /// starttime = 10
+    /// starttime = "1m30s"
     ///
-    /// Defaults to `1`, max `65536`.
-    pub starttime: u16,
+    /// Defaults to `1`.
+    pub starttime: Duration,
 }
 
 #[allow(unused)]
@@ -77,12 +79,12 @@ pub struct HealthCheck {
     /// ```toml
     /// cmd = <string>
     /// args = <<string>>
-    /// timeout = <int>
+    /// timeout = <duration>
     /// ```
-    /// - Uptime: Consider healthy after running for `startttime` seconds.
+    /// - Uptime: Consider healthy after running for `startttime`.
     ///
     /// ```toml
-    /// starttime = <int>
+    /// starttime = <duration>
     /// ```
     #[serde(flatten)]
     check: HealthCheckType,
@@ -100,23 +102,27 @@ pub struct HealthCheck {
     #[serde(default = "dflt_retries")]
     retries: usize,
 
-    /// Time (in seconds) to wait after a failed healthcheck before retrying.
+    /// Time to wait after a failed healthcheck before retrying, and after the process
+    /// exited unexpectedly before restarting it. Fixed, or growing with each consecutive
+    /// failure, see [`Backoff`].
     ///
     /// ```toml
     /// [processes.nginx.healthcheck]
     /// starttime = 3
-    /// backoff = 3
+    /// backoff = { initial = "1s", max = "5m", multiplier = 2.0, jitter = 0.2 }
     /// ```
     ///
     /// Defaults to `5`.
     #[serde(default = "dflt_backoff")]
-    backoff: usize,
+    backoff: Backoff,
 }
 
 impl Default for HealthCheck {
     fn default() -> Self {
         Self {
-            check: HealthCheckType::Uptime(UptimeHealthCheck { starttime: 1 }),
+            check: HealthCheckType::Uptime(UptimeHealthCheck {
+                starttime: Duration::from_secs(1),
+            }),
             retries: dflt_retries(),
             backoff: dflt_backoff(),
         }
@@ -142,14 +148,14 @@ impl HealthCheck {
         }
     }
 
-    pub fn starttime(&self) -> u16 {
+    pub fn starttime(&self) -> Duration {
         match &self.check {
             HealthCheckType::Uptime(UptimeHealthCheck { starttime }) => *starttime,
             _ => panic!("starttime() called on a Command HealthCheck"),
         }
     }
 
-    pub fn timeout(&self) -> usize {
+    pub fn timeout(&self) -> Duration {
         match &self.check {
             HealthCheckType::Command(CommandHealthCheck { cmd: _, args: _, timeout }) => *timeout,
             _ => panic!("timeout() called on an Uptime HealthCheck"),
@@ -160,8 +166,44 @@ impl HealthCheck {
         self.retries
     }
 
-    pub fn backoff(&self) -> usize {
-        self.backoff
+    pub fn backoff(&self) -> &Backoff {
+        &self.backoff
+    }
+
+    /// Returns the names of the fields differing between `self` and `other`, prefixed
+    /// with `healthcheck.`. Switching between a command and an uptime healthcheck is
+    /// reported as a change of `healthcheck` as a whole.
+    pub fn changed_fields(&self, other: &Self) -> Vec<&'static str> {
+        let mut changed = vec![];
+
+        match (&self.check, &other.check) {
+            (HealthCheckType::Command(old), HealthCheckType::Command(new)) => {
+                if old.cmd != new.cmd {
+                    changed.push("healthcheck.cmd");
+                }
+                if old.args != new.args {
+                    changed.push("healthcheck.args");
+                }
+                if old.timeout != new.timeout {
+                    changed.push("healthcheck.timeout");
+                }
+            }
+            (HealthCheckType::Uptime(old), HealthCheckType::Uptime(new)) => {
+                if old.starttime != new.starttime {
+                    changed.push("healthcheck.starttime");
+                }
+            }
+            _ => changed.push("healthcheck"),
+        }
+
+        if self.retries != other.retries {
+            changed.push("healthcheck.retries");
+        }
+        if self.backoff != other.backoff {
+            changed.push("healthcheck.backoff");
+        }
+
+        changed
     }
 }
 
@@ -177,7 +219,7 @@ impl HealthCheck {
     }
 
     pub fn set_backoff(&mut self, backoff: usize) -> &mut Self {
-        self.backoff = backoff;
+        self.backoff = Backoff::fixed(Duration::from_secs(backoff as u64));
         self
     }
 
@@ -185,6 +227,11 @@ impl HealthCheck {
         self.retries = retries;
         self
     }
+
+    pub fn set_backoff_policy(&mut self, backoff: Backoff) -> &mut Self {
+        self.backoff = backoff;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -198,18 +245,20 @@ mod tests {
                 check: HealthCheckType::Command(CommandHealthCheck {
                     cmd: "/usr/bin/echo".to_string(),
                     args: Vec::new(),
-                    timeout: 5,
+                    timeout: Duration::from_secs(5),
                 }),
                 retries: 5,
-                backoff: 5,
+                backoff: dflt_backoff(),
             }
         }
 
         pub fn uptime() -> Self {
             Self {
-                check: HealthCheckType::Uptime(UptimeHealthCheck { starttime: 1 }),
+                check: HealthCheckType::Uptime(UptimeHealthCheck {
+                    starttime: Duration::from_secs(1),
+                }),
                 retries: 5,
-                backoff: 5,
+                backoff: dflt_backoff(),
             }
         }
     }
@@ -229,7 +278,7 @@ mod tests {
     #[test]
     fn backoff() {
         let hc = HealthCheck::command();
-        assert_eq!(hc.backoff(), 5);
+        assert_eq!(hc.backoff().delay(1), std::time::Duration::from_secs(5));
     }
 
     #[test]
@@ -241,13 +290,13 @@ mod tests {
     #[test]
     fn timeout_on_command_healthcheck() {
         let hc = HealthCheck::command();
-        assert_eq!(hc.timeout(), 5);
+        assert_eq!(hc.timeout(), Duration::from_secs(5));
     }
 
     #[test]
     fn starttime_on_uptime_healthcheck() {
         let hc = HealthCheck::uptime();
-        assert_eq!(hc.starttime(), 1);
+        assert_eq!(hc.starttime(), Duration::from_secs(1));
     }
 
     #[test]
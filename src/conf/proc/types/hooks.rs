@@ -0,0 +1,147 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+use super::{Duration, ExecutableFile};
+
+fn dflt_hook_timeout() -> Duration {
+    Duration::from_secs(30)
+}
+
+/// # `HookKind`
+/// `src/conf/proc/types/hooks.rs`
+///
+/// The transitions of a process hooks can be run at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookKind {
+    /// Before spawning the process, it is only spawned if the hook succeeds.
+    PreStart,
+    /// Once the process became `Healthy`.
+    PostStart,
+    /// Before sending the first stop signal, which waits for the hook to exit.
+    PreStop,
+    /// Once the process is `Stopped` or `Completed`.
+    PostStop,
+}
+
+impl fmt::Display for HookKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HookKind::PreStart => write!(f, "pre_start"),
+            HookKind::PostStart => write!(f, "post_start"),
+            HookKind::PreStop => write!(f, "pre_stop"),
+            HookKind::PostStop => write!(f, "post_stop"),
+        }
+    }
+}
+
+/// # `Hook`
+/// `src/conf/proc/types/hooks.rs`
+///
+/// Command run at a transition of the process, from its `workingdir`:
+/// ```toml
+/// [processes.api.hooks.pre_start]
+/// cmd = "/usr/local/bin/migrate"
+/// args = ["--database", "api"]
+/// timeout = "5m"
+/// user = "api"
+/// env = [["DATABASE_URL", "postgres://localhost/api"]]
+/// ```
+///
+/// Besides `env`, the hook gets `TASKMASTER_HOOK`, `TASKMASTER_PROCESS_NAME`, and
+/// `TASKMASTER_PID`/`TASKMASTER_EXIT_CODE` when they are known. It is killed once
+/// `timeout` (defaults to `30`) passed, which counts as a failure.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Hook {
+    cmd: ExecutableFile,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default = "dflt_hook_timeout")]
+    timeout: Duration,
+    #[serde(default)]
+    user: Option<String>,
+    #[serde(default)]
+    env: Vec<(String, String)>,
+}
+
+impl Hook {
+    pub fn cmd(&self) -> &ExecutableFile {
+        &self.cmd
+    }
+
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    pub fn env(&self) -> &[(String, String)] {
+        &self.env
+    }
+}
+
+#[cfg(test)]
+impl Hook {
+    pub fn new(cmd: &str, args: Vec<String>) -> Self {
+        Self {
+            cmd: ExecutableFile::new(cmd),
+            args,
+            timeout: dflt_hook_timeout(),
+            user: None,
+            env: Vec::new(),
+        }
+    }
+
+    pub fn set_env(&mut self, env: Vec<(String, String)>) -> &mut Self {
+        self.env = env;
+        self
+    }
+}
+
+/// # `Hooks`
+/// `src/conf/proc/types/hooks.rs`
+///
+/// The [`Hook`]s configured for a process, see [`HookKind`] for when they run.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Hooks {
+    #[serde(default)]
+    pre_start: Option<Hook>,
+    #[serde(default)]
+    post_start: Option<Hook>,
+    #[serde(default)]
+    pre_stop: Option<Hook>,
+    #[serde(default)]
+    post_stop: Option<Hook>,
+}
+
+impl Hooks {
+    pub fn get(&self, kind: HookKind) -> Option<&Hook> {
+        match kind {
+            HookKind::PreStart => self.pre_start.as_ref(),
+            HookKind::PostStart => self.post_start.as_ref(),
+            HookKind::PreStop => self.pre_stop.as_ref(),
+            HookKind::PostStop => self.post_stop.as_ref(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl Hooks {
+    pub fn set(&mut self, kind: HookKind, hook: Hook) -> &mut Self {
+        match kind {
+            HookKind::PreStart => self.pre_start = Some(hook),
+            HookKind::PostStart => self.post_start = Some(hook),
+            HookKind::PreStop => self.pre_stop = Some(hook),
+            HookKind::PostStop => self.post_stop = Some(hook),
+        }
+        self
+    }
+}
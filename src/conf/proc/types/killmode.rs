@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// # `KillMode`
+/// `src/conf/proc/types/killmode.rs`
+///
+/// Which processes receive the `stopsignals` (and `SIGKILL` once `stoptime` passed)
+/// when stopping a process. Every process is started in its own process group.
+/// - `process`: Only the process started by taskmaster.
+/// - `group`: Every member of the process group, e.g. the workers of a shell wrapper.
+/// - `tree`: The process group, plus every descendant, including ones which moved to
+///   another group or got orphaned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KillMode {
+    Process,
+    #[default]
+    Group,
+    Tree,
+}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// # `ProcessType`
+/// `src/conf/proc/types/process_type.rs`
+///
+/// What the process is expected to do once started.
+/// - `service`: Keep running until stopped.
+/// - `oneshot`: Run to completion, e.g. a migration or a `schedule`d job. Its last runs
+///   are kept (see `history`), and it can be run with `run`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessType {
+    #[default]
+    Service,
+    Oneshot,
+}
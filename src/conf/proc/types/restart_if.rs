@@ -0,0 +1,67 @@
+use serde::Deserialize;
+
+use super::{ByteSize, Duration};
+
+/// # `RestartIf`
+/// `src/conf/proc/types/restart_if.rs`
+///
+/// Resource limits for a running process, checked against the usage sampled from
+/// `/proc` (summed over the process and its descendants). A process exceeding one of
+/// them for longer than `for` is restarted gracefully, as with `restart`.
+/// ```toml
+/// restart_if = { rss_above = "2GiB", cpu_above = 90.0, fds_above = 1000, for = "2m", max_lifetime = "24h" }
+/// ```
+///
+/// `cpu_above` is a percentage of a single core, above 100 when more than one core is
+/// used. `max_lifetime` restarts the process once it has been up that long, regardless
+/// of its usage. Only healthy processes are checked.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RestartIf {
+    #[serde(default)]
+    rss_above: Option<ByteSize>,
+    #[serde(default)]
+    cpu_above: Option<f64>,
+    #[serde(default)]
+    fds_above: Option<u64>,
+    #[serde(default, rename = "for")]
+    for_: Duration,
+    #[serde(default)]
+    max_lifetime: Option<Duration>,
+}
+
+impl RestartIf {
+    pub fn rss_above(&self) -> Option<ByteSize> {
+        self.rss_above
+    }
+
+    pub fn cpu_above(&self) -> Option<f64> {
+        self.cpu_above
+    }
+
+    pub fn fds_above(&self) -> Option<u64> {
+        self.fds_above
+    }
+
+    /// How long a limit has to be exceeded before the process is restarted.
+    pub fn for_(&self) -> Duration {
+        self.for_
+    }
+
+    pub fn max_lifetime(&self) -> Option<Duration> {
+        self.max_lifetime
+    }
+}
+
+#[cfg(test)]
+impl RestartIf {
+    pub fn set_fds_above(&mut self, fds_above: u64) -> &mut Self {
+        self.fds_above = Some(fds_above);
+        self
+    }
+
+    pub fn set_max_lifetime(&mut self, max_lifetime: Duration) -> &mut Self {
+        self.max_lifetime = Some(max_lifetime);
+        self
+    }
+}
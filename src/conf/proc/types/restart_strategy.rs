@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// # `RestartStrategy`
+/// `src/conf/proc/types/restart_strategy.rs`
+///
+/// How a running process is replaced on `restart`.
+/// - `stop-first`: Stop the running instance, then start a new one.
+/// - `start-first`: Start a new instance next to the running one and only stop the old
+///   one once the new one is healthy. If the new one fails, the old one keeps running.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartStrategy {
+    #[default]
+    StopFirst,
+    StartFirst,
+}
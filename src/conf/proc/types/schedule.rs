@@ -0,0 +1,471 @@
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{
+    Deserialize, Deserializer,
+    de::{self, MapAccess, Visitor, value::MapAccessDeserializer},
+};
+
+use super::{
+    TimeZone,
+    timezone::{civil_from_days, days_from_civil},
+};
+
+const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// # `CronExpr`
+/// `src/conf/proc/types/schedule.rs`
+///
+/// Standard 5-field cron expression (`minute hour day-of-month month day-of-week`),
+/// supporting `*`, ranges (`1-5`), lists (`1,15`), steps (`*/15`, `0-30/10`), month and
+/// weekday names (`jan`, `mon`), and the `@hourly`, `@daily`, `@weekly`, `@monthly`
+/// and `@yearly` shorthands.
+///
+/// Like in cron, a day matches if either the day of month or the day of week matches
+/// when both are restricted.
+///
+/// Around daylight saving time changes, it also behaves like cron: runs due in the local
+/// times skipped when the clocks go forward are started right after the change, and
+/// the local times repeated when they go back only match again if the hour is not
+/// fixed, so `*/15 * * * *` keeps running every 15 minutes while `30 2 * * *` runs once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronExpr {
+    source: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    hours_restricted: bool,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl CronExpr {
+    /// Unix timestamp of the first matching minute after `after`, in `timezone`.
+    /// `None` if nothing matches within the next five years, e.g. for `0 0 31 2 *`.
+    pub fn next_after(&self, after: i64, timezone: &TimeZone) -> Option<i64> {
+        let mut utc = after.div_euclid(60) * 60 + 60;
+        let limit = utc + 5 * 366 * 86_400;
+
+        while utc < limit {
+            let local = utc + timezone.offset_at(utc) as i64;
+            let skipped = local - (utc - 60 + timezone.offset_at(utc - 60) as i64) - 60;
+            if skipped > 0 && (local - skipped..local).step_by(60).any(|local| self.skip(local).is_none()) {
+                return Some(utc);
+            }
+
+            match self.skip(local) {
+                None if self.hours_restricted && timezone.to_utc(local) < utc => utc += 60,
+                None => return Some(utc),
+                // `to_utc` maps to the first occurrence, which would jump over the
+                // repeated local times right after the clocks go back.
+                Some(next) => utc = timezone.to_utc(next).min(utc + next - local).max(utc + 60),
+            }
+        }
+        None
+    }
+
+    /// Next local time which could match after `local`, skipping the rest of the month,
+    /// day or hour if those do not match. `None` if `local` matches.
+    fn skip(&self, local: i64) -> Option<i64> {
+        let days = local.div_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+        if !matches(self.months, month) {
+            return Some(
+                match month {
+                    12 => days_from_civil(year + 1, 1, 1),
+                    _ => days_from_civil(year, month + 1, 1),
+                } * 86_400,
+            );
+        }
+        if !self.day_matches(day, (days + 4).rem_euclid(7) as u32) {
+            return Some((days + 1) * 86_400);
+        }
+        let secs = local.rem_euclid(86_400);
+        if !matches(self.hours, (secs / 3600) as u32) {
+            return Some(days * 86_400 + (secs / 3600 + 1) * 3600);
+        }
+        if !matches(self.minutes, (secs % 3600 / 60) as u32) {
+            return Some(local.div_euclid(60) * 60 + 60);
+        }
+        None
+    }
+
+    fn day_matches(&self, day: u32, weekday: u32) -> bool {
+        let day = matches(self.days, day);
+        let weekday = matches(self.weekdays, weekday);
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+}
+
+fn matches(field: u64, value: u32) -> bool {
+    field & (1 << value) != 0
+}
+
+/// Parses one field of a cron expression into a bit set of the matching values.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let value = |s: &str| -> Result<u32, String> {
+        let value = match names.iter().position(|name| name.eq_ignore_ascii_case(s)) {
+            Some(i) => i as u32 + min,
+            None => s.parse::<u32>().map_err(|_| format!("invalid value '{s}'"))?,
+        };
+        match (min..=max).contains(&value) {
+            true => Ok(value),
+            false => Err(format!("{value} is out of range {min}-{max}")),
+        }
+    };
+
+    let mut bits = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<u32>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("invalid step '{step}'"))?,
+            ),
+            None => (part, 1),
+        };
+        let (first, last) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((first, last)) => (value(first)?, value(last)?),
+            None if step > 1 => (value(range)?, max),
+            None => (value(range)?, value(range)?),
+        };
+        if first > last {
+            return Err(format!("invalid range '{range}'"));
+        }
+        for value in (first..=last).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+impl TryFrom<&str> for CronExpr {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let expanded = match value.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields = expanded.split_whitespace().collect::<Vec<&str>>();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(format!("invalid cron expression '{value}': expected 5 fields (minute hour day-of-month month day-of-week)"));
+        };
+        let invalid = |field: &str, e: String| format!("invalid cron expression '{value}': {field}: {e}");
+
+        let mut weekday_bits = parse_field(weekdays, 0, 7, &WEEKDAYS).map_err(|e| invalid("day of week", e))?;
+        // Both 0 and 7 are sunday.
+        if weekday_bits & (1 << 7) != 0 {
+            weekday_bits = (weekday_bits | 1) & !(1 << 7);
+        }
+
+        let cron = Self {
+            source: value.to_string(),
+            minutes: parse_field(minutes, 0, 59, &[]).map_err(|e| invalid("minute", e))?,
+            hours: parse_field(hours, 0, 23, &[]).map_err(|e| invalid("hour", e))?,
+            days: parse_field(days, 1, 31, &[]).map_err(|e| invalid("day of month", e))?,
+            months: parse_field(months, 1, 12, &MONTHS).map_err(|e| invalid("month", e))?,
+            weekdays: weekday_bits,
+            hours_restricted: !hours.starts_with('*'),
+            days_restricted: !days.starts_with('*'),
+            weekdays_restricted: !weekdays.starts_with('*'),
+        };
+
+        match cron.next_after(0, &TimeZone::utc()) {
+            Some(_) => Ok(cron),
+            None => Err(format!("invalid cron expression '{value}': never matches")),
+        }
+    }
+}
+
+impl fmt::Display for CronExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for CronExpr {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        CronExpr::try_from(s.as_str()).map_err(de::Error::custom)
+    }
+}
+
+/// # `OverlapPolicy`
+/// `src/conf/proc/types/schedule.rs`
+///
+/// What to do when a scheduled run is due while the previous one is still running.
+/// - `skip`: Leave the running one alone and drop the new run.
+/// - `queue`: Start the new run once the running one finished. At most one run is
+///   queued.
+/// - `replace`: Stop the running one and start the new run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlapPolicy {
+    #[default]
+    Skip,
+    Queue,
+    Replace,
+}
+
+fn dflt_timezone() -> TimeZone {
+    TimeZone::load("local").unwrap_or_else(|_| TimeZone::utc())
+}
+
+/// # `Schedule`
+/// `src/conf/proc/types/schedule.rs`
+///
+/// Runs a process to completion periodically, either as a plain [`CronExpr`] or as a
+/// table:
+/// ```toml
+/// schedule = "*/15 * * * *"
+/// schedule = { cron = "0 3 * * *", timezone = "Europe/Berlin", overlap = "queue", catch_up = true }
+/// ```
+///
+/// `timezone` defaults to the host's, `overlap` to `skip` (see [`OverlapPolicy`]). Runs
+/// missed while taskmaster was not running (or could not keep up) are dropped, unless
+/// `catch_up` is set, in which case they are made up for with a single run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    cron: CronExpr,
+    timezone: TimeZone,
+    overlap: OverlapPolicy,
+    catch_up: bool,
+}
+
+impl Schedule {
+    pub fn cron(&self) -> &CronExpr {
+        &self.cron
+    }
+
+    pub fn timezone(&self) -> &TimeZone {
+        &self.timezone
+    }
+
+    pub fn overlap(&self) -> OverlapPolicy {
+        self.overlap
+    }
+
+    pub fn catch_up(&self) -> bool {
+        self.catch_up
+    }
+
+    /// First scheduled run after `after`.
+    pub fn next_after(&self, after: SystemTime) -> Option<SystemTime> {
+        let after = match after.duration_since(UNIX_EPOCH) {
+            Ok(since_epoch) => since_epoch.as_secs() as i64,
+            Err(_) => 0,
+        };
+        let next = self.cron.next_after(after, &self.timezone)?;
+        Some(UNIX_EPOCH + std::time::Duration::from_secs(next.max(0) as u64))
+    }
+
+    /// Formats `time` in the schedule's time zone.
+    pub fn format(&self, time: SystemTime) -> String {
+        let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or_default();
+        self.timezone.format(secs)
+    }
+}
+
+#[cfg(test)]
+impl Schedule {
+    pub fn new(cron: &str, timezone: TimeZone, overlap: OverlapPolicy) -> Self {
+        Self {
+            cron: CronExpr::try_from(cron).unwrap(),
+            timezone,
+            overlap,
+            catch_up: false,
+        }
+    }
+
+    pub fn set_catch_up(&mut self, catch_up: bool) -> &mut Self {
+        self.catch_up = catch_up;
+        self
+    }
+}
+
+impl<'de> Deserialize<'de> for Schedule {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Table {
+            cron: CronExpr,
+            #[serde(default = "dflt_timezone")]
+            timezone: TimeZone,
+            #[serde(default)]
+            overlap: OverlapPolicy,
+            #[serde(default)]
+            catch_up: bool,
+        }
+
+        struct ScheduleVisitor;
+
+        impl<'de> Visitor<'de> for ScheduleVisitor {
+            type Value = Schedule;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a cron expression or a table with `cron`, `timezone`, `overlap` and `catch_up`")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Schedule, E> {
+                Ok(Schedule {
+                    cron: CronExpr::try_from(v).map_err(E::custom)?,
+                    timezone: dflt_timezone(),
+                    overlap: OverlapPolicy::default(),
+                    catch_up: false,
+                })
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Schedule, A::Error> {
+                let table = Table::deserialize(MapAccessDeserializer::new(map))?;
+                Ok(Schedule {
+                    cron: table.cron,
+                    timezone: table.timezone,
+                    overlap: table.overlap,
+                    catch_up: table.catch_up,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(ScheduleVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i64, month: u32, day: u32, hour: i64, minute: i64) -> i64 {
+        days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60
+    }
+
+    fn next(cron: &str, after: i64) -> i64 {
+        CronExpr::try_from(cron).unwrap().next_after(after, &TimeZone::utc()).unwrap()
+    }
+
+    #[test]
+    fn next_run() {
+        let now = at(2025, 6, 10, 12, 7); // a tuesday
+        assert_eq!(next("*/15 * * * *", now), at(2025, 6, 10, 12, 15));
+        assert_eq!(next("*/15 * * * *", at(2025, 6, 10, 12, 15)), at(2025, 6, 10, 12, 30));
+        assert_eq!(next("0 3 * * *", now), at(2025, 6, 11, 3, 0));
+        assert_eq!(next("30 9 * * mon-fri", at(2025, 6, 13, 10, 0)), at(2025, 6, 16, 9, 30));
+        assert_eq!(next("0 0 1 jan *", now), at(2026, 1, 1, 0, 0));
+        assert_eq!(next("@monthly", now), at(2025, 7, 1, 0, 0));
+        assert_eq!(next("0 0 29 2 *", now), at(2028, 2, 29, 0, 0));
+        // Day of month or day of week once both are restricted.
+        assert_eq!(next("0 0 13 * 5", now), at(2025, 6, 13, 0, 0));
+        assert_eq!(next("0 0 13 * 5", at(2025, 6, 13, 0, 0)), at(2025, 6, 20, 0, 0));
+        assert_eq!(next("0 0 1 * 7", now), at(2025, 6, 15, 0, 0));
+        assert_eq!(next("0 0 1 * 7", at(2025, 6, 29, 0, 0)), at(2025, 7, 1, 0, 0));
+        // A field starting with `*` does not count as restricted, so both have to match.
+        assert_eq!(next("0 0 */2 * 5", at(2025, 6, 13, 0, 0)), at(2025, 6, 27, 0, 0));
+        assert_eq!(next("0 0 1 * *", now), at(2025, 7, 1, 0, 0));
+        assert_eq!(next("0 0 * * 5", now), at(2025, 6, 13, 0, 0));
+    }
+
+    #[test]
+    fn timezone() {
+        let tz = TimeZone::load("Europe/Berlin").unwrap();
+        let cron = CronExpr::try_from("0 3 * * *").unwrap();
+        assert_eq!(cron.next_after(at(2025, 6, 10, 12, 0), &tz), Some(at(2025, 6, 11, 1, 0)));
+        assert_eq!(cron.next_after(at(2025, 12, 10, 12, 0), &tz), Some(at(2025, 12, 11, 2, 0)));
+    }
+
+    fn runs(cron: &str, tz: &TimeZone, after: i64, count: usize) -> Vec<i64> {
+        let cron = CronExpr::try_from(cron).unwrap();
+        std::iter::successors(cron.next_after(after, tz), |run| cron.next_after(*run, tz))
+            .take(count)
+            .collect()
+    }
+
+    #[test]
+    fn clocks_go_forward() {
+        // 2025-03-30 01:00 UTC, Berlin goes from 02:00 to 03:00 local time.
+        let tz = TimeZone::load("Europe/Berlin").unwrap();
+
+        // 02:30 does not exist, the run starts right after the change instead.
+        assert_eq!(runs("30 2 * * *", &tz, at(2025, 3, 29, 12, 0), 2), [at(2025, 3, 30, 1, 0), at(2025, 3, 31, 0, 30)]);
+        // The 02:00 and 03:00 runs fall on the same instant and only run once.
+        assert_eq!(
+            runs("0 * * * *", &tz, at(2025, 3, 29, 23, 30), 3),
+            [at(2025, 3, 30, 0, 0), at(2025, 3, 30, 1, 0), at(2025, 3, 30, 2, 0)]
+        );
+        assert_eq!(
+            runs("*/30 * * * *", &tz, at(2025, 3, 30, 0, 0), 3),
+            [at(2025, 3, 30, 0, 30), at(2025, 3, 30, 1, 0), at(2025, 3, 30, 1, 30)]
+        );
+        // Runs outside of the skipped hour are not affected.
+        assert_eq!(runs("0 1,3 * * *", &tz, at(2025, 3, 29, 12, 0), 2), [at(2025, 3, 30, 0, 0), at(2025, 3, 30, 1, 0)]);
+        assert_eq!(runs("0 0 31 3 *", &tz, at(2025, 3, 30, 0, 30), 1), [at(2025, 3, 30, 22, 0)]);
+    }
+
+    #[test]
+    fn clocks_go_back() {
+        // 2025-10-26 01:00 UTC, Berlin goes from 03:00 back to 02:00 local time.
+        let tz = TimeZone::load("Europe/Berlin").unwrap();
+
+        // A fixed time in the repeated hour only runs the first time around.
+        assert_eq!(runs("30 2 * * *", &tz, at(2025, 10, 25, 12, 0), 2), [at(2025, 10, 26, 0, 30), at(2025, 10, 27, 1, 30)]);
+        // Without a fixed hour, runs keep their interval through the repeated hour.
+        assert_eq!(
+            runs("*/30 * * * *", &tz, at(2025, 10, 26, 0, 0), 4),
+            [at(2025, 10, 26, 0, 30), at(2025, 10, 26, 1, 0), at(2025, 10, 26, 1, 30), at(2025, 10, 26, 2, 0)]
+        );
+        assert_eq!(
+            runs("0 * * * *", &tz, at(2025, 10, 25, 23, 30), 3),
+            [at(2025, 10, 26, 0, 0), at(2025, 10, 26, 1, 0), at(2025, 10, 26, 2, 0)]
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        for cron in ["", "* * * *", "60 * * * *", "* * 0 * *", "*/0 * * * *", "5-1 * * * *", "0 0 31 2 *", "* * * foo *"] {
+            assert!(CronExpr::try_from(cron).is_err(), "{cron}");
+        }
+    }
+
+    #[test]
+    fn deserialize() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            schedule: Schedule,
+        }
+
+        let schedule = toml::from_str::<Wrapper>(r#"schedule = "*/5 * * * *""#).unwrap().schedule;
+        assert_eq!(schedule.overlap(), OverlapPolicy::Skip);
+        assert!(!schedule.catch_up());
+
+        let schedule = toml::from_str::<Wrapper>(r#"schedule = { cron = "0 3 * * *", timezone = "UTC", overlap = "queue", catch_up = true }"#)
+            .unwrap()
+            .schedule;
+        assert_eq!(schedule.cron().to_string(), "0 3 * * *");
+        assert_eq!(schedule.timezone().name(), "UTC");
+        assert_eq!(schedule.overlap(), OverlapPolicy::Queue);
+        assert!(schedule.catch_up());
+
+        assert!(toml::from_str::<Wrapper>(r#"schedule = { cron = "0 3 * * *", overlap = "never" }"#).is_err());
+        assert!(toml::from_str::<Wrapper>(r#"schedule = { cron = "0 3 * * *", timezone = "Nowhere/Special" }"#).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use std::net::SocketAddr;
+
+use serde::{Deserialize, Deserializer};
+
+fn dflt_backlog() -> u16 {
+    128
+}
+
+fn dflt_mode() -> SocketMode {
+    SocketMode { mode: 0o666 }
+}
+
+/// # `SocketConfig`
+/// `src/conf/proc/types/socket.rs`
+///
+/// A listening socket bound by taskmaster and passed to the process, inferred from the
+/// configured fields:
+/// - TCP:
+/// ```toml
+/// tcp = "0.0.0.0:8080"
+/// backlog = 128
+/// ```
+/// - Unix:
+/// ```toml
+/// unix = "/run/app.sock"
+/// backlog = 128
+/// mode = "660"
+/// ```
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(untagged)]
+pub enum SocketConfig {
+    Tcp(TcpSocketConfig),
+    Unix(UnixSocketConfig),
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(deny_unknown_fields)]
+pub struct TcpSocketConfig {
+    /// Address to listen on.
+    ///
+    /// ```toml
+    /// tcp = "127.0.0.1:8080"
+    /// ```
+    ///
+    /// Required.
+    pub tcp: SocketAddr,
+
+    /// Maximum number of pending connections.
+    ///
+    /// Defaults to `128`, max `65535`.
+    #[serde(default = "dflt_backlog")]
+    pub backlog: u16,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(deny_unknown_fields)]
+pub struct UnixSocketConfig {
+    /// Path of the socket file. An existing file at this path is replaced.
+    ///
+    /// ```toml
+    /// unix = "/run/app.sock"
+    /// ```
+    ///
+    /// Required.
+    pub unix: String,
+
+    /// Maximum number of pending connections.
+    ///
+    /// Defaults to `128`, max `65535`.
+    #[serde(default = "dflt_backlog")]
+    pub backlog: u16,
+
+    /// Permissions of the socket file.
+    ///
+    /// ```toml
+    /// unix = "/run/app.sock"
+    /// mode = "660"
+    /// ```
+    ///
+    /// Defaults to `666`.
+    #[serde(default = "dflt_mode")]
+    pub mode: SocketMode,
+}
+
+#[cfg(test)]
+impl SocketConfig {
+    pub fn unix(path: &str) -> Self {
+        Self::Unix(UnixSocketConfig {
+            unix: path.to_string(),
+            backlog: dflt_backlog(),
+            mode: dflt_mode(),
+        })
+    }
+}
+
+/// # `SocketMode`
+/// `src/conf/proc/types/socket.rs`
+///
+/// Permissions of a Unix socket file, as 3 octal digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SocketMode {
+    mode: u32,
+}
+
+impl SocketMode {
+    pub fn mode(&self) -> u32 {
+        self.mode
+    }
+}
+
+impl<'de> Deserialize<'de> for SocketMode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        if s.len() != 3 {
+            return Err(serde::de::Error::custom(format!("invalid length for socket mode, expected 3, got {}", s.len())));
+        }
+
+        match u32::from_str_radix(&s, 8) {
+            Ok(mode) => Ok(Self { mode }),
+            Err(err) => Err(serde::de::Error::custom(format!("invalid value for socket mode: '{s}', expected 3 octal digits: {err}"))),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use serde::Deserialize;
+
+use super::Duration;
+
+/// # `StartLimit`
+/// `src/conf/proc/types/startlimit.rs`
+///
+/// Circuit breaker for processes stuck in a crash loop: once a process failed more than
+/// `burst` times within `interval`, it is put in the `fatal` state and no longer
+/// restarted automatically.
+/// ```toml
+/// startlimit = { burst = 5, interval = "60s" }
+/// ```
+///
+/// Every unexpected exit, failed start and failed healthcheck counts as a failure, and
+/// so does exiting successfully with `autorestart = "always"`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StartLimit {
+    burst: u32,
+    interval: Duration,
+}
+
+impl StartLimit {
+    pub fn new(burst: u32, interval: Duration) -> Self {
+        Self { burst, interval }
+    }
+
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+}
@@ -0,0 +1,117 @@
+use libc::SIGKILL;
+use serde::{Deserialize, Deserializer};
+
+use super::{Duration, StopSignal};
+
+/// # `StopStep`
+/// `src/conf/proc/types/stop.rs`
+///
+/// One step of a [`StopSequence`]: send `signal` (if any), then give the process `wait`
+/// to exit before moving on to the next step.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StopStep {
+    #[serde(default)]
+    signal: Option<StopSignal>,
+    #[serde(default)]
+    wait: Option<Duration>,
+}
+
+impl StopStep {
+    pub fn new(signal: Option<StopSignal>, wait: Duration) -> Self {
+        Self { signal, wait: Some(wait) }
+    }
+
+    pub fn signal(&self) -> Option<StopSignal> {
+        self.signal
+    }
+
+    /// `None` if not configured, in which case `stoptime` applies.
+    pub fn wait(&self) -> Option<Duration> {
+        self.wait
+    }
+}
+
+/// # `StopSequence`
+/// `src/conf/proc/types/stop.rs`
+///
+/// Ordered steps taken to stop a process, escalating until it exits:
+/// ```toml
+/// stop = [{ signal = "TERM", wait = "10s" }, { signal = "INT", wait = "5s" }, { signal = "KILL" }]
+/// ```
+///
+/// A step without `wait` waits for `stoptime`, a step without `signal` only waits. If
+/// the process is still running after the last step, it is killed with `SIGKILL`, so
+/// `KILL` may only be used as the last step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StopSequence(Vec<StopStep>);
+
+impl StopSequence {
+    pub fn steps(&self) -> &[StopStep] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl StopSequence {
+    pub fn new(steps: Vec<StopStep>) -> Self {
+        Self(steps)
+    }
+}
+
+impl<'de> Deserialize<'de> for StopSequence {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let steps = Vec::<StopStep>::deserialize(deserializer)?;
+
+        if steps.is_empty() {
+            return Err(serde::de::Error::custom("stop sequence must have at least one step"));
+        }
+        if steps[..steps.len() - 1].iter().any(|s| s.signal == Some(StopSignal(SIGKILL))) {
+            return Err(serde::de::Error::custom("KILL can only be used as the last step of the stop sequence"));
+        }
+
+        Ok(Self(steps))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libc::SIGTERM;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Wrapper {
+        stop: StopSequence,
+    }
+
+    fn parse(toml: &str) -> Result<StopSequence, toml::de::Error> {
+        toml::from_str::<Wrapper>(toml).map(|w| w.stop)
+    }
+
+    #[test]
+    fn sequence() {
+        let stop = parse(r#"stop = [{ signal = "TERM", wait = "10s" }, { wait = 1 }, { signal = "KILL" }]"#).unwrap();
+        assert_eq!(
+            stop.steps(),
+            [
+                StopStep::new(Some(StopSignal(SIGTERM)), Duration::from_secs(10)),
+                StopStep::new(None, Duration::from_secs(1)),
+                StopStep {
+                    signal: Some(StopSignal(SIGKILL)),
+                    wait: None
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(parse("stop = []").is_err());
+        assert!(parse(r#"stop = [{ signal = "KILL" }, { signal = "TERM" }]"#).is_err());
+        assert!(parse(r#"stop = [{ signal = "TERM", timeout = "1s" }]"#).is_err());
+    }
+}
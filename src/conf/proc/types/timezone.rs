@@ -0,0 +1,547 @@
+use std::{fs, path::Path, sync::OnceLock};
+
+use serde::{Deserialize, Deserializer};
+
+const ZONEINFO: &str = "/usr/share/zoneinfo";
+const LOCALTIME: &str = "/etc/localtime";
+
+/// # `TimeZone`
+/// `src/conf/proc/types/timezone.rs`
+///
+/// IANA time zone loaded from the system's zoneinfo database, e.g. `"Europe/Berlin"`.
+/// `"UTC"` is always available, `"local"` is the zone of the host (`/etc/localtime`,
+/// or UTC if it is missing).
+///
+/// The transitions listed in the zone file are used up to the last one, the rule in the
+/// file's footer (a POSIX `TZ` string like `CET-1CEST,M3.5.0,M10.5.0/3`) after it.
+/// "Slim" zone files, as written by `zic` by default, only list historic transitions
+/// and rely on the rule for the current years.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeZone {
+    name: String,
+    /// UTC offset in seconds before the first transition.
+    initial: i32,
+    /// Unix timestamps at which the UTC offset changes, with the new offset.
+    transitions: Vec<(i64, i32)>,
+    /// Rule for the times after the last transition, from the footer of the zone file.
+    rule: Option<PosixTz>,
+}
+
+impl TimeZone {
+    pub fn utc() -> Self {
+        Self {
+            name: "UTC".to_string(),
+            initial: 0,
+            transitions: Vec::new(),
+            rule: None,
+        }
+    }
+
+    pub fn load(name: &str) -> Result<Self, String> {
+        match name {
+            "UTC" | "Etc/UTC" => return Ok(Self::utc()),
+            "local" if !Path::new(LOCALTIME).exists() => {
+                return Ok(Self {
+                    name: name.to_string(),
+                    ..Self::utc()
+                });
+            }
+            _ => {}
+        }
+
+        let path = match name {
+            "local" => LOCALTIME.to_string(),
+            _ if name.starts_with('/') || name.split('/').any(|part| part == ".." || part.is_empty()) => {
+                return Err(format!("invalid time zone: '{name}'"));
+            }
+            _ => format!("{ZONEINFO}/{name}"),
+        };
+        let data = fs::read(&path).map_err(|e| format!("could not load time zone '{name}' from {path}: {e}"))?;
+        parse_tzif(name, &data).ok_or_else(|| format!("could not load time zone '{name}' from {path}: invalid TZif file"))
+    }
+
+    /// The zone of the host, loaded once, falling back to UTC if it cannot be loaded.
+    pub fn local() -> &'static Self {
+        static LOCAL: OnceLock<TimeZone> = OnceLock::new();
+        LOCAL.get_or_init(|| Self::load("local").unwrap_or_else(|_| Self::utc()))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// UTC offset in seconds at the unix timestamp `utc`.
+    pub fn offset_at(&self, utc: i64) -> i32 {
+        match (self.transitions.partition_point(|(at, _)| *at <= utc), &self.rule) {
+            (i, Some(rule)) if i == self.transitions.len() => rule.offset_at(utc),
+            (0, _) => self.initial,
+            (i, _) => self.transitions[i - 1].1,
+        }
+    }
+
+    /// Converts local time (in seconds since the epoch, as if the zone was UTC) to a
+    /// unix timestamp. Times repeated when the clocks go back map to their first
+    /// occurrence, times skipped when they go forward are moved forward by the gap.
+    pub fn to_utc(&self, local: i64) -> i64 {
+        let before = self.offset_at(local - 86_400);
+        let after = self.offset_at(local + 86_400);
+        for offset in [before, after] {
+            if self.offset_at(local - offset as i64) == offset {
+                return local - offset as i64;
+            }
+        }
+        local - before as i64
+    }
+
+    /// Formats the unix timestamp `utc` in this zone, e.g. `2025-03-30T03:00:00+02:00`.
+    pub fn format(&self, utc: i64) -> String {
+        let offset = self.offset_at(utc);
+        let local = utc + offset as i64;
+        let (year, month, day) = civil_from_days(local.div_euclid(86_400));
+        let secs = local.rem_euclid(86_400);
+        let sign = if offset < 0 { '-' } else { '+' };
+        format!(
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}{sign}{:02}:{:02}",
+            secs / 3600,
+            secs % 3600 / 60,
+            secs % 60,
+            offset.abs() / 3600,
+            offset.abs() % 3600 / 60
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for TimeZone {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        TimeZone::load(&name).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Reads the transitions of a TZif file (RFC 8536), using the 64-bit data block and the
+/// footer of version 2+ files. A footer which cannot be parsed is ignored.
+fn parse_tzif(name: &str, data: &[u8]) -> Option<TimeZone> {
+    let counts = |header: &[u8]| -> Option<[usize; 6]> {
+        if header.get(..4)? != b"TZif" {
+            return None;
+        }
+        let mut counts = [0; 6];
+        for (i, count) in counts.iter_mut().enumerate() {
+            *count = u32::from_be_bytes(header.get(20 + i * 4..24 + i * 4)?.try_into().ok()?) as usize;
+        }
+        Some(counts)
+    };
+
+    let [isutcnt, isstdcnt, leapcnt, timecnt, typecnt, charcnt] = counts(data)?;
+    let (data, time_size) = match data.get(4)? {
+        0 => (data, 4),
+        _ => {
+            let v1_len = timecnt * 5 + typecnt * 6 + charcnt + leapcnt * 8 + isstdcnt + isutcnt;
+            (data.get(44 + v1_len..)?, 8)
+        }
+    };
+    let version = *data.get(4)?;
+    let [isutcnt, isstdcnt, leapcnt, timecnt, typecnt, charcnt] = counts(data)?;
+    let body = data.get(44..)?;
+
+    let times = body.get(..timecnt * time_size)?;
+    let indices = body.get(timecnt * time_size..timecnt * (time_size + 1))?;
+    let types = body.get(timecnt * (time_size + 1)..timecnt * (time_size + 1) + typecnt * 6)?;
+    let offset = |index: usize| -> Option<i32> { Some(i32::from_be_bytes(types.get(index * 6..index * 6 + 4)?.try_into().ok()?)) };
+
+    let mut transitions = Vec::with_capacity(timecnt);
+    for (i, index) in indices.iter().enumerate() {
+        let time = &times[i * time_size..(i + 1) * time_size];
+        let at = match time_size {
+            4 => i32::from_be_bytes(time.try_into().ok()?) as i64,
+            _ => i64::from_be_bytes(time.try_into().ok()?),
+        };
+        transitions.push((at, offset(*index as usize)?));
+    }
+
+    let rule = match version {
+        0 => None,
+        _ => {
+            let len = timecnt * (time_size + 1) + typecnt * 6 + charcnt + leapcnt * (time_size + 4) + isstdcnt + isutcnt;
+            let footer = body.get(len..)?.strip_prefix(b"\n")?;
+            let footer = &footer[..footer.iter().position(|b| *b == b'\n')?];
+            std::str::from_utf8(footer).ok().and_then(PosixTz::parse)
+        }
+    };
+
+    Some(TimeZone {
+        name: name.to_string(),
+        initial: offset(0)?,
+        transitions,
+        rule,
+    })
+}
+
+/// Rule of a POSIX `TZ` string, as found in the footer of TZif files, e.g.
+/// `CET-1CEST,M3.5.0,M10.5.0/3`: one hour ahead of UTC, two while daylight saving time
+/// is in effect from the last Sunday of March at 02:00 to the last Sunday of October at
+/// 03:00.
+#[derive(Debug, Clone, PartialEq)]
+struct PosixTz {
+    /// UTC offset in seconds of standard time.
+    std_offset: i32,
+    dst: Option<PosixDst>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PosixDst {
+    /// UTC offset in seconds while daylight saving time is in effect.
+    offset: i32,
+    /// When daylight saving time starts, in local standard time.
+    start: (PosixDate, i32),
+    /// When daylight saving time ends, in local daylight saving time.
+    end: (PosixDate, i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PosixDate {
+    /// `Jn`: day `n` of the year, from 1 to 365, February 29 is never counted.
+    Julian(u16),
+    /// `n`: day `n` of the year, from 0 to 365, counting February 29.
+    ZeroBased(u16),
+    /// `Mm.w.d`: weekday `d` (0 is Sunday) of week `w` of month `m`, week 5 being the
+    /// last one.
+    MonthWeekDay(u32, u32, u32),
+}
+
+impl PosixTz {
+    fn parse(s: &str) -> Option<Self> {
+        let mut s = s.as_bytes();
+        skip_name(&mut s)?;
+        // POSIX offsets are west of UTC, `CET-1` is one hour ahead.
+        let std_offset = -parse_time(&mut s)?;
+        if s.is_empty() {
+            return Some(Self { std_offset, dst: None });
+        }
+
+        skip_name(&mut s)?;
+        let offset = match s.first() {
+            Some(b',') => std_offset + 3600,
+            _ => -parse_time(&mut s)?,
+        };
+        s = s.strip_prefix(b",")?;
+        let start = parse_rule(&mut s)?;
+        s = s.strip_prefix(b",")?;
+        let end = parse_rule(&mut s)?;
+        if !s.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            std_offset,
+            dst: Some(PosixDst { offset, start, end }),
+        })
+    }
+
+    /// UTC offset in seconds at the unix timestamp `utc`.
+    fn offset_at(&self, utc: i64) -> i32 {
+        let Some(dst) = &self.dst else {
+            return self.std_offset;
+        };
+
+        let (year, _, _) = civil_from_days((utc + self.std_offset as i64).div_euclid(86_400));
+        let start = dst.start.0.day(year) * 86_400 + dst.start.1 as i64 - self.std_offset as i64;
+        let end = dst.end.0.day(year) * 86_400 + dst.end.1 as i64 - dst.offset as i64;
+        // On the southern hemisphere, daylight saving time spans the turn of the year.
+        let in_dst = match start <= end {
+            true => start <= utc && utc < end,
+            false => !(end <= utc && utc < start),
+        };
+        match in_dst {
+            true => dst.offset,
+            false => self.std_offset,
+        }
+    }
+}
+
+impl PosixDate {
+    /// Days since 1970-01-01 of this date in `year`.
+    fn day(self, year: i64) -> i64 {
+        let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+        let jan_1 = days_from_civil(year, 1, 1);
+        match self {
+            PosixDate::Julian(n) => jan_1 + n as i64 - 1 + (leap && n >= 60) as i64,
+            PosixDate::ZeroBased(n) => jan_1 + n as i64,
+            PosixDate::MonthWeekDay(month, week, weekday) => {
+                let first = days_from_civil(year, month, 1);
+                let next = match month {
+                    12 => days_from_civil(year + 1, 1, 1),
+                    _ => days_from_civil(year, month + 1, 1),
+                };
+                // 1970-01-01 was a Thursday.
+                let first_weekday = (first + 4).rem_euclid(7);
+                let mut day = first + (weekday as i64 - first_weekday).rem_euclid(7) + (week as i64 - 1) * 7;
+                while day >= next {
+                    day -= 7;
+                }
+                day
+            }
+        }
+    }
+}
+
+/// Skips a zone abbreviation, either alphabetic (`CET`) or quoted (`<+0330>`).
+fn skip_name(s: &mut &[u8]) -> Option<()> {
+    let len = match s.first()? {
+        b'<' => s.iter().position(|b| *b == b'>')? + 1,
+        _ => s.iter().position(|b| !b.is_ascii_alphabetic()).unwrap_or(s.len()),
+    };
+    if len < 3 {
+        return None;
+    }
+    *s = &s[len..];
+    Some(())
+}
+
+/// Parses `[+-]hh[:mm[:ss]]` into seconds.
+fn parse_time(s: &mut &[u8]) -> Option<i32> {
+    let sign = match s.first()? {
+        b'-' => -1,
+        _ => 1,
+    };
+    if matches!(s.first(), Some(b'+' | b'-')) {
+        *s = &s[1..];
+    }
+
+    let mut secs = 0;
+    for (i, factor) in [3600, 60, 1].into_iter().enumerate() {
+        if i > 0 {
+            match s.strip_prefix(b":") {
+                Some(rest) => *s = rest,
+                None => break,
+            }
+        }
+        secs += parse_number(s)? as i32 * factor;
+    }
+    Some(sign * secs)
+}
+
+/// Parses `date[/time]`, the time defaulting to 02:00.
+fn parse_rule(s: &mut &[u8]) -> Option<(PosixDate, i32)> {
+    let date = match s.first()? {
+        b'J' => {
+            *s = &s[1..];
+            PosixDate::Julian(parse_number(s)?.try_into().ok().filter(|n| (1..=365).contains(n))?)
+        }
+        b'M' => {
+            *s = &s[1..];
+            let month = parse_number(s)?;
+            *s = s.strip_prefix(b".")?;
+            let week = parse_number(s)?;
+            *s = s.strip_prefix(b".")?;
+            let weekday = parse_number(s)?;
+            if !(1..=12).contains(&month) || !(1..=5).contains(&week) || weekday > 6 {
+                return None;
+            }
+            PosixDate::MonthWeekDay(month, week, weekday)
+        }
+        _ => PosixDate::ZeroBased(parse_number(s)?.try_into().ok().filter(|n| *n <= 365)?),
+    };
+
+    let time = match s.strip_prefix(b"/") {
+        Some(rest) => {
+            *s = rest;
+            parse_time(s)?
+        }
+        None => 7200,
+    };
+    Some((date, time))
+}
+
+fn parse_number(s: &mut &[u8]) -> Option<u32> {
+    let len = s.iter().position(|b| !b.is_ascii_digit()).unwrap_or(s.len());
+    let number = std::str::from_utf8(&s[..len]).ok()?.parse().ok()?;
+    *s = &s[len..];
+    Some(number)
+}
+
+/// Days since 1970-01-01 of the given date (proleptic Gregorian calendar).
+pub(super) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = month as i64;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Date of the day `days` days after 1970-01-01, as `(year, month, day)`.
+pub(super) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_roundtrip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        for days in [-1, 0, 59, 11_016, 11_017, 20_000, 50_000] {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+    }
+
+    #[test]
+    fn berlin_dst_after_the_last_transition() {
+        let tz = TimeZone::load("Europe/Berlin").unwrap();
+        assert_eq!(tz.format(days_from_civil(2100, 7, 1) * 86_400), "2100-07-01T02:00:00+02:00");
+        assert_eq!(tz.format(days_from_civil(2100, 12, 1) * 86_400), "2100-12-01T01:00:00+01:00");
+    }
+
+    #[test]
+    fn berlin_dst() {
+        let tz = TimeZone::load("Europe/Berlin").unwrap();
+        // 2025-03-30 01:00 UTC, clocks go from 02:00 to 03:00 local time.
+        let switch = days_from_civil(2025, 3, 30) * 86_400 + 3600;
+        assert_eq!(tz.offset_at(switch - 1), 3600);
+        assert_eq!(tz.offset_at(switch), 7200);
+        assert_eq!(tz.format(switch), "2025-03-30T03:00:00+02:00");
+
+        // 02:30 does not exist that day and is moved to 03:30.
+        let local = days_from_civil(2025, 3, 30) * 86_400 + 2 * 3600 + 1800;
+        assert_eq!(tz.to_utc(local), switch + 1800);
+    }
+
+    /// A TZif file with `transitions` (time, index into `types`) between `types` (UTC
+    /// offset, abbreviation). Version 2+ files get `footer`, and all offsets in their
+    /// version 1 data block are 0, only the 64-bit block is supposed to be read.
+    fn tzif(version: u8, transitions: &[(i64, u8)], types: &[(i32, &str)], footer: &str) -> Vec<u8> {
+        let chars = types
+            .iter()
+            .flat_map(|(_, abbreviation)| abbreviation.bytes().chain([0]))
+            .collect::<Vec<u8>>();
+        let block = |time_size: usize, offsets: bool| {
+            let mut data = b"TZif".to_vec();
+            data.push(version);
+            data.extend([0; 15]);
+            for count in [0, 0, 0, transitions.len(), types.len(), chars.len()] {
+                data.extend(u32::to_be_bytes(count as u32));
+            }
+            for (at, _) in transitions {
+                match time_size {
+                    4 => data.extend((*at as i32).to_be_bytes()),
+                    _ => data.extend(at.to_be_bytes()),
+                }
+            }
+            data.extend(transitions.iter().map(|(_, index)| index));
+            let mut abbreviation = 0;
+            for (offset, name) in types {
+                data.extend(if offsets { *offset } else { 0 }.to_be_bytes());
+                data.extend([0, abbreviation]);
+                abbreviation += name.len() as u8 + 1;
+            }
+            data.extend(&chars);
+            data
+        };
+
+        match version {
+            0 => block(4, true),
+            _ => {
+                let mut data = block(4, false);
+                data.extend(block(8, true));
+                data.extend(format!("\n{footer}\n").as_bytes());
+                data
+            }
+        }
+    }
+
+    /// A version 2 file without transitions, as written by `zic -b slim` for zones
+    /// which did not change their rules recently.
+    fn slim_tzif(offset: i32, abbreviation: &str, footer: &str) -> Vec<u8> {
+        tzif(b'2', &[], &[(offset, abbreviation)], footer)
+    }
+
+    #[test]
+    fn version_1_file() {
+        // Berlin's 2025 transitions, and nothing after them without a footer.
+        let spring = days_from_civil(2025, 3, 30) * 86_400 + 3600;
+        let autumn = days_from_civil(2025, 10, 26) * 86_400 + 3600;
+        let tz = parse_tzif("Europe/Berlin", &tzif(0, &[(spring, 1), (autumn, 0)], &[(3600, "CET"), (7200, "CEST")], "")).unwrap();
+
+        assert_eq!(tz.offset_at(spring - 1), 3600);
+        assert_eq!(tz.offset_at(spring), 7200);
+        assert_eq!(tz.offset_at(autumn), 3600);
+        assert_eq!(tz.offset_at(days_from_civil(2050, 7, 1) * 86_400), 3600);
+    }
+
+    #[test]
+    fn version_2_file_uses_64_bit_data() {
+        // 1900-01-01, before the range of the 32-bit transition times.
+        let early = days_from_civil(1900, 1, 1) * 86_400;
+        let spring = days_from_civil(2025, 3, 30) * 86_400 + 3600;
+        for version in [b'2', b'3'] {
+            let data = tzif(version, &[(early, 1), (spring, 2)], &[(3208, "LMT"), (3600, "CET"), (7200, "CEST")], "CET-1CEST,M3.5.0,M10.5.0/3");
+            let tz = parse_tzif("Europe/Berlin", &data).unwrap();
+
+            assert_eq!(tz.offset_at(early - 1), 3208);
+            assert_eq!(tz.offset_at(early), 3600);
+            assert_eq!(tz.offset_at(spring), 7200);
+            assert_eq!(tz.format(days_from_civil(2050, 1, 1) * 86_400), "2050-01-01T01:00:00+01:00");
+        }
+    }
+
+    #[test]
+    fn slim_file_uses_footer() {
+        let tz = parse_tzif("Europe/Berlin", &slim_tzif(3600, "CET", "CET-1CEST,M3.5.0,M10.5.0/3")).unwrap();
+
+        // 2025-03-30 01:00 UTC and 2025-10-26 01:00 UTC.
+        let spring = days_from_civil(2025, 3, 30) * 86_400 + 3600;
+        let autumn = days_from_civil(2025, 10, 26) * 86_400 + 3600;
+        assert_eq!(tz.offset_at(spring - 1), 3600);
+        assert_eq!(tz.offset_at(spring), 7200);
+        assert_eq!(tz.offset_at(autumn - 1), 7200);
+        assert_eq!(tz.offset_at(autumn), 3600);
+        assert_eq!(tz.format(days_from_civil(2050, 7, 1) * 86_400), "2050-07-01T02:00:00+02:00");
+        assert_eq!(tz.format(days_from_civil(2050, 1, 1) * 86_400), "2050-01-01T01:00:00+01:00");
+    }
+
+    #[test]
+    fn posix_tz_rules() {
+        // Southern hemisphere, daylight saving time from the first Sunday of October to
+        // the first Sunday of April.
+        let sydney = PosixTz::parse("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+        assert_eq!(sydney.offset_at(days_from_civil(2025, 1, 15) * 86_400), 11 * 3600);
+        assert_eq!(sydney.offset_at(days_from_civil(2025, 7, 15) * 86_400), 10 * 3600);
+
+        let no_dst = PosixTz::parse("<+0330>-3:30").unwrap();
+        assert_eq!(no_dst.offset_at(0), 3 * 3600 + 1800);
+
+        // 2024 is a leap year, J60 is March 1 and 59 is February 29.
+        assert_eq!(PosixDate::Julian(60).day(2024), days_from_civil(2024, 3, 1));
+        assert_eq!(PosixDate::ZeroBased(59).day(2024), days_from_civil(2024, 2, 29));
+        assert_eq!(PosixDate::MonthWeekDay(3, 5, 0).day(2025), days_from_civil(2025, 3, 30));
+
+        for invalid in ["", "C-1", "CET", "CET-1CEST", "CET-1CEST,M13.1.0,M10.5.0", "CET-1CEST,M3.5.0"] {
+            assert_eq!(PosixTz::parse(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(TimeZone::load("Mars/Olympus_Mons").is_err());
+        assert!(TimeZone::load("../../etc/passwd").is_err());
+        assert!(TimeZone::load("/etc/passwd").is_err());
+        assert_eq!(TimeZone::load("UTC").unwrap().format(0), "1970-01-01T00:00:00+00:00");
+    }
+}
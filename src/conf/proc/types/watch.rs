@@ -0,0 +1,126 @@
+use serde::Deserialize;
+
+use super::{Duration, StopSignal};
+
+fn dflt_debounce() -> Duration {
+    Duration::from_millis(500)
+}
+
+/// # `Watch`
+/// `src/conf/proc/types/watch.rs`
+///
+/// Restarts the process whenever one of the watched files changes, meant for running
+/// services locally while working on them.
+/// ```toml
+/// watch = { paths = ["./src", "./config"], ignore = ["*.swp", "target"], debounce = "500ms" }
+/// ```
+///
+/// `paths` are files or directories, the latter being watched recursively. Relative paths
+/// are relative to `workingdir`. `ignore` is matched against the name of every changed
+/// file and every directory below the watched ones, `*` matching any number of
+/// characters and `?` a single one.
+///
+/// A change is only acted upon once the watched files were left alone for `debounce`.
+/// With `signal`, the process is sent that signal instead of being restarted:
+/// ```toml
+/// watch = { paths = ["./config.yml"], signal = "HUP" }
+/// ```
+///
+/// Every watching process uses an inotify instance of its own, of which Linux allows 128
+/// per user by default (`fs.inotify.max_user_instances`). A process whose watcher cannot
+/// be set up is not watched until its `watch` config changes.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Watch {
+    paths: Vec<String>,
+    #[serde(default)]
+    ignore: Vec<String>,
+    #[serde(default = "dflt_debounce")]
+    debounce: Duration,
+    #[serde(default)]
+    signal: Option<StopSignal>,
+}
+
+impl Watch {
+    pub fn new(paths: Vec<String>) -> Self {
+        Self {
+            paths,
+            ignore: Vec::new(),
+            debounce: dflt_debounce(),
+            signal: None,
+        }
+    }
+
+    pub fn paths(&self) -> &[String] {
+        &self.paths
+    }
+
+    pub fn ignore(&self) -> &[String] {
+        &self.ignore
+    }
+
+    pub fn debounce(&self) -> Duration {
+        self.debounce
+    }
+
+    pub fn signal(&self) -> Option<StopSignal> {
+        self.signal
+    }
+
+    /// Whether a file or directory called `name` is excluded by one of the `ignore`
+    /// patterns.
+    pub fn ignores(&self, name: &str) -> bool {
+        self.ignore
+            .iter()
+            .any(|pattern| wildcard_match(pattern.as_bytes(), name.as_bytes()))
+    }
+}
+
+#[cfg(test)]
+impl Watch {
+    pub fn set_ignore(&mut self, ignore: Vec<String>) -> &mut Self {
+        self.ignore = ignore;
+        self
+    }
+
+    pub fn set_debounce(&mut self, debounce: Duration) -> &mut Self {
+        self.debounce = debounce;
+        self
+    }
+
+    pub fn set_signal(&mut self, signal: StopSignal) -> &mut Self {
+        self.signal = Some(signal);
+        self
+    }
+}
+
+/// Matches `name` against `pattern`, `*` matching any number of characters and `?` a
+/// single one.
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, None) => true,
+        (Some((b'*', rest)), _) => wildcard_match(rest, name) || (!name.is_empty() && wildcard_match(pattern, &name[1..])),
+        (Some((b'?', rest)), Some((_, name))) => wildcard_match(rest, name),
+        (Some((p, rest)), Some((n, name))) => p == n && wildcard_match(rest, name),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignore_patterns() {
+        let mut watch = Watch::new(vec![".".into()]);
+        let watch = watch.set_ignore(vec!["*.swp".into(), "target".into(), "?.tmp".into()]);
+
+        assert!(watch.ignores(".main.rs.swp"));
+        assert!(watch.ignores("target"));
+        assert!(watch.ignores("a.tmp"));
+        assert!(!watch.ignores("main.rs"));
+        assert!(!watch.ignores("target2"));
+        assert!(!watch.ignores("ab.tmp"));
+        assert!(!watch.ignores("swp"));
+    }
+}
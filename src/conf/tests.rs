@@ -4,7 +4,7 @@ mod from_str {
 
     use crate::conf::{
         Config,
-        defaults::dflt_socketpath,
+        defaults::{dflt_pidfile, dflt_socketpath},
         proc::{defaults, types},
     };
 
@@ -26,12 +26,72 @@ mod from_str {
         assert_eq!(conf.processes()["nginx"].env(), &Vec::new());
     }
 
+    #[test]
+    fn changed_fields() {
+        let old = Config::from_str("[processes.sleep]\ncmd = \"/usr/bin/sleep\"\nworkingdir = \"/tmp\"\n").expect("could not parse config");
+        let new = Config::from_str(
+            "[processes.sleep]\ncmd = \"/usr/bin/sleep\"\nworkingdir = \"/tmp\"\nstoptime = 9\nargs = [\"1\"]\n[processes.sleep.healthcheck]\nstarttime = 1\nretries = 1\n",
+        )
+        .expect("could not parse config");
+
+        assert_eq!(old.processes()["sleep"].changed_fields(&old.processes()["sleep"]), Vec::<&str>::new());
+        assert_eq!(old.processes()["sleep"].changed_fields(&new.processes()["sleep"]), vec!["args", "healthcheck.retries", "stoptime"]);
+    }
+
+    #[test]
+    fn watch_config() {
+        let conf_str = "[processes.sleep]\ncmd = \"/usr/bin/sleep\"\nworkingdir = \"/tmp\"\n";
+        assert!(!Config::from_str(conf_str).expect("could not parse config").watch_config());
+
+        let conf_str = "watch_config = true\n[processes.sleep]\ncmd = \"/usr/bin/sleep\"\nworkingdir = \"/tmp\"\n";
+        assert!(Config::from_str(conf_str).expect("could not parse config").watch_config());
+    }
+
+    #[test]
+    fn sockets() {
+        let conf_str = "[processes.web]\ncmd = \"/usr/bin/sleep\"\nworkingdir = \"/tmp\"\n[processes.web.sockets.http]\ntcp = \"127.0.0.1:8080\"\n[processes.web.sockets.admin]\nunix = \"/tmp/web.sock\"\nbacklog = 16\nmode = \"660\"\n";
+        let conf = Config::from_str(conf_str).expect("could not parse config");
+        let sockets = conf.processes()["web"].sockets();
+
+        assert_eq!(sockets.keys().collect::<Vec<&String>>(), vec!["admin", "http"]);
+        match &sockets["http"] {
+            types::SocketConfig::Tcp(tcp) => {
+                assert_eq!(tcp.tcp.port(), 8080);
+                assert_eq!(tcp.backlog, 128);
+            }
+            _ => panic!("expected a TCP socket"),
+        }
+        match &sockets["admin"] {
+            types::SocketConfig::Unix(unix) => {
+                assert_eq!(unix.unix, "/tmp/web.sock");
+                assert_eq!(unix.backlog, 16);
+                assert_eq!(unix.mode.mode(), 0o660);
+            }
+            _ => panic!("expected a Unix socket"),
+        }
+
+        let conf_str = "[processes.web]\ncmd = \"/usr/bin/sleep\"\nworkingdir = \"/tmp\"\n[processes.web.sockets.http]\ntcp = \"localhost\"\n";
+        assert!(Config::from_str(conf_str).is_err());
+    }
+
     #[test]
     fn socketpath_default() {
         let conf_str = "[processes.nginx]\ncmd = \"/usr/sbin/nginx\"\nworkingdir = \"/tmp\"\n";
         assert_eq!(Config::from_str(conf_str).expect("could not parse config").socketpath(), dflt_socketpath())
     }
 
+    #[test]
+    fn pidfile_default() {
+        let conf_str = "[processes.sleep]\ncmd = \"/usr/bin/sleep\"\nworkingdir = \"/tmp\"\n";
+        assert_eq!(Config::from_str(conf_str).expect("could not parse config").pidfile(), dflt_pidfile())
+    }
+
+    #[test]
+    fn pidfile_configured() {
+        let conf_str = "pidfile = \"/tmp/custom.pid\"\n[processes.sleep]\ncmd = \"/usr/bin/sleep\"\nworkingdir = \"/tmp\"\n";
+        assert_eq!(Config::from_str(conf_str).expect("could not parse config").pidfile(), "/tmp/custom.pid")
+    }
+
     #[test]
     fn cmd_nonexisting() {
         let conf_str = "[processes.nginx]\ncmd = \"/usr/sbin/ngin\"\nworkingdir = \"/tmp\"\n";
@@ -71,7 +131,7 @@ mod from_str {
 
     #[test]
     fn processes_out_of_range() {
-        let conf_str = "[processes.nginx]\ncmd = \"/usr/sbin/nginx\"\nworkingdir = \"/tmp\"\nprocesses = 256";
+        let conf_str = "[processes.nginx]\ncmd = \"/usr/sbin/nginx\"\nworkingdir = \"/tmp\"\nprocesses = 65536";
         assert!(Config::from_str(conf_str).is_err());
     }
 
@@ -229,15 +289,24 @@ mod from_str {
     }
 
     #[test]
-    fn stoptime_out_of_range() {
-        let conf_str = "[processes.nginx]\ncmd = \"/usr/sbin/nginx\"\nworkingdir = \"/tmp\"\nstoptime  = 256";
+    fn stoptime_durations() {
+        let conf_str = "[processes.sleep]\ncmd = \"/usr/bin/sleep\"\nworkingdir = \"/tmp\"\nstoptime = 600";
+        assert_eq!(Config::from_str(conf_str).unwrap().processes()["sleep"].stoptime(), types::Duration::from_secs(600));
+
+        let conf_str = "[processes.sleep]\ncmd = \"/usr/bin/sleep\"\nworkingdir = \"/tmp\"\nstoptime = \"250ms\"";
+        assert_eq!(Config::from_str(conf_str).unwrap().processes()["sleep"].stoptime(), types::Duration::from_millis(250));
+
+        let conf_str = "[processes.sleep]\ncmd = \"/usr/bin/sleep\"\nworkingdir = \"/tmp\"\nstoptime = -1";
         assert!(Config::from_str(conf_str).is_err());
     }
 
     #[test]
     fn stoptime_default() {
         let conf_str = "[processes.nginx]\ncmd = \"/usr/sbin/nginx\"\nworkingdir = \"/tmp\"\n";
-        assert_eq!(Config::from_str(conf_str).expect("could not parse config").processes()["nginx"].stoptime(), 5);
+        assert_eq!(
+            Config::from_str(conf_str).expect("could not parse config").processes()["nginx"].stoptime(),
+            types::Duration::from_secs(5)
+        );
     }
 
     #[test]
@@ -329,7 +398,7 @@ mod from_file {
 
     use crate::conf::{
         Config,
-        proc::types::{AccessibleDirectory, StopSignal},
+        proc::types::{AccessibleDirectory, Duration, StopSignal},
     };
 
     #[test]
@@ -384,7 +453,7 @@ mod from_file {
         assert_eq!(conf.processes()["sleep"].autorestart().max_retries(), 5);
         assert_eq!(conf.processes()["sleep"].exitcodes(), &vec![0, 2]);
         assert_eq!(conf.processes()["sleep"].stopsignals(), &vec![StopSignal(SIGTERM), StopSignal(SIGUSR1)]);
-        assert_eq!(conf.processes()["sleep"].stoptime(), 5);
+        assert_eq!(conf.processes()["sleep"].stoptime(), Duration::from_secs(5));
         assert_eq!(conf.processes()["sleep"].stdout().clone().unwrap().path(), ("/tmp/sleep.stdout".to_string()));
         assert_eq!(conf.processes()["sleep"].stderr().clone().unwrap().path(), ("/tmp/sleep.stderr".to_string()));
         assert_eq!(
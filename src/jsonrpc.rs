@@ -1,4 +1,9 @@
+pub mod daemon_info;
 pub mod handlers;
+pub mod process_details;
+pub mod reload_report;
 pub mod request;
 pub mod response;
+pub mod rolling_restart_progress;
+pub mod run_history;
 pub mod short_process;
@@ -0,0 +1,120 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::run::daemon::Daemon;
+
+/// Record of a configuration reload that could not be applied. The daemon keeps
+/// running on its last-known-good configuration when this happens.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FailedReload {
+    /// Seconds since the UNIX epoch.
+    timestamp: u64,
+    config_path: String,
+    error: String,
+}
+
+impl FailedReload {
+    pub fn new(config_path: &str, error: &str) -> Self {
+        Self {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            config_path: config_path.to_owned(),
+            error: error.to_owned(),
+        }
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn config_path(&self) -> &str {
+        &self.config_path
+    }
+
+    pub fn error(&self) -> &str {
+        &self.error
+    }
+}
+
+/// A configuration the daemon successfully applied, see `rollback`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SnapshotInfo {
+    /// Seconds since the UNIX epoch.
+    timestamp: u64,
+    /// Hash of the config file content.
+    hash: u64,
+}
+
+impl SnapshotInfo {
+    pub fn new(timestamp: u64, hash: u64) -> Self {
+        Self { timestamp, hash }
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DaemonInfo {
+    pid: u32,
+    config_path: String,
+    socketpath: String,
+    pidfile: String,
+    processes: usize,
+    shutting_down: bool,
+    last_failed_reload: Option<FailedReload>,
+    /// Oldest first, the last one is the active configuration.
+    snapshots: Vec<SnapshotInfo>,
+}
+
+impl DaemonInfo {
+    pub fn from_daemon(daemon: &Daemon) -> Self {
+        Self {
+            pid: std::process::id(),
+            config_path: daemon.config_path().to_owned(),
+            socketpath: daemon.socket_path().to_owned(),
+            pidfile: daemon.pidfile().to_owned(),
+            processes: daemon.processes().len(),
+            shutting_down: daemon.shutting_down(),
+            last_failed_reload: daemon.last_failed_reload().clone(),
+            snapshots: daemon.snapshots().infos(),
+        }
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub fn config_path(&self) -> &str {
+        &self.config_path
+    }
+
+    pub fn socketpath(&self) -> &str {
+        &self.socketpath
+    }
+
+    pub fn pidfile(&self) -> &str {
+        &self.pidfile
+    }
+
+    pub fn processes(&self) -> usize {
+        self.processes
+    }
+
+    pub fn shutting_down(&self) -> bool {
+        self.shutting_down
+    }
+
+    pub fn last_failed_reload(&self) -> &Option<FailedReload> {
+        &self.last_failed_reload
+    }
+
+    pub fn snapshots(&self) -> &[SnapshotInfo] {
+        &self.snapshots
+    }
+}
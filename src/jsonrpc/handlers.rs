@@ -5,18 +5,32 @@ use tokio::{
 };
 
 use super::{
-    request::{AttachFile, RequestAttach, RequestRestart, RequestStart, RequestStop},
+    request::{
+        AttachFile, RequestAttach, RequestDescribe, RequestHistory, RequestReload, RequestReset, RequestResetFailed, RequestRestart, RequestRollback,
+        RequestRollingRestart, RequestRollingRestartStatus, RequestRun, RequestScale, RequestStart, RequestStop,
+    },
     response::ErrorCode,
 };
+use crate::{
+    conf::proc::types::{ProcessType, RestartStrategy},
+    log_error,
+    run::daemon::socket::AsyncUnixSocket,
+};
 use crate::{
     jsonrpc::{
+        daemon_info::DaemonInfo,
+        process_details::ProcessDetails,
         response::{ResponseResult, ResponseType},
+        run_history::{RunHistory, RunStarted},
         short_process::ShortProcess,
     },
     log_info, proc_info,
-    run::{daemon::Daemon, proc::Process, statemachine::states::ProcessState},
+    run::{
+        daemon::Daemon,
+        proc::{ManualOverride, Process, history::RunTrigger},
+        statemachine::states::ProcessState,
+    },
 };
-use crate::{log_error, run::daemon::socket::AsyncUnixSocket};
 use std::{collections::HashMap, error::Error};
 
 use super::{
@@ -32,9 +46,19 @@ pub async fn handle_request(daemon: &mut Daemon, request: Request) -> Response {
         Start(request) => handle_request_start(daemon.processes_mut(), request),
         Stop(request) => handle_request_stop(daemon.processes_mut(), request),
         Restart(request) => handle_request_restart(daemon.processes_mut(), request),
-        Reload => handle_request_reload(daemon),
+        Reload(request) => handle_request_reload(daemon, request),
         Halt => handle_request_halt(daemon),
         Attach(request) => handle_request_attach(daemon, request).await,
+        Info => handle_request_info(daemon),
+        Rollback(request) => handle_request_rollback(daemon, request),
+        Reset(request) => handle_request_reset(daemon.processes_mut(), request),
+        RollingRestart(request) => handle_request_rolling_restart(daemon, request),
+        RollingRestartStatus(request) => handle_request_rolling_restart_status(daemon, request),
+        Scale(request) => handle_request_scale(daemon, request),
+        ResetFailed(request) => handle_request_reset_failed(daemon.processes_mut(), request),
+        History(request) => handle_request_history(daemon.processes_mut(), request),
+        Run(request) => handle_request_run(daemon.processes_mut(), request),
+        Describe(request) => handle_request_describe(daemon.processes_mut(), request),
     };
 
     Response::from_request(request, response_type)
@@ -66,7 +90,25 @@ fn handle_request_status_single(processes: &mut HashMap<String, Process>, reques
 
     log_info!("getting status for process",; process = process.name());
 
-    ResponseType::Result(ResponseResult::StatusSingle(ShortProcess::from_process(process)))
+    ResponseType::Result(ResponseResult::StatusSingle(Box::new(ShortProcess::from_process(process))))
+}
+
+fn handle_request_describe(processes: &mut HashMap<String, Process>, request: &RequestDescribe) -> ResponseType {
+    let process = match processes.get(request.name()) {
+        Some(p) => p,
+        None => {
+            log_error!("invalid describe request",; request = request);
+            return ResponseType::Error(ResponseError {
+                code: ErrorCode::InvalidParams,
+                message: format!("no process with name {} found", request.name()),
+                data: None,
+            });
+        }
+    };
+
+    log_info!("describing process",; process = process.name());
+
+    ResponseType::Result(ResponseResult::Describe(Box::new(ProcessDetails::from_process(process))))
 }
 
 fn handle_request_start(processes: &mut HashMap<String, Process>, request: &RequestStart) -> ResponseType {
@@ -82,7 +124,14 @@ fn handle_request_start(processes: &mut HashMap<String, Process>, request: &Requ
         }
     };
 
+    if process.reset_failed() {
+        proc_info!(&process, "cleared fatal state");
+    }
+    if !process.run_in_progress() {
+        process.set_next_trigger(RunTrigger::Manual);
+    }
     process.push_desired_state(ProcessState::Healthy);
+    process.set_manual_override(Some(ManualOverride::Started));
 
     proc_info!(&process, "starting");
 
@@ -107,6 +156,7 @@ fn handle_request_stop(processes: &mut HashMap<String, Process>, request: &Reque
     };
 
     process.push_desired_state(ProcessState::Idle);
+    process.set_manual_override(Some(ManualOverride::Stopped));
 
     proc_info!(&process, "stopping");
 
@@ -130,29 +180,230 @@ fn handle_request_restart(processes: &mut HashMap<String, Process>, request: &Re
         }
     };
 
-    process.push_desired_state(ProcessState::Ready);
+    process.set_manual_override(Some(ManualOverride::Started));
+    process.set_next_trigger(RunTrigger::Manual);
+
+    match process.restart() {
+        Ok(RestartStrategy::StartFirst) => {
+            proc_info!(&process, "restarting, starting replacement first");
+            ResponseType::Result(ResponseResult::Restart(format!("restarting process with name {}, starting replacement first", process.name())))
+        }
+        Ok(RestartStrategy::StopFirst) => {
+            proc_info!(&process, "restarting");
+            ResponseType::Result(ResponseResult::Restart(format!("restarting process with name {} ", process.name())))
+        }
+        Err(e) => ResponseType::Error(ResponseError {
+            code: ErrorCode::InvalidRequest,
+            message: format!("could not restart process with name {}: {e}", process.name()),
+            data: None,
+        }),
+    }
+}
+
+fn handle_request_reset(processes: &mut HashMap<String, Process>, request: &RequestReset) -> ResponseType {
+    let targets = match request.name() {
+        Some(name) => match processes.get_mut(name) {
+            Some(process) => vec![process],
+            None => {
+                log_error!("invalid reset request",; request = request);
+                return ResponseType::Error(ResponseError {
+                    code: ErrorCode::InvalidParams,
+                    message: format!("no process with name {name} found"),
+                    data: None,
+                });
+            }
+        },
+        None => processes.values_mut().collect(),
+    };
+
+    for process in targets {
+        process.set_manual_override(None);
+        process.clear_runtime_failures();
+        process.healthcheck_mut().clear_failures();
+        proc_info!(&process, "cleared manual override and failure counters");
+    }
+
+    ResponseType::Result(ResponseResult::Reset(match request.name() {
+        Some(name) => format!("reset process with name {name}"),
+        None => "reset all processes".to_string(),
+    }))
+}
+
+fn handle_request_reset_failed(processes: &mut HashMap<String, Process>, request: &RequestResetFailed) -> ResponseType {
+    let targets = match request.name() {
+        Some(name) => match processes.get_mut(name) {
+            Some(process) => vec![process],
+            None => {
+                log_error!("invalid reset_failed request",; request = request);
+                return ResponseType::Error(ResponseError {
+                    code: ErrorCode::InvalidParams,
+                    message: format!("no process with name {name} found"),
+                    data: None,
+                });
+            }
+        },
+        None => processes.values_mut().collect(),
+    };
+
+    let mut cleared = 0;
+    for process in targets {
+        if process.reset_failed() {
+            proc_info!(&process, "cleared fatal state");
+            cleared += 1;
+        }
+    }
+
+    ResponseType::Result(ResponseResult::ResetFailed(match request.name() {
+        Some(name) if cleared == 0 => format!("process with name {name} is not fatal"),
+        Some(name) => format!("cleared fatal state of process with name {name}"),
+        None => format!("cleared fatal state of {cleared} process(es)"),
+    }))
+}
+
+fn oneshot_process<'a>(processes: &'a mut HashMap<String, Process>, name: &str) -> Result<&'a mut Process, ResponseError> {
+    match processes.get_mut(name) {
+        Some(process) if process.config().process_type() == ProcessType::Oneshot => Ok(process),
+        Some(_) => Err(ResponseError {
+            code: ErrorCode::InvalidRequest,
+            message: format!("process with name {name} is not a oneshot job"),
+            data: None,
+        }),
+        None => Err(ResponseError {
+            code: ErrorCode::InvalidParams,
+            message: format!("no process with name {name} found"),
+            data: None,
+        }),
+    }
+}
+
+fn handle_request_history(processes: &mut HashMap<String, Process>, request: &RequestHistory) -> ResponseType {
+    let process = match oneshot_process(processes, request.name()) {
+        Ok(p) => p,
+        Err(e) => {
+            log_error!("invalid history request",; request = request);
+            return ResponseType::Error(e);
+        }
+    };
+
+    log_info!("getting run history for process",; process = process.name());
+
+    ResponseType::Result(ResponseResult::History(RunHistory::from_process(process)))
+}
+
+fn handle_request_run(processes: &mut HashMap<String, Process>, request: &RequestRun) -> ResponseType {
+    let process = match oneshot_process(processes, request.name()) {
+        Ok(p) => p,
+        Err(e) => {
+            log_error!("invalid run request",; request = request);
+            return ResponseType::Error(e);
+        }
+    };
+
+    if process.run_in_progress() {
+        return ResponseType::Error(ResponseError {
+            code: ErrorCode::InvalidRequest,
+            message: format!("a run of process with name {} is already in progress", process.name()),
+            data: None,
+        });
+    }
 
-    proc_info!(&process, "restarting");
+    if process.reset_failed() {
+        proc_info!(&process, "cleared fatal state");
+    }
+    let run = process.request_run(RunTrigger::Manual);
+    proc_info!(&process, "starting run",; run = run);
 
-    ResponseType::Result(ResponseResult::Restart(format!("restarting process with name {} ", process.name())))
+    ResponseType::Result(ResponseResult::Run(RunStarted::new(process.name(), run)))
 }
 
-fn handle_request_reload(daemon: &mut Daemon) -> ResponseType {
+fn handle_request_reload(daemon: &mut Daemon, request: &RequestReload) -> ResponseType {
+    if request.dry_run() {
+        return match daemon.reload_dry_run() {
+            Ok(report) => ResponseType::Result(ResponseResult::Reload(report)),
+            Err(e) => ResponseType::Error(ResponseError {
+                code: ErrorCode::InternalError,
+                message: format!("error while parsing config file: {e}"),
+                data: None,
+            }),
+        };
+    }
+
     match daemon.reload() {
-        Ok(()) => ResponseType::Result(ResponseResult::Reload),
+        Ok(report) => ResponseType::Result(ResponseResult::Reload(report)),
         Err(e) => ResponseType::Error(ResponseError {
             code: ErrorCode::InternalError,
-            message: format!("error while parsing config file: {e}"),
+            message: format!("error while parsing config file, still running on the last valid configuration: {e}"),
             data: None,
         }),
     }
 }
 
-fn handle_request_halt(daemon: &mut Daemon) -> ResponseType {
-    for (_name, proc) in daemon.processes_mut().iter_mut() {
-        proc.push_desired_state(ProcessState::Stopped);
+fn handle_request_rollback(daemon: &mut Daemon, request: &RequestRollback) -> ResponseType {
+    match daemon.rollback(request.steps()) {
+        Ok(report) => ResponseType::Result(ResponseResult::Rollback(report)),
+        Err(e) => {
+            log_error!("invalid rollback request",; request = request);
+            ResponseType::Error(ResponseError {
+                code: ErrorCode::InvalidParams,
+                message: e,
+                data: None,
+            })
+        }
+    }
+}
+
+fn handle_request_rolling_restart(daemon: &mut Daemon, request: &RequestRollingRestart) -> ResponseType {
+    match daemon.start_rolling_restart(request.name(), request.batch_size(), request.max_unavailable(), request.abort_on_failure()) {
+        Ok(progress) => ResponseType::Result(ResponseResult::RollingRestart(progress)),
+        Err(e) => {
+            log_error!("invalid rolling restart request",; request = request);
+            ResponseType::Error(ResponseError {
+                code: ErrorCode::InvalidParams,
+                message: e,
+                data: None,
+            })
+        }
+    }
+}
+
+fn handle_request_rolling_restart_status(daemon: &mut Daemon, request: &RequestRollingRestartStatus) -> ResponseType {
+    match daemon.rolling_restarts().progress(request.name()) {
+        Some(progress) => ResponseType::Result(ResponseResult::RollingRestartStatus(progress.to_owned())),
+        None => {
+            log_error!("invalid rolling restart status request",; request = request);
+            ResponseType::Error(ResponseError {
+                code: ErrorCode::InvalidParams,
+                message: format!("no rolling restart of {} found", request.name()),
+                data: None,
+            })
+        }
+    }
+}
+
+fn handle_request_scale(daemon: &mut Daemon, request: &RequestScale) -> ResponseType {
+    match daemon.scale(request.name(), request.processes()) {
+        Ok(previous) => {
+            ResponseType::Result(ResponseResult::Scale(format!("scaled {} from {previous} to {} instance(s)", request.name(), request.processes())))
+        }
+        Err(e) => {
+            log_error!("invalid scale request",; request = request);
+            ResponseType::Error(ResponseError {
+                code: ErrorCode::InvalidParams,
+                message: e,
+                data: None,
+            })
+        }
     }
-    daemon.shutdown();
+}
+
+fn handle_request_info(daemon: &mut Daemon) -> ResponseType {
+    log_info!("getting daemon info");
+
+    ResponseType::Result(ResponseResult::Info(DaemonInfo::from_daemon(daemon)))
+}
+
+fn handle_request_halt(daemon: &mut Daemon) -> ResponseType {
+    daemon.halt();
 
     log_info!("received halt command, shutting down engine");
 
@@ -334,11 +585,23 @@ mod tests {
     };
 
     use crate::{
-        conf::{Config, proc::ProcessConfig},
-        jsonrpc::{request::RequestType, short_process},
+        conf::{
+            Config,
+            proc::{
+                ProcessConfig,
+                types::{self, HealthCheck, StartLimit},
+            },
+        },
+        jsonrpc::{
+            reload_report::ReloadReport,
+            request::RequestType,
+            rolling_restart_progress::{InstanceStatus, RolloutStatus},
+            short_process,
+        },
     };
     static ID_COUNTER: AtomicU32 = AtomicU32::new(1);
     use super::*;
+    use crate::run::proc::history::RunOutcome;
 
     // Returns a 8 bytes random alphanumeric string.
     fn randstring() -> String {
@@ -355,7 +618,7 @@ mod tests {
 
         let _ = d.run_once().await;
 
-        let _ = handle_request(&mut d, Request::new(1, RequestType::new_status()));
+        let _ = handle_request(&mut d, Request::new(1, RequestType::new_status())).await;
         let response = handle_request(&mut d, Request::new(1, RequestType::new_halt())).await;
         assert!(matches!(response.response_type(), ResponseType::Result(_)));
     }
@@ -369,7 +632,7 @@ mod tests {
         let _ = d.run_once().await;
 
         handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_halt())).await;
-        assert_eq!(d.shutting_down(), true);
+        assert!(d.shutting_down());
     }
 
     #[tokio::test]
@@ -438,7 +701,7 @@ mod tests {
 
         match response.response_type() {
             ResponseType::Result(res) => match res {
-                ResponseResult::Status(status) => assert_eq!(*status.get(0).unwrap().state(), short_process::State::Idle),
+                ResponseResult::Status(status) => assert_eq!(*status.first().unwrap().state(), short_process::State::Idle),
                 _ => panic!("received unexpected response: {:?}", res),
             },
             ResponseType::Error(e) => panic!("handle_request returned an error: {:?}", e),
@@ -525,7 +788,7 @@ mod tests {
 
         let _ = d.run_once().await;
 
-        assert_eq!(d.processes().get("sleep").unwrap().config().autostart(), true);
+        assert!(d.processes().get("sleep").unwrap().config().autostart());
 
         let _ = fs::remove_file(&path);
 
@@ -543,7 +806,7 @@ mod tests {
         tokio::time::sleep(Duration::from_millis(100)).await;
         let _ = d.run_once().await;
 
-        assert_eq!(d.processes().get("sleep").unwrap().config().autostart(), false);
+        assert!(!d.processes().get("sleep").unwrap().config().autostart());
     }
 
     #[tokio::test]
@@ -604,94 +867,840 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn restart() {
-        let mut conf = Config::random();
-        let mut proc = ProcessConfig::default();
-        let proc = proc.set_cmd("sleep").set_args(vec!["2".to_string()]);
-        let conf = conf.add_process("sleep", proc.to_owned());
-        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+    async fn reload_dry_run_reports_without_applying() {
+        let conf = r#"
+        [processes.sleep]
+        cmd = "/usr/bin/sleep"
+        args = ["2"]
+        workingdir = "/tmp"
 
-        let _ = d.run_once().await;
+        [processes.gone]
+        cmd = "/usr/bin/sleep"
+        args = ["2"]
+        workingdir = "/tmp"
 
-        // Wait for process to be healthy
-        tokio::time::sleep(Duration::from_millis(1100)).await;
-        let _ = d.run_once().await;
-        assert_eq!(d.processes().get("sleep").unwrap().state(), ProcessState::Healthy);
+        [processes.same]
+        cmd = "/usr/bin/sleep"
+        args = ["2"]
+        workingdir = "/tmp"
+        "#;
+        let path = format!("/tmp/{}.toml", randstring());
+        let mut file = File::create(&path).unwrap();
+        let _ = File::write(&mut file, conf.as_bytes());
+        let mut conf = Config::from_file(&path).unwrap();
+        let conf = conf.set_socketpath(&format!("/tmp/{}.sock", randstring()));
+        let mut d = Daemon::from_config(conf.to_owned(), path.to_owned());
 
-        handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_restart("sleep"))).await;
-        let _ = d.run_once().await;
-        assert!(matches!(d.processes().get("sleep").unwrap().state(), ProcessState::Stopping(_)));
+        let changed_conf = r#"
+        [processes.sleep]
+        cmd = "/usr/bin/sleep"
+        args = ["3"]
+        workingdir = "/tmp"
+        stoptime = 1
 
-        tokio::time::sleep(Duration::from_millis(100)).await;
+        [processes.new]
+        cmd = "/usr/bin/sleep"
+        args = ["2"]
+        workingdir = "/tmp"
 
-        let _ = d.run_once().await;
-        assert_eq!(d.processes().get("sleep").unwrap().state(), ProcessState::Stopped);
+        [processes.same]
+        cmd = "/usr/bin/sleep"
+        args = ["2"]
+        workingdir = "/tmp"
+        "#;
+        let mut file = File::create(&path).unwrap();
+        let _ = File::write(&mut file, changed_conf.as_bytes());
 
-        let _ = d.run_once().await;
-        assert!(matches!(d.processes().get("sleep").unwrap().state(), ProcessState::HealthCheck(_)));
-    }
+        let mut expected = ReloadReport::new(true);
+        expected.add("new");
+        expected.remove("gone");
+        expected.restart("sleep", &["args", "stoptime"]);
+        expected.keep("same");
 
-    #[tokio::test]
-    async fn restart_nonexisting_process() {
-        let mut conf = Config::random();
-        let mut proc = ProcessConfig::default();
-        let proc = proc.set_cmd("sleep").set_args(vec!["2".to_string()]);
-        let conf = conf.add_process("sleep", proc.to_owned());
-        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+        let response =
+            handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_reload_dry_run())).await;
+        match response.response_type() {
+            ResponseType::Result(ResponseResult::Reload(report)) => assert_eq!(report, &expected),
+            _ => panic!("expected reload report, got {:?}", response.response_type()),
+        }
+        assert!(!d.processes().contains_key("new"));
+        assert_eq!(d.processes().get("sleep").unwrap().config().args(), &vec!["2".to_string()]);
 
-        let _ = d.run_once().await;
+        let mut expected = ReloadReport::new(false);
+        expected.add("new");
+        expected.remove("gone");
+        expected.restart("sleep", &["args", "stoptime"]);
+        expected.keep("same");
 
-        // Wait for process to be healthy
-        tokio::time::sleep(Duration::from_millis(1100)).await;
-        let _ = d.run_once().await;
-        assert_eq!(d.processes().get("sleep").unwrap().state(), ProcessState::Healthy);
+        let response = handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_reload())).await;
+        match response.response_type() {
+            ResponseType::Result(ResponseResult::Reload(report)) => assert_eq!(report, &expected),
+            _ => panic!("expected reload report, got {:?}", response.response_type()),
+        }
+        assert!(d.processes().contains_key("new"));
+        assert_eq!(d.processes().get("sleep").unwrap().config().args(), &vec!["3".to_string()]);
 
-        let response = handle_request(
-            &mut d,
-            Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_restart("notaprocess")),
-        )
-        .await;
-        assert!(matches!(response.response_type(), ResponseType::Error(_)));
+        let _ = fs::remove_file(&path);
     }
 
     #[tokio::test]
-    async fn start_already_running() {
-        let mut conf = Config::random();
-        let mut proc = ProcessConfig::default();
-        let proc = proc.set_cmd("sleep").set_args(vec!["2".to_string()]);
-        let conf = conf.add_process("sleep", proc.to_owned());
-        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+    async fn reload_live_fields_do_not_restart() {
+        let conf = r#"
+        [processes.sleep]
+        cmd = "/usr/bin/sleep"
+        args = ["5"]
+        workingdir = "/tmp"
+        autostart = true
+        [processes.sleep.healthcheck]
+        starttime = 1
+        "#;
+        let path = format!("/tmp/{}.toml", randstring());
+        let mut file = File::create(&path).unwrap();
+        let _ = File::write(&mut file, conf.as_bytes());
+        let mut conf = Config::from_file(&path).unwrap();
+        let conf = conf.set_socketpath(&format!("/tmp/{}.sock", randstring()));
+        let mut d = Daemon::from_config(conf.to_owned(), path.to_owned());
 
         let _ = d.run_once().await;
-
-        // Wait for process to be healthy
         tokio::time::sleep(Duration::from_millis(1100)).await;
         let _ = d.run_once().await;
         assert_eq!(d.processes().get("sleep").unwrap().state(), ProcessState::Healthy);
+        let pid = d.processes().get("sleep").unwrap().id();
+
+        let changed_conf = r#"
+        [processes.sleep]
+        cmd = "/usr/bin/sleep"
+        args = ["5"]
+        workingdir = "/tmp"
+        autostart = true
+        autorestart = "always"
+        exitcodes = [0, 1]
+        stopsignals = ["INT"]
+        stoptime = 1
+        [processes.sleep.healthcheck]
+        starttime = 1
+        retries = 1
+        "#;
+        let mut file = File::create(&path).unwrap();
+        let _ = File::write(&mut file, changed_conf.as_bytes());
+
+        let response = handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_reload())).await;
+        match response.response_type() {
+            ResponseType::Result(ResponseResult::Reload(report)) => {
+                assert!(report.restarted().is_empty());
+                assert_eq!(report.updated().len(), 1);
+                assert_eq!(report.updated()[0].fields(), ["autorestart", "exitcodes", "healthcheck.retries", "stopsignals", "stoptime"]);
+            }
+            _ => panic!("expected reload report, got {:?}", response.response_type()),
+        }
 
-        // Running start on a running process should have no effect.
-        handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_start("sleep"))).await;
         let _ = d.run_once().await;
-        assert_eq!(d.processes().get("sleep").unwrap().state(), ProcessState::Healthy);
+        let sleep = d.processes().get("sleep").unwrap();
+        assert_eq!(sleep.state(), ProcessState::Healthy);
+        assert_eq!(sleep.id(), pid);
+        assert_eq!(sleep.config().stoptime(), types::Duration::from_secs(1));
+        assert_eq!(sleep.healthcheck().retries(), 1);
+
+        let _ = fs::remove_file(&path);
     }
 
     #[tokio::test]
-    async fn start_error_nonexisting_process() {
-        let mut conf = Config::random();
-        let mut proc = ProcessConfig::default();
-        let proc = proc.set_cmd("sleep").set_args(vec!["2".to_string()]);
-        let conf = conf.add_process("sleep", proc.set_autostart(false).to_owned());
-        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+    async fn reload_scale_up_only_starts_new_instances() {
+        let conf = r#"
+        [processes.sleep]
+        cmd = "/usr/bin/sleep"
+        args = ["5"]
+        workingdir = "/tmp"
+        autostart = true
+        processes = 4
+        "#;
+        let path = format!("/tmp/{}.toml", randstring());
+        let mut file = File::create(&path).unwrap();
+        let _ = File::write(&mut file, conf.as_bytes());
+        let mut conf = Config::from_file(&path).unwrap();
+        let conf = conf.set_socketpath(&format!("/tmp/{}.sock", randstring()));
+        let mut d = Daemon::from_config(conf.to_owned(), path.to_owned());
 
         let _ = d.run_once().await;
+        let pids = (0..4)
+            .map(|i| d.processes().get(&format!("sleep_{i}")).unwrap().id())
+            .collect::<Vec<_>>();
+        assert!(pids.iter().all(Option::is_some));
 
-        assert_eq!(d.processes().get("sleep").unwrap().state(), ProcessState::Idle);
+        let changed_conf = r#"
+        [processes.sleep]
+        cmd = "/usr/bin/sleep"
+        args = ["5"]
+        workingdir = "/tmp"
+        autostart = true
+        processes = 6
+        "#;
+        let mut file = File::create(&path).unwrap();
+        let _ = File::write(&mut file, changed_conf.as_bytes());
 
-        let response = handle_request(
-            &mut d,
+        let response = handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_reload())).await;
+        match response.response_type() {
+            ResponseType::Result(ResponseResult::Reload(report)) => {
+                assert_eq!(report.added(), ["sleep_4", "sleep_5"]);
+                assert!(report.restarted().is_empty());
+            }
+            _ => panic!("expected reload report, got {:?}", response.response_type()),
+        }
+
+        let _ = d.run_once().await;
+        for (i, pid) in pids.iter().enumerate() {
+            assert_eq!(d.processes().get(&format!("sleep_{i}")).unwrap().id(), *pid);
+        }
+        assert!(d.processes().get("sleep_4").unwrap().id().is_some());
+        assert!(d.processes().get("sleep_5").unwrap().id().is_some());
+
+        for p in d.processes_mut().values_mut() {
+            let _ = p.kill_forcefully();
+        }
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn rollback_reapplies_previous_config() {
+        let conf = r#"
+        [processes.sleep]
+        cmd = "/usr/bin/sleep"
+        args = ["2"]
+        workingdir = "/tmp"
+        "#;
+        let path = format!("/tmp/{}.toml", randstring());
+        let mut file = File::create(&path).unwrap();
+        let _ = File::write(&mut file, conf.as_bytes());
+        let mut conf = Config::from_file(&path).unwrap();
+        let conf = conf.set_socketpath(&format!("/tmp/{}.sock", randstring()));
+        let mut d = Daemon::from_config(conf.to_owned(), path.to_owned());
+
+        let response = handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_rollback(1))).await;
+        assert!(matches!(response.response_type(), ResponseType::Error(_)));
+
+        let changed_conf = r#"
+        [processes.sleep]
+        cmd = "/usr/bin/sleep"
+        args = ["3"]
+        workingdir = "/tmp"
+        "#;
+        let mut file = File::create(&path).unwrap();
+        let _ = File::write(&mut file, changed_conf.as_bytes());
+        let _ = handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_reload())).await;
+        assert_eq!(d.processes().get("sleep").unwrap().config().args(), &vec!["3".to_string()]);
+        assert_eq!(d.snapshots().infos().len(), 2);
+
+        let response = handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_rollback(1))).await;
+        match response.response_type() {
+            ResponseType::Result(ResponseResult::Rollback(report)) => assert_eq!(report.restarted()[0].fields(), ["args"]),
+            _ => panic!("expected rollback report, got {:?}", response.response_type()),
+        }
+        assert_eq!(d.processes().get("sleep").unwrap().config().args(), &vec!["2".to_string()]);
+        assert_eq!(d.snapshots().infos().len(), 1);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn reload_invalid_config_keeps_last_valid() {
+        let conf = r#"
+        [processes.sleep]
+        cmd = "/usr/bin/sleep"
+        args = ["2"]
+        workingdir = "/tmp"
+        "#;
+        let path = format!("/tmp/{}.toml", randstring());
+        let mut file = File::create(&path).unwrap();
+        let _ = File::write(&mut file, conf.as_bytes());
+        let mut conf = Config::from_file(&path).unwrap();
+        let conf = conf.set_socketpath(&format!("/tmp/{}.sock", randstring()));
+        let mut d = Daemon::from_config(conf.to_owned(), path.to_owned());
+
+        let _ = d.run_once().await;
+
+        let invalid_conf = r#"
+        [processes.sleep]
+        cmd = "/usr/bin/sleep"
+        args = ["2"]
+        workingdir = "/tmp"
+        unknown_field = true
+        "#;
+        let mut file = File::create(&path).unwrap();
+        let _ = File::write(&mut file, invalid_conf.as_bytes());
+
+        let response = handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_reload())).await;
+        assert!(matches!(response.response_type(), ResponseType::Error(_)));
+        assert!(d.processes().contains_key("sleep"));
+        assert!(d.last_failed_reload().is_some());
+
+        let response = handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_info())).await;
+        match response.response_type() {
+            ResponseType::Result(ResponseResult::Info(info)) => {
+                let failed = info.last_failed_reload().as_ref().unwrap();
+                assert_eq!(failed.config_path(), path);
+                assert!(failed.error().contains("unknown_field"));
+            }
+            _ => panic!("expected info response, got {:?}", response.response_type()),
+        }
+
+        let fixed_conf = r#"
+        [processes.sleep]
+        cmd = "/usr/bin/sleep"
+        args = ["3"]
+        workingdir = "/tmp"
+        "#;
+        let mut file = File::create(&path).unwrap();
+        let _ = File::write(&mut file, fixed_conf.as_bytes());
+
+        let response = handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_reload())).await;
+        assert!(matches!(response.response_type(), ResponseType::Result(_)));
+        assert!(d.last_failed_reload().is_none());
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn stop_survives_daemon_restart_until_reset() {
+        let mut conf = Config::random();
+        let mut proc = ProcessConfig::default();
+        let conf = conf
+            .add_process(
+                "worker",
+                proc.set_cmd("sleep")
+                    .set_args(vec!["2".to_string()])
+                    .set_autostart(true)
+                    .to_owned(),
+            )
+            .set_statefile(&format!("/tmp/{}.state", randstring()));
+        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+
+        let _ = handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_stop("worker"))).await;
+        d.persist_state();
+
+        let mut restarted = Daemon::from_config(conf.to_owned(), "path".to_string());
+        restarted.restore_state();
+        assert_eq!(restarted.processes()["worker"].manual_override(), Some(ManualOverride::Stopped));
+        assert!(restarted.processes()["worker"].desired_states().is_empty());
+
+        let response = handle_request(
+            &mut restarted,
+            Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_reset(None)),
+        )
+        .await;
+        assert!(matches!(response.response_type(), ResponseType::Result(ResponseResult::Reset(_))));
+        restarted.persist_state();
+
+        let mut restarted = Daemon::from_config(conf.to_owned(), "path".to_string());
+        restarted.restore_state();
+        assert_eq!(restarted.processes()["worker"].manual_override(), None);
+        assert_eq!(restarted.processes()["worker"].desired_states().front(), Some(&ProcessState::Ready));
+
+        let _ = fs::remove_file(conf.statefile());
+    }
+
+    #[tokio::test]
+    async fn restart() {
+        let mut conf = Config::random();
+        let mut proc = ProcessConfig::default();
+        let proc = proc.set_cmd("sleep").set_args(vec!["2".to_string()]);
+        let conf = conf.add_process("sleep", proc.to_owned());
+        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+
+        let _ = d.run_once().await;
+
+        // Wait for process to be healthy
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let _ = d.run_once().await;
+        assert_eq!(d.processes().get("sleep").unwrap().state(), ProcessState::Healthy);
+
+        handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_restart("sleep"))).await;
+        let _ = d.run_once().await;
+        assert!(matches!(d.processes().get("sleep").unwrap().state(), ProcessState::Stopping(_)));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let _ = d.run_once().await;
+        assert_eq!(d.processes().get("sleep").unwrap().state(), ProcessState::Stopped);
+
+        let _ = d.run_once().await;
+        assert!(matches!(d.processes().get("sleep").unwrap().state(), ProcessState::HealthCheck(_)));
+    }
+
+    #[tokio::test]
+    async fn restart_start_first() {
+        let mut conf = Config::random();
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("sleep")
+            .set_args(vec!["10".to_string()])
+            .set_restart_strategy(RestartStrategy::StartFirst);
+        let conf = conf.add_process("sleep", proc.to_owned());
+        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+
+        let _ = d.run_once().await;
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let _ = d.run_once().await;
+        let old_pid = d.processes()["sleep"].id().unwrap();
+
+        handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_restart("sleep"))).await;
+        let _ = d.run_once().await;
+        assert_eq!(d.processes()["sleep"].state(), ProcessState::Healthy);
+        assert!(matches!(d.processes()["sleep"].replacement().unwrap().state(), ProcessState::HealthCheck(_)));
+        assert_eq!(d.processes()["sleep"].id(), Some(old_pid));
+
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let _ = d.run_once().await;
+        assert_eq!(d.processes()["sleep"].state(), ProcessState::Healthy);
+        assert!(d.processes()["sleep"].replacement().is_none());
+        assert_ne!(d.processes()["sleep"].id(), Some(old_pid));
+        assert_eq!(d.processes()["sleep"].retiring().len(), 1);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let _ = d.run_once().await;
+        assert!(d.processes()["sleep"].retiring().is_empty());
+        assert!(d.processes()["sleep"].restart_failure().is_none());
+    }
+
+    #[tokio::test]
+    async fn restart_start_first_counts_as_restart() {
+        let mut conf = Config::random();
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("sleep")
+            .set_args(vec!["10".to_string()])
+            .set_restart_strategy(RestartStrategy::StartFirst);
+        let conf = conf.add_process("sleep", proc.to_owned());
+        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+
+        let _ = d.run_once().await;
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let _ = d.run_once().await;
+        let old_started_at = d.processes()["sleep"].started_at().unwrap();
+
+        handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_restart("sleep"))).await;
+        let _ = d.run_once().await;
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let _ = d.run_once().await;
+        assert!(d.processes()["sleep"].replacement().is_none());
+
+        // Uptime and restarts are the ones of the new instance.
+        assert!(d.processes()["sleep"].started_at().unwrap() > old_started_at);
+        assert!(d.processes()["sleep"].uptime().unwrap() < Duration::from_millis(2000));
+        assert_eq!(d.processes()["sleep"].restarts(), 1);
+        d.kill_all();
+    }
+
+    #[tokio::test]
+    async fn restart_start_first_keeps_old_instance_on_failure() {
+        let marker = format!("/tmp/{}", randstring());
+        let mut hc = HealthCheck::default();
+        let hc = hc.set_retries(0);
+        let mut conf = Config::random();
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("sh")
+            .set_args(vec!["-c".to_string(), format!("[ -e {marker} ] && exit 1; touch {marker}; sleep 10")])
+            .set_healthcheck(hc.to_owned())
+            .set_restart_strategy(RestartStrategy::StartFirst);
+        let conf = conf.add_process("sh", proc.to_owned());
+        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+
+        let _ = d.run_once().await;
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let _ = d.run_once().await;
+        let old_pid = d.processes()["sh"].id().unwrap();
+
+        handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_restart("sh"))).await;
+        for _ in 0..20 {
+            let _ = d.run_once().await;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        assert_eq!(d.processes()["sh"].state(), ProcessState::Healthy);
+        assert_eq!(d.processes()["sh"].id(), Some(old_pid));
+        assert!(d.processes()["sh"].replacement().is_none());
+        assert!(d.processes()["sh"].restart_failure().is_some());
+        let _ = std::fs::remove_file(&marker);
+    }
+
+    #[tokio::test]
+    async fn rolling_restart_one_instance_at_a_time() {
+        let mut conf = Config::random();
+        let mut proc = ProcessConfig::default();
+        let proc = proc.set_cmd("sleep").set_args(vec!["10".to_string()]).set_processes(2);
+        let conf = conf.add_process("sleep", proc.to_owned());
+        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+
+        let _ = d.run_once().await;
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let _ = d.run_once().await;
+        let old_pids = ["sleep_0", "sleep_1"].map(|name| d.processes()[name].id().unwrap());
+
+        let response = handle_request(
+            &mut d,
+            Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_rolling_restart("sleep", 1, 1, true)),
+        )
+        .await;
+        assert!(matches!(response.response_type(), ResponseType::Result(ResponseResult::RollingRestart(_))));
+
+        let started = std::time::Instant::now();
+        while !d.rolling_restarts().progress("sleep").unwrap().finished() && started.elapsed() < Duration::from_secs(10) {
+            let _ = d.run_once().await;
+            let unavailable = d.processes().values().filter(|p| p.state() != ProcessState::Healthy).count();
+            assert!(unavailable <= 1);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let response = handle_request(
+            &mut d,
+            Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_rolling_restart_status("sleep")),
+        )
+        .await;
+        let ResponseType::Result(ResponseResult::RollingRestartStatus(progress)) = response.response_type() else {
+            panic!("expected rolling restart progress, got {response:?}");
+        };
+        assert_eq!(*progress.status(), RolloutStatus::Completed);
+        assert!(progress.instances().iter().all(|i| *i.status() == InstanceStatus::Done));
+        for (name, old_pid) in ["sleep_0", "sleep_1"].into_iter().zip(old_pids) {
+            assert_ne!(d.processes()[name].id(), Some(old_pid));
+        }
+    }
+
+    #[tokio::test]
+    async fn rolling_restart_waits_for_healthcheck_retries() {
+        // Fails every other attempt, so each restarted instance needs one retry.
+        let marker = format!("/tmp/{}_rolling_restart_retries", randstring());
+        let mut hc = HealthCheck::new();
+        let hc = hc
+            .set_check(types::HealthCheckType::Command(types::CommandHealthCheck {
+                cmd: "/bin/sh".to_string(),
+                args: vec!["-c".to_string(), format!("rm {marker} 2>/dev/null || {{ touch {marker}; exit 1; }}")],
+                timeout: types::Duration::from_secs(1),
+            }))
+            .set_backoff(0)
+            .set_retries(3);
+        let mut conf = Config::random();
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("sleep")
+            .set_args(vec!["10".to_string()])
+            .set_processes(2)
+            .set_healthcheck(hc.to_owned());
+        let conf = conf.add_process("sleep", proc.to_owned());
+        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+
+        let started = std::time::Instant::now();
+        while d.processes().values().any(|p| p.state() != ProcessState::Healthy) && started.elapsed() < Duration::from_secs(10) {
+            let _ = d.run_once().await;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let response = handle_request(
+            &mut d,
+            Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_rolling_restart("sleep", 1, 1, true)),
+        )
+        .await;
+        assert!(matches!(response.response_type(), ResponseType::Result(ResponseResult::RollingRestart(_))));
+
+        let started = std::time::Instant::now();
+        while !d.rolling_restarts().progress("sleep").unwrap().finished() && started.elapsed() < Duration::from_secs(10) {
+            let _ = d.run_once().await;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let progress = d.rolling_restarts().progress("sleep").unwrap();
+        assert_eq!(*progress.status(), RolloutStatus::Completed);
+        assert!(progress.instances().iter().all(|i| *i.status() == InstanceStatus::Done));
+        let _ = fs::remove_file(&marker);
+    }
+
+    #[tokio::test]
+    async fn rolling_restart_skips_stopped_instances() {
+        let mut conf = Config::random();
+        let mut proc = ProcessConfig::default();
+        let proc = proc.set_cmd("sleep").set_args(vec!["10".to_string()]).set_processes(2);
+        let conf = conf.add_process("sleep", proc.to_owned());
+        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+
+        let _ = d.run_once().await;
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let _ = d.run_once().await;
+        handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_stop("sleep_1"))).await;
+        let started = std::time::Instant::now();
+        while !matches!(d.processes()["sleep_1"].state(), ProcessState::Stopped | ProcessState::Idle) && started.elapsed() < Duration::from_secs(10) {
+            let _ = d.run_once().await;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let response = handle_request(
+            &mut d,
+            Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_rolling_restart("sleep", 1, 1, true)),
+        )
+        .await;
+        assert!(matches!(response.response_type(), ResponseType::Result(ResponseResult::RollingRestart(_))));
+
+        let started = std::time::Instant::now();
+        while !d.rolling_restarts().progress("sleep").unwrap().finished() && started.elapsed() < Duration::from_secs(10) {
+            let _ = d.run_once().await;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let progress = d.rolling_restarts().progress("sleep").unwrap();
+        assert_eq!(*progress.status(), RolloutStatus::Completed);
+        assert_eq!(*progress.instances()[0].status(), InstanceStatus::Done);
+        assert_eq!(*progress.instances()[1].status(), InstanceStatus::Skipped);
+        assert!(matches!(d.processes()["sleep_1"].state(), ProcessState::Stopped | ProcessState::Idle));
+    }
+
+    #[tokio::test]
+    async fn rolling_restart_fails_without_room_to_restart() {
+        // Instances exit right away once the marker exists, so the first restarted one
+        // gives up and takes the only unavailable slot for good.
+        let marker = format!("/tmp/{}_rolling_restart_stall", randstring());
+        let mut hc = HealthCheck::new();
+        let hc = hc.set_backoff(0).set_retries(0);
+        let mut conf = Config::random();
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("/bin/sh")
+            .set_args(vec!["-c".to_string(), format!("[ -e {marker} ] && exit 1; exec sleep 10")])
+            .set_processes(3)
+            .set_healthcheck(hc.to_owned());
+        let conf = conf.add_process("sh", proc.to_owned());
+        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+
+        let started = std::time::Instant::now();
+        while d.processes().values().any(|p| p.state() != ProcessState::Healthy) && started.elapsed() < Duration::from_secs(10) {
+            let _ = d.run_once().await;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        fs::write(&marker, "").unwrap();
+
+        let response = handle_request(
+            &mut d,
+            Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_rolling_restart("sh", 1, 1, false)),
+        )
+        .await;
+        assert!(matches!(response.response_type(), ResponseType::Result(ResponseResult::RollingRestart(_))));
+
+        let started = std::time::Instant::now();
+        while !d.rolling_restarts().progress("sh").unwrap().finished() && started.elapsed() < Duration::from_secs(10) {
+            let _ = d.run_once().await;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let progress = d.rolling_restarts().progress("sh").unwrap();
+        assert!(matches!(progress.status(), RolloutStatus::Failed(_)), "{progress}");
+        assert!(matches!(progress.instances()[0].status(), InstanceStatus::Failed(_)));
+        assert_eq!(*progress.instances()[1].status(), InstanceStatus::Pending);
+        assert_eq!(*progress.instances()[2].status(), InstanceStatus::Pending);
+        let _ = fs::remove_file(&marker);
+    }
+
+    #[tokio::test]
+    async fn rolling_restart_single_instance() {
+        let mut conf = Config::random();
+        let mut proc = ProcessConfig::default();
+        let proc = proc.set_cmd("sleep").set_args(vec!["10".to_string()]);
+        let conf = conf.add_process("sleep", proc.to_owned());
+        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+
+        let response = handle_request(
+            &mut d,
+            Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_rolling_restart("sleep", 1, 1, false)),
+        )
+        .await;
+        assert!(matches!(response.response_type(), ResponseType::Error(_)));
+    }
+
+    #[tokio::test]
+    async fn restart_nonexisting_process() {
+        let mut conf = Config::random();
+        let mut proc = ProcessConfig::default();
+        let proc = proc.set_cmd("sleep").set_args(vec!["2".to_string()]);
+        let conf = conf.add_process("sleep", proc.to_owned());
+        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+
+        let _ = d.run_once().await;
+
+        // Wait for process to be healthy
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let _ = d.run_once().await;
+        assert_eq!(d.processes().get("sleep").unwrap().state(), ProcessState::Healthy);
+
+        let response = handle_request(
+            &mut d,
+            Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_restart("notaprocess")),
+        )
+        .await;
+        assert!(matches!(response.response_type(), ResponseType::Error(_)));
+    }
+
+    #[tokio::test]
+    async fn start_already_running() {
+        let mut conf = Config::random();
+        let mut proc = ProcessConfig::default();
+        let proc = proc.set_cmd("sleep").set_args(vec!["2".to_string()]);
+        let conf = conf.add_process("sleep", proc.to_owned());
+        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+
+        let _ = d.run_once().await;
+
+        // Wait for process to be healthy
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let _ = d.run_once().await;
+        assert_eq!(d.processes().get("sleep").unwrap().state(), ProcessState::Healthy);
+
+        // Running start on a running process should have no effect.
+        handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_start("sleep"))).await;
+        let _ = d.run_once().await;
+        assert_eq!(d.processes().get("sleep").unwrap().state(), ProcessState::Healthy);
+    }
+
+    #[tokio::test]
+    async fn start_clears_fatal_state() {
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("sh")
+            .set_args(vec!["-c".to_string(), "exit 1".to_string()])
+            .set_startlimit(StartLimit::new(0, types::Duration::from_secs(60)));
+        let mut conf = Config::random();
+        let conf = conf.add_process("sleep", proc.to_owned());
+        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+
+        let started = std::time::Instant::now();
+        while !matches!(d.processes()["sleep"].state(), ProcessState::Fatal(_)) && started.elapsed() < Duration::from_secs(3) {
+            let _ = d.run_once().await;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let response = handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_status())).await;
+        match response.response_type() {
+            ResponseType::Result(ResponseResult::Status(processes)) => {
+                assert_eq!(*processes[0].state(), short_process::State::Fatal("failed 1 time(s) within 60s".to_string()))
+            }
+            _ => panic!("unexpected response: {response:?}"),
+        }
+
+        let response = handle_request(
+            &mut d,
+            Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_reset_failed(Some("sleep"))),
+        )
+        .await;
+        assert!(matches!(response.response_type(), ResponseType::Result(ResponseResult::ResetFailed(_))));
+        assert_eq!(d.processes()["sleep"].state(), ProcessState::Stopped);
+
+        // Only `start` runs it again.
+        let _ = d.run_once().await;
+        assert_eq!(d.processes()["sleep"].state(), ProcessState::Stopped);
+        handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_start("sleep"))).await;
+        let _ = d.run_once().await;
+        assert!(matches!(d.processes()["sleep"].state(), ProcessState::HealthCheck(_)));
+    }
+
+    #[tokio::test]
+    async fn start_error_nonexisting_process() {
+        let mut conf = Config::random();
+        let mut proc = ProcessConfig::default();
+        let proc = proc.set_cmd("sleep").set_args(vec!["2".to_string()]);
+        let conf = conf.add_process("sleep", proc.set_autostart(false).to_owned());
+        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+
+        let _ = d.run_once().await;
+
+        assert_eq!(d.processes().get("sleep").unwrap().state(), ProcessState::Idle);
+
+        let response = handle_request(
+            &mut d,
             Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_start("notaprocess")),
         )
         .await;
         assert!(matches!(response.response_type(), ResponseType::Error(_)));
     }
+
+    #[tokio::test]
+    async fn run_oneshot_and_read_history() {
+        let mut conf = Config::random();
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("sh")
+            .set_args(vec!["-c".to_string(), "exit 0".to_string()])
+            .set_autostart(false)
+            .set_process_type(types::ProcessType::Oneshot);
+        let conf = conf.add_process("job", proc.to_owned());
+        let conf = conf.add_process("service", ProcessConfig::default().set_autostart(false).to_owned());
+        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+
+        let response =
+            handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_run("service"))).await;
+        assert!(matches!(response.response_type(), ResponseType::Error(_)));
+
+        let response = handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_run("job"))).await;
+        let ResponseType::Result(ResponseResult::Run(started)) = response.response_type() else {
+            panic!("expected run started, got {response:?}");
+        };
+        assert_eq!(started.run(), 1);
+
+        let response = handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_run("job"))).await;
+        assert!(matches!(response.response_type(), ResponseType::Error(_)));
+
+        let started = std::time::Instant::now();
+        while d.processes()["job"].run_in_progress() && started.elapsed() < Duration::from_secs(3) {
+            let _ = d.run_once().await;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let response =
+            handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_history("job"))).await;
+        let ResponseType::Result(ResponseResult::History(history)) = response.response_type() else {
+            panic!("expected run history, got {response:?}");
+        };
+        assert!(!history.pending());
+        assert_eq!(history.runs().len(), 1);
+        assert_eq!(history.runs()[0].id(), 1);
+        assert_eq!(history.runs()[0].trigger(), RunTrigger::Manual);
+        assert_eq!(history.runs()[0].outcome(), RunOutcome::Exited(0));
+        assert!(history.runs()[0].success());
+    }
+
+    #[tokio::test]
+    async fn describe() {
+        let mut conf = Config::random();
+        let mut proc = ProcessConfig::default();
+        let proc = proc.set_cmd("sleep").set_args(vec!["10".to_string()]);
+        let conf = conf.add_process("sleep", proc.to_owned());
+        let mut d = Daemon::from_config(conf.to_owned(), "path".to_string());
+
+        let _ = d.run_once().await;
+        handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_stop("sleep"))).await;
+
+        let request = Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_describe("sleep"));
+        let request_type = serde_json::from_str(&serde_json::to_string(request.request_type()).unwrap()).unwrap();
+        let response = handle_request(&mut d, request).await;
+
+        // The shell only gets to see the JSON.
+        let mut response = serde_json::from_str::<Response>(&serde_json::to_string(&response).unwrap()).unwrap();
+        response.set_response_result(&request_type);
+        let ResponseType::Result(ResponseResult::Describe(details)) = response.response_type() else {
+            panic!("expected process details, got {response:?}");
+        };
+        assert_eq!(details.process().pid(), d.processes()["sleep"].id());
+        assert!(details.process().uptime().is_some());
+        assert!(details.started_at().is_some());
+        assert_eq!(details.process().restarts(), 0);
+        assert_eq!(details.desired_states(), [short_process::State::Idle]);
+
+        let started = std::time::Instant::now();
+        while d.processes()["sleep"].state() != ProcessState::Stopped && started.elapsed() < Duration::from_secs(3) {
+            let _ = d.run_once().await;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let response =
+            handle_request(&mut d, Request::new(ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed), RequestType::new_describe("sleep"))).await;
+        let ResponseType::Result(ResponseResult::Describe(details)) = response.response_type() else {
+            panic!("expected process details, got {response:?}");
+        };
+        assert_eq!(details.process().pid(), None);
+        assert!(details.process().last_exit().is_some());
+        assert_eq!(details.manual_override(), Some(ManualOverride::Stopped));
+    }
 }
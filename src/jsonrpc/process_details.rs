@@ -0,0 +1,129 @@
+use std::{fmt, time::UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::short_process::{ShortProcess, State, compact_duration};
+use crate::{
+    conf::proc::types::TimeZone,
+    run::{
+        proc::{ManualOverride, Process},
+        statemachine::states::ProcessState,
+    },
+};
+
+/// # `ProcessDetails`
+/// `src/jsonrpc/process_details.rs`
+///
+/// Everything taskmaster knows about the state of a single process, returned by
+/// `describe`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProcessDetails {
+    process: ShortProcess,
+    /// State the process was in when it failed, while it is `Failed`.
+    failed_in: Option<State>,
+    /// Unix timestamp of the last time the process was spawned.
+    started_at: Option<u64>,
+    runtime_failures: usize,
+    healthcheck_failures: usize,
+    manual_override: Option<ManualOverride>,
+    /// States the process is going to be moved to, in order.
+    desired_states: Vec<State>,
+}
+
+impl ProcessDetails {
+    pub fn from_process(process: &Process) -> Self {
+        Self {
+            process: ShortProcess::from_process(process),
+            failed_in: match process.state() {
+                ProcessState::Failed(prev_state) => Some(State::from_process_state(*prev_state)),
+                _ => None,
+            },
+            started_at: process
+                .started_at()
+                .and_then(|at| at.duration_since(UNIX_EPOCH).ok())
+                .map(|since_epoch| since_epoch.as_secs()),
+            runtime_failures: process.runtime_failures(),
+            healthcheck_failures: process.healthcheck_failures(),
+            manual_override: process.manual_override(),
+            desired_states: process
+                .desired_states()
+                .iter()
+                .cloned()
+                .map(State::from_process_state)
+                .collect(),
+        }
+    }
+
+    pub fn process(&self) -> &ShortProcess {
+        &self.process
+    }
+
+    pub fn failed_in(&self) -> Option<&State> {
+        self.failed_in.as_ref()
+    }
+
+    pub fn started_at(&self) -> Option<u64> {
+        self.started_at
+    }
+
+    pub fn runtime_failures(&self) -> usize {
+        self.runtime_failures
+    }
+
+    pub fn healthcheck_failures(&self) -> usize {
+        self.healthcheck_failures
+    }
+
+    pub fn manual_override(&self) -> Option<ManualOverride> {
+        self.manual_override
+    }
+
+    pub fn desired_states(&self) -> &[State] {
+        &self.desired_states
+    }
+}
+
+impl fmt::Display for ProcessDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let process = &self.process;
+        write!(f, "{process}")?;
+        if let Some(failed_in) = &self.failed_in {
+            write!(f, "\n  failed in: {failed_in}")?;
+        }
+        if let Some(pid) = process.pid() {
+            write!(f, "\n  pid: {pid}")?;
+        }
+        if let Some(started_at) = self.started_at {
+            write!(f, "\n  last started: {}", TimeZone::local().format(started_at as i64))?;
+        }
+        if let Some(uptime) = process.uptime() {
+            write!(f, "\n  uptime: {}", compact_duration(uptime))?;
+        }
+        if let Some(resources) = process.resources() {
+            write!(f, "\n  resources: {resources}")?;
+        }
+        write!(f, "\n  restarts: {}", process.restarts())?;
+        if let Some(last_exit) = process.last_exit() {
+            write!(f, "\n  last exit: {last_exit}")?;
+        }
+        write!(f, "\n  runtime failures: {}", self.runtime_failures)?;
+        write!(f, "\n  healthcheck failures: {}", self.healthcheck_failures)?;
+        if let Some(manual_override) = self.manual_override {
+            let manual_override = match manual_override {
+                ManualOverride::Started => "started",
+                ManualOverride::Stopped => "stopped",
+            };
+            write!(f, "\n  {manual_override} by an operator")?;
+        }
+        if !self.desired_states.is_empty() {
+            let desired = self
+                .desired_states
+                .iter()
+                .map(State::to_string)
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            write!(f, "\n  pending: {desired}")?;
+        }
+        Ok(())
+    }
+}
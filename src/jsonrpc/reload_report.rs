@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+/// A process whose configuration changed, along with the config fields that differ.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ChangedProcess {
+    name: String,
+    fields: Vec<String>,
+}
+
+impl ChangedProcess {
+    pub fn new(name: &str, fields: &[&str]) -> Self {
+        Self {
+            name: name.to_owned(),
+            fields: fields.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+}
+
+/// # `ReloadReport`
+/// `src/jsonrpc/reload_report.rs`
+///
+/// Outcome of comparing a freshly parsed configuration against the running processes.
+/// Returned by both `reload` and `reload --dry-run`, in the latter case nothing
+/// listed in it has been applied.
+///
+/// Processes in `updated` only had fields change which can be applied while they are
+/// running, processes in `restarted` had at least one field change requiring a restart.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ReloadReport {
+    dry_run: bool,
+    added: Vec<String>,
+    removed: Vec<String>,
+    restarted: Vec<ChangedProcess>,
+    updated: Vec<ChangedProcess>,
+    unchanged: Vec<String>,
+}
+
+impl ReloadReport {
+    pub fn new(dry_run: bool) -> Self {
+        Self { dry_run, ..Default::default() }
+    }
+
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    pub fn added(&self) -> &[String] {
+        &self.added
+    }
+
+    pub fn removed(&self) -> &[String] {
+        &self.removed
+    }
+
+    pub fn restarted(&self) -> &[ChangedProcess] {
+        &self.restarted
+    }
+
+    pub fn updated(&self) -> &[ChangedProcess] {
+        &self.updated
+    }
+
+    pub fn unchanged(&self) -> &[String] {
+        &self.unchanged
+    }
+
+    pub fn add(&mut self, name: &str) {
+        self.added.push(name.to_owned());
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.removed.push(name.to_owned());
+    }
+
+    pub fn restart(&mut self, name: &str, fields: &[&str]) {
+        self.restarted.push(ChangedProcess::new(name, fields));
+    }
+
+    pub fn update(&mut self, name: &str, fields: &[&str]) {
+        self.updated.push(ChangedProcess::new(name, fields));
+    }
+
+    pub fn keep(&mut self, name: &str) {
+        self.unchanged.push(name.to_owned());
+    }
+
+    /// Sorts all lists by process name, `HashMap` iteration order is not stable.
+    pub fn sort(&mut self) {
+        self.added.sort();
+        self.removed.sort();
+        self.restarted.sort_by(|a, b| a.name.cmp(&b.name));
+        self.updated.sort_by(|a, b| a.name.cmp(&b.name));
+        self.unchanged.sort();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.restarted.is_empty() && self.updated.is_empty()
+    }
+}
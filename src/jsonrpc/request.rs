@@ -54,9 +54,19 @@ pub enum RequestType {
     Start(RequestStart),
     Stop(RequestStop),
     Restart(RequestRestart),
-    Reload,
+    Reload(RequestReload),
     Halt,
     Attach(RequestAttach),
+    Info,
+    Rollback(RequestRollback),
+    Reset(RequestReset),
+    RollingRestart(RequestRollingRestart),
+    RollingRestartStatus(RequestRollingRestartStatus),
+    Scale(RequestScale),
+    ResetFailed(RequestResetFailed),
+    History(RequestHistory),
+    Run(RequestRun),
+    Describe(RequestDescribe),
 }
 
 impl RequestType {
@@ -89,13 +99,85 @@ impl RequestType {
     }
 
     pub fn new_reload() -> Self {
-        Self::Reload
+        Self::Reload(RequestReload::default())
+    }
+
+    pub fn new_reload_dry_run() -> Self {
+        Self::Reload(RequestReload {
+            params: ParamsReload { dry_run: true },
+        })
     }
 
     pub fn new_halt() -> Self {
         Self::Halt
     }
 
+    pub fn new_info() -> Self {
+        Self::Info
+    }
+
+    pub fn new_reset(name: Option<&str>) -> Self {
+        Self::Reset(RequestReset {
+            params: ParamsOptionalName { name: name.map(str::to_owned) },
+        })
+    }
+
+    pub fn new_rollback(steps: usize) -> Self {
+        Self::Rollback(RequestRollback {
+            params: ParamsRollback { steps },
+        })
+    }
+
+    pub fn new_rolling_restart(name: &str, batch_size: usize, max_unavailable: usize, abort_on_failure: bool) -> Self {
+        Self::RollingRestart(RequestRollingRestart {
+            params: ParamsRollingRestart {
+                name: name.to_owned(),
+                batch_size,
+                max_unavailable,
+                abort_on_failure,
+            },
+        })
+    }
+
+    pub fn new_rolling_restart_status(name: &str) -> Self {
+        Self::RollingRestartStatus(RequestRollingRestartStatus {
+            params: ParamsName { name: name.to_owned() },
+        })
+    }
+
+    pub fn new_scale(name: &str, processes: u16) -> Self {
+        Self::Scale(RequestScale {
+            params: ParamsScale {
+                name: name.to_owned(),
+                processes,
+            },
+        })
+    }
+
+    pub fn new_reset_failed(name: Option<&str>) -> Self {
+        Self::ResetFailed(RequestResetFailed {
+            params: ParamsOptionalName { name: name.map(str::to_owned) },
+        })
+    }
+
+    pub fn new_history(name: &str) -> Self {
+        Self::History(RequestHistory {
+            params: ParamsName { name: name.to_owned() },
+        })
+    }
+
+    pub fn new_run(name: &str) -> Self {
+        Self::Run(RequestRun {
+            params: ParamsName { name: name.to_owned() },
+        })
+    }
+
+    pub fn new_describe(name: &str) -> Self {
+        Self::Describe(RequestDescribe {
+            params: ParamsName { name: name.to_owned() },
+        })
+    }
+
     pub fn new_attach(name: &str, to: &AttachFile) -> Self {
         Self::Attach(RequestAttach {
             params: ParamsName { name: name.to_owned() },
@@ -141,6 +223,86 @@ impl RequestStop {
     }
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Debug, Default)]
+pub struct ParamsReload {
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// `params` may be omitted entirely, `{"method": "reload"}` applies the new configuration.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Default)]
+pub struct RequestReload {
+    #[serde(default)]
+    params: ParamsReload,
+}
+
+impl RequestReload {
+    pub fn dry_run(&self) -> bool {
+        self.params.dry_run
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Default)]
+pub struct ParamsOptionalName {
+    #[serde(default)]
+    name: Option<String>,
+}
+
+/// Without a name, applies to all processes.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct RequestReset {
+    #[serde(default)]
+    params: ParamsOptionalName,
+}
+
+impl RequestReset {
+    pub fn name(&self) -> Option<&str> {
+        self.params.name.as_deref()
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct RequestResetFailed {
+    #[serde(default)]
+    params: ParamsOptionalName,
+}
+
+impl RequestResetFailed {
+    pub fn name(&self) -> Option<&str> {
+        self.params.name.as_deref()
+    }
+}
+
+fn dflt_rollback_steps() -> usize {
+    1
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct ParamsRollback {
+    /// How many configurations to go back, `1` being the one applied before the
+    /// active one.
+    #[serde(default = "dflt_rollback_steps")]
+    steps: usize,
+}
+
+impl Default for ParamsRollback {
+    fn default() -> Self {
+        Self { steps: dflt_rollback_steps() }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct RequestRollback {
+    #[serde(default)]
+    params: ParamsRollback,
+}
+
+impl RequestRollback {
+    pub fn steps(&self) -> usize {
+        self.params.steps
+    }
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
 pub enum AttachFile {
@@ -192,6 +354,121 @@ impl RequestRestart {
     }
 }
 
+fn dflt_rolling_restart_batch_size() -> usize {
+    1
+}
+
+fn dflt_rolling_restart_max_unavailable() -> usize {
+    1
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct ParamsRollingRestart {
+    /// Name of the program, without the `_<n>` suffix of its instances.
+    name: String,
+    /// How many instances are restarted at once.
+    #[serde(default = "dflt_rolling_restart_batch_size")]
+    batch_size: usize,
+    /// How many instances may be down at once, including ones not restarted by the
+    /// rolling restart.
+    #[serde(default = "dflt_rolling_restart_max_unavailable")]
+    max_unavailable: usize,
+    /// Stop restarting instances after the first one did not become healthy.
+    #[serde(default)]
+    abort_on_failure: bool,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct RequestRollingRestart {
+    params: ParamsRollingRestart,
+}
+
+impl RequestRollingRestart {
+    pub fn name(&self) -> &str {
+        &self.params.name
+    }
+
+    pub fn batch_size(&self) -> usize {
+        self.params.batch_size
+    }
+
+    pub fn max_unavailable(&self) -> usize {
+        self.params.max_unavailable
+    }
+
+    pub fn abort_on_failure(&self) -> bool {
+        self.params.abort_on_failure
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct RequestRollingRestartStatus {
+    params: ParamsName,
+}
+
+impl RequestRollingRestartStatus {
+    pub fn name(&self) -> &str {
+        &self.params.name
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct ParamsScale {
+    /// Name of the program, without the `_<n>` suffix of its instances.
+    name: String,
+    /// Number of instances to run.
+    processes: u16,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct RequestScale {
+    params: ParamsScale,
+}
+
+impl RequestScale {
+    pub fn name(&self) -> &str {
+        &self.params.name
+    }
+
+    pub fn processes(&self) -> u16 {
+        self.params.processes
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct RequestHistory {
+    params: ParamsName,
+}
+
+impl RequestHistory {
+    pub fn name(&self) -> &str {
+        &self.params.name
+    }
+}
+
+/// Starts a `oneshot` process, see [`crate::jsonrpc::run_history::RunStarted`].
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct RequestRun {
+    params: ParamsName,
+}
+
+impl RequestRun {
+    pub fn name(&self) -> &str {
+        &self.params.name
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct RequestDescribe {
+    params: ParamsName,
+}
+
+impl RequestDescribe {
+    pub fn name(&self) -> &str {
+        &self.params.name
+    }
+}
+
 mod test {
 
     #[test]
@@ -216,6 +493,37 @@ mod test {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_reload_without_params() {
+        let json = r#"{
+            "id": 4,
+            "json_rpc": "2.0",
+            "method": "reload"
+        }"#;
+
+        let request = serde_json::from_str::<super::Request>(json).unwrap();
+        match request.request_type() {
+            super::RequestType::Reload(reload) => assert!(!reload.dry_run()),
+            other => panic!("expected reload request, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_reload_dry_run() {
+        let json = r#"{
+            "id": 5,
+            "json_rpc": "2.0",
+            "method": "reload",
+            "params": { "dry_run": true }
+        }"#;
+
+        let request = serde_json::from_str::<super::Request>(json).unwrap();
+        match request.request_type() {
+            super::RequestType::Reload(reload) => assert!(reload.dry_run()),
+            other => panic!("expected reload request, got {other:?}"),
+        }
+    }
+
     #[test]
     fn test_invalid_params() {
         let json = r#"{
@@ -228,4 +536,17 @@ mod test {
         let result = serde_json::from_str::<super::Request>(json);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_rolling_restart_defaults() {
+        let json = r#"{
+            "id": 6,
+            "json_rpc": "2.0",
+            "method": "rolling_restart",
+            "params": { "name": "web" }
+        }"#;
+
+        let request = serde_json::from_str::<super::Request>(json).unwrap();
+        assert_eq!(*request.request_type(), super::RequestType::new_rolling_restart("web", 1, 1, false));
+    }
 }
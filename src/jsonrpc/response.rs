@@ -1,7 +1,12 @@
 use serde::{Deserialize, Serialize, Serializer, ser::Error};
 
 use super::{
+    daemon_info::DaemonInfo,
+    process_details::ProcessDetails,
+    reload_report::ReloadReport,
     request::{Request, RequestType},
+    rolling_restart_progress::RollingRestartProgress,
+    run_history::{RunHistory, RunStarted},
     short_process::ShortProcess,
 };
 
@@ -40,16 +45,38 @@ impl Response {
         match &self.response_type {
             ResponseType::Error(_) => {}
             ResponseType::Result(res) => match res {
-                ResponseResult::Status(_) | ResponseResult::StatusSingle(_) | ResponseResult::Attach { .. } => {}
-                ResponseResult::Start(msg) | ResponseResult::Stop(msg) | ResponseResult::Restart(msg) => match request_type {
+                ResponseResult::Status(_)
+                | ResponseResult::StatusSingle(_)
+                | ResponseResult::Attach { .. }
+                | ResponseResult::Info(_)
+                | ResponseResult::History(_)
+                | ResponseResult::Run(_)
+                | ResponseResult::Describe(_)
+                | ResponseResult::Halt => {}
+                ResponseResult::Reload(report) | ResponseResult::Rollback(report) => match request_type {
+                    RequestType::Reload(_) => self.response_type = ResponseType::Result(ResponseResult::Reload(report.to_owned())),
+                    RequestType::Rollback(_) => self.response_type = ResponseType::Result(ResponseResult::Rollback(report.to_owned())),
+                    _ => {}
+                },
+                ResponseResult::RollingRestart(progress) | ResponseResult::RollingRestartStatus(progress) => match request_type {
+                    RequestType::RollingRestart(_) => self.response_type = ResponseType::Result(ResponseResult::RollingRestart(progress.to_owned())),
+                    RequestType::RollingRestartStatus(_) => {
+                        self.response_type = ResponseType::Result(ResponseResult::RollingRestartStatus(progress.to_owned()))
+                    }
+                    _ => {}
+                },
+                ResponseResult::Start(msg)
+                | ResponseResult::Stop(msg)
+                | ResponseResult::Restart(msg)
+                | ResponseResult::Reset(msg)
+                | ResponseResult::Scale(msg)
+                | ResponseResult::ResetFailed(msg) => match request_type {
                     RequestType::Start(_) => self.response_type = ResponseType::Result(ResponseResult::Start(msg.to_owned())),
                     RequestType::Stop(_) => self.response_type = ResponseType::Result(ResponseResult::Stop(msg.to_owned())),
                     RequestType::Restart(_) => self.response_type = ResponseType::Result(ResponseResult::Restart(msg.to_owned())),
-                    _ => {}
-                },
-                ResponseResult::Reload | ResponseResult::Halt => match request_type {
-                    RequestType::Reload => self.response_type = ResponseType::Result(ResponseResult::Reload),
-                    RequestType::Halt => self.response_type = ResponseType::Result(ResponseResult::Halt),
+                    RequestType::Reset(_) => self.response_type = ResponseType::Result(ResponseResult::Reset(msg.to_owned())),
+                    RequestType::Scale(_) => self.response_type = ResponseType::Result(ResponseResult::Scale(msg.to_owned())),
+                    RequestType::ResetFailed(_) => self.response_type = ResponseType::Result(ResponseResult::ResetFailed(msg.to_owned())),
                     _ => {}
                 },
             },
@@ -80,13 +107,23 @@ pub enum ResponseType {
 #[serde(untagged)]
 pub enum ResponseResult {
     Status(Vec<ShortProcess>),
-    StatusSingle(ShortProcess),
+    StatusSingle(Box<ShortProcess>),
     Start(String),
     Stop(String),
     Restart(String),
-    Reload,
+    Reload(ReloadReport),
     Halt,
     Attach { name: String, socketpath: String, to: String },
+    Info(DaemonInfo),
+    Rollback(ReloadReport),
+    Reset(String),
+    RollingRestart(RollingRestartProgress),
+    RollingRestartStatus(RollingRestartProgress),
+    Scale(String),
+    ResetFailed(String),
+    History(RunHistory),
+    Run(RunStarted),
+    Describe(Box<ProcessDetails>),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
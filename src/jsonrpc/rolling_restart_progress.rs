@@ -0,0 +1,154 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RolloutStatus {
+    Running,
+    Completed,
+    /// Stopped restarting instances after one of them failed, see `abort_on_failure`.
+    Aborted,
+    /// Could not restart the remaining instances without exceeding `max_unavailable`.
+    Failed(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceStatus {
+    Pending,
+    Restarting,
+    Done,
+    Failed(String),
+    /// Not restarted, since an operator stopped it.
+    Skipped,
+}
+
+/// Progress of a single instance of the program.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct InstanceProgress {
+    name: String,
+    status: InstanceStatus,
+}
+
+impl InstanceProgress {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            status: InstanceStatus::Pending,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn status(&self) -> &InstanceStatus {
+        &self.status
+    }
+
+    pub fn set_status(&mut self, status: InstanceStatus) {
+        self.status = status;
+    }
+}
+
+/// # `RollingRestartProgress`
+/// `src/jsonrpc/rolling_restart_progress.rs`
+///
+/// State of a rolling restart of all instances of a program, returned by both
+/// `rolling_restart` and `rolling_restart_status`. Instances are listed in the order
+/// they are restarted in.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RollingRestartProgress {
+    program: String,
+    status: RolloutStatus,
+    batch_size: usize,
+    max_unavailable: usize,
+    abort_on_failure: bool,
+    instances: Vec<InstanceProgress>,
+}
+
+impl RollingRestartProgress {
+    pub fn new(program: &str, batch_size: usize, max_unavailable: usize, abort_on_failure: bool, instances: &[String]) -> Self {
+        Self {
+            program: program.to_owned(),
+            status: RolloutStatus::Running,
+            batch_size,
+            max_unavailable,
+            abort_on_failure,
+            instances: instances.iter().map(|name| InstanceProgress::new(name)).collect(),
+        }
+    }
+
+    pub fn program(&self) -> &str {
+        &self.program
+    }
+
+    pub fn status(&self) -> &RolloutStatus {
+        &self.status
+    }
+
+    pub fn set_status(&mut self, status: RolloutStatus) {
+        self.status = status;
+    }
+
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    pub fn max_unavailable(&self) -> usize {
+        self.max_unavailable
+    }
+
+    pub fn abort_on_failure(&self) -> bool {
+        self.abort_on_failure
+    }
+
+    pub fn instances(&self) -> &[InstanceProgress] {
+        &self.instances
+    }
+
+    pub fn instances_mut(&mut self) -> &mut [InstanceProgress] {
+        &mut self.instances
+    }
+
+    pub fn finished(&self) -> bool {
+        self.status != RolloutStatus::Running
+    }
+
+    fn count(&self, matches: impl Fn(&InstanceStatus) -> bool) -> usize {
+        self.instances.iter().filter(|i| matches(&i.status)).count()
+    }
+}
+
+impl fmt::Display for InstanceProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.status {
+            InstanceStatus::Pending => write!(f, "{}: pending", self.name),
+            InstanceStatus::Restarting => write!(f, "{}: restarting", self.name),
+            InstanceStatus::Done => write!(f, "{}: done", self.name),
+            InstanceStatus::Failed(reason) => write!(f, "{}: failed ({reason})", self.name),
+            InstanceStatus::Skipped => write!(f, "{}: skipped (stopped by an operator)", self.name),
+        }
+    }
+}
+
+impl fmt::Display for RollingRestartProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match &self.status {
+            RolloutStatus::Running => "running".to_string(),
+            RolloutStatus::Completed => "completed".to_string(),
+            RolloutStatus::Aborted => "aborted".to_string(),
+            RolloutStatus::Failed(reason) => format!("failed: {reason}"),
+        };
+        write!(
+            f,
+            "rolling restart of {}: {status} ({}/{} done, {} failed, {} skipped)",
+            self.program,
+            self.count(|s| *s == InstanceStatus::Done),
+            self.instances.len(),
+            self.count(|s| matches!(s, InstanceStatus::Failed(_))),
+            self.count(|s| *s == InstanceStatus::Skipped),
+        )
+    }
+}
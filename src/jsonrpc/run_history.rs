@@ -0,0 +1,84 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::run::proc::{Process, history::Run};
+
+/// # `RunHistory`
+/// `src/jsonrpc/run_history.rs`
+///
+/// Last runs of a `oneshot` process, oldest first, returned by `history`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RunHistory {
+    name: String,
+    runs: Vec<Run>,
+    /// Whether a run is in progress or about to start, its outcome is not in `runs` yet.
+    pending: bool,
+}
+
+impl RunHistory {
+    pub fn from_process(process: &Process) -> Self {
+        Self {
+            name: process.name().to_owned(),
+            runs: process.history().cloned().collect(),
+            pending: process.run_in_progress(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn runs(&self) -> &[Run] {
+        &self.runs
+    }
+
+    pub fn pending(&self) -> bool {
+        self.pending
+    }
+}
+
+impl fmt::Display for RunHistory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.runs.is_empty() {
+            write!(f, "{}: no runs yet", self.name)?;
+        } else {
+            write!(f, "{}:", self.name)?;
+            for run in self.runs.iter() {
+                write!(f, "\n  {run}")?;
+            }
+        }
+        if self.pending {
+            write!(f, "\n  (run in progress)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returned by `run`, `run` being the id the started run is going to show up with in
+/// the process' history.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RunStarted {
+    name: String,
+    run: u64,
+}
+
+impl RunStarted {
+    pub fn new(name: &str, run: u64) -> Self {
+        Self { name: name.to_owned(), run }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn run(&self) -> u64 {
+        self.run
+    }
+}
+
+impl fmt::Display for RunStarted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "starting run #{} of {}", self.run, self.name)
+    }
+}
@@ -2,12 +2,38 @@ use std::{fmt, time::Instant};
 
 use serde::{Deserialize, Serialize};
 
-use crate::run::{proc::Process, statemachine::states::ProcessState};
+use crate::run::{
+    proc::{Process, history::RunOutcome, resources::Resources},
+    statemachine::states::ProcessState,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ShortProcess {
     name: String,
     state: State,
+    /// State of the new instance of an ongoing `start-first` restart.
+    #[serde(default)]
+    replacement: Option<State>,
+    #[serde(default)]
+    restart_failure: Option<String>,
+    /// Why the process was last restarted for exceeding its `restart_if` limits.
+    #[serde(default)]
+    watchdog_restart: Option<String>,
+    /// When the next scheduled run is due, in the schedule's time zone.
+    #[serde(default)]
+    next_run: Option<String>,
+    #[serde(default)]
+    pid: Option<u32>,
+    /// Seconds since the process was spawned, while it is running.
+    #[serde(default)]
+    uptime: Option<u64>,
+    #[serde(default)]
+    restarts: u64,
+    #[serde(default)]
+    last_exit: Option<RunOutcome>,
+    /// Resources used by the process and its descendants, while it is running.
+    #[serde(default)]
+    resources: Option<Resources>,
 }
 
 impl ShortProcess {
@@ -15,9 +41,58 @@ impl ShortProcess {
         Self {
             name: process.name().to_owned(),
             state: State::from_process_state(process.state()),
+            replacement: process.replacement().map(|r| State::from_process_state(r.state())),
+            restart_failure: process.restart_failure().map(str::to_owned),
+            watchdog_restart: process.watchdog_restart().map(str::to_owned),
+            next_run: process
+                .config()
+                .schedule()
+                .zip(process.next_run())
+                .map(|(schedule, next_run)| schedule.format(next_run)),
+            pid: process.pid(),
+            uptime: process.uptime().map(|uptime| uptime.as_secs()),
+            restarts: process.restarts(),
+            last_exit: process.last_exit(),
+            resources: process.resources().copied(),
         }
     }
 
+    pub fn replacement(&self) -> Option<&State> {
+        self.replacement.as_ref()
+    }
+
+    pub fn restart_failure(&self) -> Option<&str> {
+        self.restart_failure.as_deref()
+    }
+
+    pub fn watchdog_restart(&self) -> Option<&str> {
+        self.watchdog_restart.as_deref()
+    }
+
+    pub fn next_run(&self) -> Option<&str> {
+        self.next_run.as_deref()
+    }
+
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    pub fn uptime(&self) -> Option<u64> {
+        self.uptime
+    }
+
+    pub fn restarts(&self) -> u64 {
+        self.restarts
+    }
+
+    pub fn last_exit(&self) -> Option<RunOutcome> {
+        self.last_exit
+    }
+
+    pub fn resources(&self) -> Option<&Resources> {
+        self.resources.as_ref()
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -38,6 +113,8 @@ pub enum State {
     Completed,
     Stopping(u64),
     Stopped,
+    /// Gave up on after failing more often than its `startlimit` allows, with the reason.
+    Fatal(String),
 }
 
 impl State {
@@ -52,7 +129,37 @@ impl State {
             ProcessState::Completed => Self::Completed,
             ProcessState::Stopping(instant) => Self::Stopping(instant.elapsed().as_secs()),
             ProcessState::Stopped => Self::Stopped,
+            ProcessState::Fatal(reason) => Self::Fatal(reason),
+        }
+    }
+}
+
+/// Formats `secs` with its two most significant units, e.g. `3h12m`.
+pub fn compact_duration(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m{}s", secs / 60, secs % 60),
+        3600..86_400 => format!("{}h{}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{}d{}h", secs / 86_400, secs % 86_400 / 3600),
+    }
+}
+
+impl fmt::Display for ShortProcess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.state)?;
+        if let Some(replacement) = &self.replacement {
+            write!(f, " (replacement: {replacement})")?;
+        }
+        if let Some(failure) = &self.restart_failure {
+            write!(f, " (last restart failed: {failure})")?;
+        }
+        if let Some(reason) = &self.watchdog_restart {
+            write!(f, " (restarted by watchdog: {reason})")?;
+        }
+        if let Some(next_run) = &self.next_run {
+            write!(f, " (next run: {next_run})")?;
         }
+        Ok(())
     }
 }
 
@@ -70,8 +177,25 @@ impl fmt::Display for State {
             Completed => "completed".to_owned(),
             Stopping(s) => format!("stopping since {s} seconds"),
             Stopped => "stopped".to_owned(),
+            Fatal(reason) => format!("fatal ({reason})"),
         };
 
         write!(f, "{s}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact_duration_boundaries() {
+        assert_eq!(compact_duration(0), "0s");
+        assert_eq!(compact_duration(59), "59s");
+        assert_eq!(compact_duration(60), "1m0s");
+        assert_eq!(compact_duration(3599), "59m59s");
+        assert_eq!(compact_duration(3600), "1h0m");
+        assert_eq!(compact_duration(86_399), "23h59m");
+        assert_eq!(compact_duration(86_400), "1d0h");
+    }
+}
@@ -13,13 +13,45 @@ use crate::run::statemachine::states::ProcessState;
 
 struct Logger {
     logfile: Mutex<Box<dyn Write + Send>>,
+    path: Option<String>,
 }
 
 impl Logger {
     const FORMAT: &[u8] = b"%Y-%m-%d %H:%M:%S\0";
 
     pub fn new(logfile: Box<dyn Write + Send>) -> Self {
-        Self { logfile: Mutex::new(logfile) }
+        Self {
+            logfile: Mutex::new(logfile),
+            path: None,
+        }
+    }
+
+    pub fn from_path(path: &str) -> Result<Self, String> {
+        let logfile = std::fs::File::create(path).map_err(|e| e.to_string())?;
+        Ok(Self {
+            logfile: Mutex::new(Box::new(logfile)),
+            path: Some(path.to_owned()),
+        })
+    }
+
+    /// Swaps the underlying writer for a freshly opened handle on `self.path`,
+    /// appending to the file if it still exists.
+    pub fn reopen(&self) -> Result<(), String> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let logfile = std::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(path)
+            .map_err(|e| format!("could not reopen logfile at path '{path}': {e}"))?;
+
+        let mut guard = self.logfile.lock().expect("Mutex lock panicked in another thread");
+        let _ = guard.flush();
+        *guard = Box::new(logfile);
+        Ok(())
     }
 
     fn get_time_fmt() -> String {
@@ -116,6 +148,10 @@ pub fn info(message: fmt::Arguments, fields: BTreeMap<String, Value>) {
     get_logger().info(message, fields);
 }
 
+pub fn warning(message: fmt::Arguments, fields: BTreeMap<String, Value>) {
+    get_logger().warning(message, fields);
+}
+
 #[allow(unused)]
 pub fn fatal(message: fmt::Arguments, fields: BTreeMap<String, Value>) {
     get_logger().fatal(message, fields);
@@ -372,9 +408,7 @@ fn get_logger() -> &'static Logger {
 }
 
 pub fn init(logfile: &str) -> Result<(), String> {
-    let stdout = std::fs::File::create(logfile).map_err(|e| e.to_string())?;
-
-    let logger = Logger::new(Box::new(stdout));
+    let logger = Logger::from_path(logfile)?;
     unsafe {
         INSTANCE = Some(logger);
     }
@@ -383,3 +417,12 @@ pub fn init(logfile: &str) -> Result<(), String> {
 
     Ok(())
 }
+
+/// Reopens the logfile passed to [`init`], used to follow log rotation.
+pub fn reopen() -> Result<(), String> {
+    get_logger().reopen()?;
+
+    log_info!("logfile reopened");
+
+    Ok(())
+}
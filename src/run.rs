@@ -1,3 +1,4 @@
 pub mod daemon;
+pub(crate) mod inotify;
 pub mod proc;
 pub(crate) mod statemachine;
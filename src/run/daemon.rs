@@ -1,35 +1,69 @@
-use std::io::Write;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::{collections::HashMap, error::Error, sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeSet, HashMap},
+    error::Error,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
 
-use libc::SIGHUP;
+use config_watcher::ConfigWatcher;
+use pidfile::PidFile;
+use reaper::Reaper;
+use rolling_restart::RollingRestarts;
 use serde::{Deserialize, Serialize};
+use signals::{DaemonSignal, Signals};
+use snapshots::ConfigSnapshots;
 use socket::AsyncUnixSocket;
+use sockets::SocketRegistry;
+use statefile::PersistedState;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::UnixStream;
-use tokio::signal::unix::{SignalKind, signal};
 use tokio::time::sleep;
 
-use super::proc::{self, Process};
+use super::proc::{self, ManualOverride, Process};
 use super::statemachine::states::ProcessState;
-use crate::conf::{Config, PID_FILE_PATH};
+use crate::conf::{Config, proc::ProcessConfig};
+use crate::jsonrpc::daemon_info::FailedReload;
 use crate::jsonrpc::handlers::AttachmentManager;
+use crate::jsonrpc::reload_report::ReloadReport;
 use crate::jsonrpc::response::{Response, ResponseError, ResponseType};
-use crate::log_info;
+use crate::jsonrpc::rolling_restart_progress::RollingRestartProgress;
 use crate::{
     conf,
     jsonrpc::{handlers::handle_request, request::Request},
     log_error,
 };
+use crate::{log, log_info, log_warn, proc_info};
+pub mod config_watcher;
 mod error;
+pub mod pidfile;
+pub mod reaper;
+pub mod rolling_restart;
+pub mod signals;
+pub mod snapshots;
 pub mod socket;
+pub mod sockets;
+pub mod statefile;
 
 pub struct Daemon {
     processes: HashMap<String, proc::Process>,
     socket_path: String,
     auth_group: String,
     config_path: String,
+    pidfile: String,
+    statefile: String,
+    /// What was last written to `statefile`.
+    persisted_state: PersistedState,
+    watch_config: bool,
+    /// Running as PID 1, e.g. as the entrypoint of a container.
+    init: bool,
+    pid_lock: Option<PidFile>,
     shutting_down: bool,
+    last_failed_reload: Option<FailedReload>,
+    snapshots: ConfigSnapshots,
+    sockets: SocketRegistry,
+    rolling_restarts: RollingRestarts,
+    /// Instances removed by `scale`, dropped from `processes` once they stopped.
+    scaled_down: BTreeSet<String>,
     attachment_manager: AttachmentManager,
 }
 
@@ -40,11 +74,7 @@ impl Daemon {
             .iter()
             .flat_map(|(proc_name, proc)| {
                 (0..proc.processes()).map(move |id| {
-                    let key = if proc.processes() > 1 {
-                        format!("{proc_name}_{id}")
-                    } else {
-                        proc_name.to_owned()
-                    };
+                    let key = instance_name(proc_name, id, proc.processes());
                     (key.clone(), proc::Process::from_process_config(proc.clone(), &key))
                 })
             })
@@ -55,7 +85,18 @@ impl Daemon {
             socket_path: conf.socketpath().to_owned(),
             auth_group: conf.authgroup().to_owned(),
             config_path,
+            pidfile: conf.pidfile().to_owned(),
+            statefile: conf.statefile().to_owned(),
+            persisted_state: PersistedState::default(),
+            watch_config: conf.watch_config(),
+            init: std::process::id() == 1,
+            pid_lock: None,
             shutting_down: false,
+            last_failed_reload: None,
+            snapshots: ConfigSnapshots::new(conf.clone()),
+            sockets: SocketRegistry::default(),
+            rolling_restarts: RollingRestarts::default(),
+            scaled_down: BTreeSet::new(),
             attachment_manager: AttachmentManager::new(),
         }
     }
@@ -80,15 +121,239 @@ impl Daemon {
         &self.config_path
     }
 
+    pub fn pidfile(&self) -> &str {
+        &self.pidfile
+    }
+
     pub fn shutting_down(&self) -> bool {
         self.shutting_down
     }
 
+    pub fn last_failed_reload(&self) -> &Option<FailedReload> {
+        &self.last_failed_reload
+    }
+
+    pub fn snapshots(&self) -> &ConfigSnapshots {
+        &self.snapshots
+    }
+
+    pub fn statefile(&self) -> &str {
+        &self.statefile
+    }
+
+    pub fn rolling_restarts(&self) -> &RollingRestarts {
+        &self.rolling_restarts
+    }
+
+    /// Names of the instances of `program`, ordered by their index. Instances removed
+    /// by `scale` which are still stopping are not listed.
+    pub fn instances(&self, program: &str) -> Vec<String> {
+        if self.processes.contains_key(program) && !self.scaled_down.contains(program) {
+            return vec![program.to_owned()];
+        }
+
+        let mut instances = self
+            .processes
+            .keys()
+            .filter(|name| !self.scaled_down.contains(*name))
+            .filter_map(|name| Some((instance_index(name, program)?, name.to_owned())))
+            .collect::<Vec<(u16, String)>>();
+        instances.sort();
+        instances.into_iter().map(|(_, name)| name).collect()
+    }
+
+    /// Starts a rolling restart of the instances of `program`, see [`RollingRestarts`].
+    pub fn start_rolling_restart(
+        &mut self,
+        program: &str,
+        batch_size: usize,
+        max_unavailable: usize,
+        abort_on_failure: bool,
+    ) -> Result<RollingRestartProgress, String> {
+        let instances = self.instances(program);
+        if instances.is_empty() {
+            return Err(format!("no program with name {program} found"));
+        }
+        if instances == [program] {
+            return Err(format!("{program} has a single instance, use restart instead"));
+        }
+
+        self.rolling_restarts
+            .start(program, instances, batch_size, max_unavailable, abort_on_failure)
+            .cloned()
+    }
+
+    /// Changes the number of instances of `program` to `count`, without touching the
+    /// config file. Surplus instances are stopped gracefully, highest index first, and
+    /// dropped once they exited. Returns the previous number of instances.
+    ///
+    /// Instances are named like `from_config` would name them, so going from 1 to more
+    /// instances renames `program` to `program_0` (and back when scaling down to 1).
+    pub fn scale(&mut self, program: &str, count: u16) -> Result<u16, String> {
+        if count == 0 {
+            return Err(format!("cannot scale {program} to 0 instances, stop it instead"));
+        }
+        let instances = self.instances(program);
+        if instances.is_empty() {
+            return Err(format!("no program with name {program} found"));
+        }
+        if self
+            .scaled_down
+            .iter()
+            .any(|name| name == program || instance_index(name, program).is_some())
+        {
+            return Err(format!("removed instances of {program} are still stopping, try again once they exited"));
+        }
+
+        let previous = instances.len() as u16;
+        let mut conf = self.processes[&instances[0]].config().clone();
+        conf.set_processes(count);
+
+        for name in instances.iter().skip(count as usize).rev() {
+            if let Some(process) = self.processes.get_mut(name) {
+                process.push_desired_state(ProcessState::Stopped);
+                proc_info!(process, "scaling down, stopping");
+            }
+            self.scaled_down.insert(name.to_owned());
+        }
+
+        for (id, name) in instances.iter().take(count as usize).enumerate() {
+            let Some(mut process) = self.processes.remove(name) else {
+                continue;
+            };
+            let new_name = instance_name(program, id as u16, count);
+            if *name != new_name {
+                proc_info!(process, "renaming",; new_name = new_name);
+                process.set_name(&new_name);
+            }
+            process.config_mut().set_processes(count);
+            self.processes.insert(new_name, process);
+        }
+
+        for id in previous..count {
+            let name = instance_name(program, id, count);
+            let mut process = Process::from_process_config(conf.clone(), &name);
+            process.desired_states_mut().clear();
+            process.push_desired_state(ProcessState::Healthy);
+            proc_info!(process, "scaling up, starting");
+            self.processes.insert(name, process);
+        }
+
+        log_info!("scaled program",; program = program, previous = previous, instances = count);
+        Ok(previous)
+    }
+
+    /// Drops the instances removed by `scale` which finished stopping.
+    fn remove_scaled_down(&mut self) {
+        let processes = &mut self.processes;
+        self.scaled_down.retain(|name| match processes.get(name) {
+            Some(process) if process_running(process) || !process.desired_states().is_empty() || process.has_unreaped_children() => true,
+            Some(_) => {
+                processes.remove(name);
+                log_info!("removed scaled down instance",; process = name);
+                false
+            }
+            None => false,
+        });
+    }
+
+    /// Restores operator overrides and failure counters persisted by a previous
+    /// instance of the daemon.
+    pub fn restore_state(&mut self) {
+        match PersistedState::load(&self.statefile) {
+            Ok(state) => {
+                state.apply(&mut self.processes);
+                log_info!("restored persisted state",; statefile = self.statefile(), processes = state.processes().len());
+                self.persisted_state = state;
+            }
+            Err(e) => log_error!("could not restore persisted state, starting from the configuration only",; statefile = self.statefile(), error = e),
+        }
+    }
+
+    /// Writes operator overrides and failure counters to the state file if they
+    /// changed since the last write.
+    pub fn persist_state(&mut self) {
+        let state = PersistedState::from_processes(self.processes());
+        if state == self.persisted_state {
+            return;
+        }
+
+        match state.save(&self.statefile) {
+            Ok(()) => self.persisted_state = state,
+            Err(e) => log_error!("could not persist state",; statefile = self.statefile(), error = e),
+        }
+    }
+
     pub fn shutdown(&mut self) {
-        let _ = std::fs::remove_file(PID_FILE_PATH);
         self.shutting_down = true;
     }
 
+    /// Requests a graceful stop of every process and shuts the daemon down once
+    /// all of them have exited.
+    pub fn halt(&mut self) {
+        for proc in self.processes_mut().values_mut() {
+            proc.push_desired_state(ProcessState::Stopped);
+        }
+        self.shutdown();
+    }
+
+    pub fn init(&self) -> bool {
+        self.init
+    }
+
+    /// Exit code of the daemon, derived from the last run of its processes: the exit
+    /// code of the first process (by name) which did not exit with one of its
+    /// `exitcodes`, `0` if there is none.
+    pub fn exit_code(&self) -> i32 {
+        let mut names = self.processes().keys().collect::<Vec<&String>>();
+        names.sort();
+
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let proc = &self.processes()[name];
+                proc.exit_code().filter(|code| !proc.config().exitcodes().contains(code))
+            })
+            .map(|code| if code == 0 { 1 } else { code })
+            .next()
+            .unwrap_or(0)
+    }
+
+    /// Kills every running process without waiting for `stoptime`.
+    pub fn kill_all(&mut self) {
+        for proc in self.processes_mut().values_mut() {
+            if proc.kill_forcefully().is_ok() {
+                proc_info!(proc, "killed forcefully on daemon exit");
+            }
+        }
+        self.shutdown();
+    }
+
+    /// Starts the scheduled runs which are due, see `schedule`.
+    fn advance_schedules(&mut self) {
+        let now = SystemTime::now();
+        for proc in self.processes.values_mut() {
+            proc.advance_schedule(now);
+        }
+    }
+
+    /// Writes the full internal state of the daemon and its processes to the log.
+    pub fn dump_state(&self) {
+        log_info!("state dump",; config_path = self.config_path(), socketpath = self.socket_path(), pidfile = self.pidfile(), shutting_down = self.shutting_down(), processes = self.processes().len());
+
+        for proc in self.processes().values() {
+            proc_info!(
+                proc,
+                "state dump",;
+                pid = proc.id(),
+                desired_states = proc.desired_states().iter().map(|s| s.to_string()).collect::<Vec<String>>(),
+                runtime_failures = proc.runtime_failures(),
+                healthcheck_failures = proc.healthcheck_failures(),
+                config = format!("{:?}", proc.config())
+            );
+        }
+    }
+
     pub fn attachment_manager(&self) -> &AttachmentManager {
         &self.attachment_manager
     }
@@ -141,7 +406,13 @@ impl Daemon {
             },
 
             _ = sleep(Duration::from_nanos(1)) => {
+                self.sockets.provide(self.processes.values_mut());
                 monitor_state(self.processes_mut()).await;
+                self.remove_scaled_down();
+                if !self.shutting_down {
+                    self.rolling_restarts.advance(&mut self.processes);
+                    self.advance_schedules();
+                }
 
                 if  self.shutting_down && self.no_process_running(){
                     return Ok(());
@@ -151,64 +422,158 @@ impl Daemon {
         Ok(())
     }
 
-    pub fn reload(&mut self) -> Result<(), String> {
+    /// Compares the processes of a freshly loaded configuration against the running
+    /// ones, without changing anything.
+    fn plan_reload(&self, processes_new: &HashMap<String, Process>, dry_run: bool) -> ReloadReport {
+        let mut report = ReloadReport::new(dry_run);
+
+        for (name, process_new) in processes_new.iter() {
+            match self.processes().get(name) {
+                Some(process_old) => {
+                    let changed = process_old.config().changed_fields(process_new.config());
+                    if changed.is_empty() {
+                        report.keep(name);
+                    } else if changed.iter().any(|field| ProcessConfig::requires_restart(field)) {
+                        report.restart(name, &changed);
+                    } else {
+                        report.update(name, &changed);
+                    }
+                }
+                None => report.add(name),
+            }
+        }
+
+        for name in self.processes().keys() {
+            if !processes_new.contains_key(name) {
+                report.remove(name);
+            }
+        }
+
+        report.sort();
+        report
+    }
+
+    /// Parses the config file and reports what a reload would change, without
+    /// applying it.
+    pub fn reload_dry_run(&self) -> Result<ReloadReport, String> {
+        let conf = Config::from_file(self.config_path()).map_err(|e| e.to_string())?;
+        let daemon_new = Daemon::from_config(conf, self.config_path().to_owned());
+
+        Ok(self.plan_reload(daemon_new.processes(), true))
+    }
+
+    /// Re-reads the config file and applies the differences to the running processes.
+    ///
+    /// If the new config cannot be parsed, nothing is changed: the daemon keeps running
+    /// on its last-known-good config and the failure is kept around for the `info` request.
+    pub fn reload(&mut self) -> Result<ReloadReport, String> {
         log_info!("reloading configuration");
         let conf = match Config::from_file(self.config_path()) {
             Ok(c) => c,
-            Err(e) => return Err(format!("{}", e).to_owned()),
+            Err(e) => {
+                let error = e.to_string();
+                log_error!("could not reload configuration, keeping last-known-good config",; config_path = self.config_path(), error = error);
+                self.last_failed_reload = Some(FailedReload::new(self.config_path(), &error));
+                return Err(error);
+            }
         };
+        self.last_failed_reload = None;
+
+        let report = self.apply_config(conf.clone());
+        self.snapshots.push(conf);
+
+        Ok(report)
+    }
+
+    /// Re-applies the configuration which was active `steps` successful reloads ago,
+    /// dropping the more recent ones from the history.
+    pub fn rollback(&mut self, steps: usize) -> Result<ReloadReport, String> {
+        let snapshot = self.snapshots.get(steps)?.clone();
+        log_warn!("rolling back configuration, the config file is left untouched and will be applied again on the next reload",; steps = steps, hash = format!("{:016x}", snapshot.config().hash()));
+
+        let report = self.apply_config(snapshot.config().clone());
+        self.snapshots.rewind(steps);
+
+        Ok(report)
+    }
+
+    /// Applies `conf` to the running processes, see [`ReloadReport`].
+    fn apply_config(&mut self, conf: Config) -> ReloadReport {
         let mut daemon_new = Daemon::from_config(conf, self.config_path().to_owned());
+        let report = self.plan_reload(daemon_new.processes(), false);
+        // Picked up by `update_config_watcher` on the next iteration of `run`.
+        self.watch_config = daemon_new.watch_config;
 
-        let mut leftover = vec![];
-        for (name, _p) in self.processes().iter() {
-            leftover.push(name.to_owned());
+        for name in report.added() {
+            if let Some(process_new) = daemon_new.processes_mut().remove(name) {
+                let _ = self.processes_mut().insert(name.to_owned(), process_new);
+            }
         }
 
-        for (process_name_new, process_new) in daemon_new.processes_mut().drain() {
-            match self.processes_mut().get_mut(&process_name_new.to_owned()) {
-                Some(process_old) => {
-                    if process_old.config() != process_new.config() {
-                        process_old.push_desired_state(ProcessState::Stopped);
-                        *process_old.config_mut() = process_new.config().clone();
-                        (*process_old)
-                            .healthcheck_mut()
-                            .set_healthcheck(process_new.healthcheck().check())
-                            .set_backoff(process_new.healthcheck().backoff())
-                            .set_retries(process_new.healthcheck().retries());
-
-                        match process_old.config().autostart() {
-                            false => process_old.push_desired_state(ProcessState::Idle),
-                            true => process_old.push_desired_state(ProcessState::Healthy),
-                        }
-                    }
-                    leftover.retain(|n| n != process_old.name());
-                }
-                None => {
-                    let _ = self.processes_mut().insert(process_name_new, process_new);
-                }
+        for changed in report.restarted() {
+            let (Some(process_old), Some(process_new)) = (self.processes.get_mut(changed.name()), daemon_new.processes().get(changed.name())) else {
+                continue;
+            };
+
+            process_old.push_desired_state(ProcessState::Stopped);
+            process_old.set_config(process_new.config().clone());
+
+            // A process an operator stopped stays stopped, even with `autostart`.
+            match (process_old.manual_override(), process_old.config().autostart()) {
+                (Some(ManualOverride::Stopped), _) | (None, false) => process_old.push_desired_state(ProcessState::Idle),
+                (Some(ManualOverride::Started), _) | (None, true) => process_old.push_desired_state(ProcessState::Healthy),
             }
         }
 
-        for l in leftover.iter() {
-            if let Some(p) = self.processes_mut().get_mut(l) {
+        for changed in report.updated() {
+            let (Some(process_old), Some(process_new)) = (self.processes.get_mut(changed.name()), daemon_new.processes().get(changed.name())) else {
+                continue;
+            };
+
+            process_old.set_config(process_new.config().clone());
+            proc_info!(process_old, "applied configuration changes without restarting",; fields = changed.fields().join(", "));
+        }
+
+        for name in report.removed() {
+            if let Some(p) = self.processes_mut().get_mut(name) {
                 p.push_desired_state(ProcessState::Stopped);
             }
         }
 
-        Ok(())
+        log_info!("configuration applied",; added = report.added().len(), removed = report.removed().len(), restarted = report.restarted().len(), updated = report.updated().len(), unchanged = report.unchanged().len());
+
+        report
     }
 
-    fn write_pid_file() -> Result<(), Box<dyn Error>> {
-        let pid = unsafe { libc::getpid() };
-        let mut pid_file = std::fs::File::create(PID_FILE_PATH)?;
-        pid_file.write_all(pid.to_string().as_bytes())?;
-        Ok(())
+    /// Starts or stops watching the config file once `watch_config` was changed by a
+    /// reload.
+    fn update_config_watcher(&mut self, config_watcher: &mut Option<ConfigWatcher>) {
+        match (self.watch_config, config_watcher.is_some()) {
+            (true, false) => match ConfigWatcher::new(&[self.config_path()]) {
+                Ok(watcher) => {
+                    log_info!("watching config file for changes",; config_path = self.config_path());
+                    *config_watcher = Some(watcher);
+                }
+                Err(e) => {
+                    log_error!("could not watch config file, automatic reloads stay disabled",; config_path = self.config_path(), error = e.to_string());
+                    self.watch_config = false;
+                }
+            },
+            (false, true) => {
+                log_info!("no longer watching config file for changes",; config_path = self.config_path());
+                *config_watcher = None;
+            }
+            _ => {}
+        }
     }
 
     pub async fn run(&mut self) -> Result<(), Box<dyn Error>> {
-        unsafe {
-            libc::signal(SIGHUP, handler_reload as usize);
-        }
+        // Lock the pid file before touching the socket, binding removes any existing
+        // socket file and would cut off a running instance from its clients.
+        self.pid_lock = match PidFile::acquire(self.pidfile()) {
+            Ok(pid_lock) => Some(pid_lock),
+            Err(e) => return Err(Box::<dyn Error>::from(format!("Failed starting the taskmaster daemon: {e}"))),
+        };
 
         let mut listener = match AsyncUnixSocket::new(self.socket_path(), self.auth_group()) {
             Ok(listener) => listener,
@@ -217,11 +582,27 @@ impl Daemon {
 
         let (sender, mut receiver) = tokio::sync::mpsc::channel(1024);
         let sender = Arc::new(sender);
-        let mut sigint = signal(SignalKind::interrupt())?;
+        let mut signals = Signals::new()?;
+        let mut config_watcher = match self.watch_config {
+            true => Some(ConfigWatcher::new(&[self.config_path()])?),
+            false => None,
+        };
 
-        Self::write_pid_file()?;
+        // Only after taking the pid file lock, a second instance must not touch the
+        // running instance's state file.
+        self.restore_state();
+        let mut last_persisted = Instant::now();
+
+        // Orphans are reparented to PID 1 (or to taskmaster as a child subreaper),
+        // nobody else is going to reap them.
+        if self.init {
+            log_info!("running as PID 1, reaping orphaned processes");
+        }
+        let mut reaper = Reaper::new(self.init);
 
         loop {
+            self.update_config_watcher(&mut config_watcher);
+
             tokio::select! {
                 accept_result = listener.accept() => {
 
@@ -249,6 +630,7 @@ impl Daemon {
 
                 Some((request, mut socket)) = receiver.recv() => {
                     let response = handle_request(self, request).await;
+                    self.persist_state();
 
                     let msg = serde_json::to_string(&response).unwrap();
 
@@ -260,48 +642,110 @@ impl Daemon {
                 },
 
                 _ = sleep(Duration::from_nanos(1)) => {
-                    unsafe {
+                    self.sockets.provide(self.processes.values_mut());
+                    monitor_state(self.processes_mut()).await;
+                    self.remove_scaled_down();
+                    if !self.shutting_down {
+                        self.rolling_restarts.advance(&mut self.processes);
+                        self.advance_schedules();
+                    }
 
-                    #[allow(static_mut_refs)]
-                    if RELOAD.load(Ordering::Relaxed) {
-                        if let Err(msg) = self.reload() {
-                            log_error!("{msg}");
-                            return Err(Box::<dyn Error>::from(msg));
-                        }
-                        RELOAD.store(false, Ordering::Relaxed);
+                    // Failure counters change while monitoring, no need to check them on every tick.
+                    if last_persisted.elapsed() >= Duration::from_secs(1) {
+                        self.persist_state();
+                        last_persisted = Instant::now();
                     }
+
+                    if reaper.due() {
+                        reaper.sweep(&self.processes().values().filter_map(Process::id).collect());
                     }
-                    monitor_state(self.processes_mut()).await;
 
                     if  self.shutting_down && self.no_process_running(){
+                        self.persist_state();
                         return Ok(());
                     }
                 }
-                _ = sigint.recv() => {
-                    log_info!("received SIGINT, exiting");
-                    self.shutdown();
+
+                Some(changed) = async {
+                    match config_watcher.as_mut() {
+                        Some(watcher) => Some(watcher.changed().await),
+                        None => None,
+                    }
+                } => match changed {
+                    Ok(()) => {
+                        log_info!("config file changed, reloading configuration",; config_path = self.config_path());
+                        let _ = self.reload();
+                    }
+                    Err(e) => {
+                        log_error!("could not watch config file, disabling automatic reloads",; config_path = self.config_path(), error = e.to_string());
+                        config_watcher = None;
+                        self.watch_config = false;
+                    }
+                },
+
+                signal = signals.recv() => match signal {
+                    DaemonSignal::Reload => {
+                        log_info!("received SIGHUP, reloading configuration");
+                        // Failures are logged and recorded by `reload`, a broken config
+                        // must never take the daemon (and its processes) down.
+                        let _ = self.reload();
+                    }
+                    DaemonSignal::Shutdown => {
+                        log_info!("received SIGINT/SIGTERM, stopping all processes and exiting");
+                        self.halt();
+                    }
+                    DaemonSignal::Quit => {
+                        log_info!("received SIGQUIT, killing all processes and exiting");
+                        self.kill_all();
+                        self.persist_state();
+                        return Ok(());
+                    }
+                    DaemonSignal::ReopenLogs => {
+                        if let Err(e) = log::reopen() {
+                            log_error!("received SIGUSR1, could not reopen logfile: {e}");
+                        }
+                    }
+                    DaemonSignal::DumpState => {
+                        log_info!("received SIGUSR2, dumping internal state");
+                        self.dump_state();
+                    }
                 }
             }
         }
     }
 
     pub fn no_process_running(&self) -> bool {
-        let mut no_process_running = true;
-        for proc in self.processes().values() {
-            use ProcessState::*;
-            match proc.state() {
-                Ready | HealthCheck(_) | Healthy | Stopping(_) => no_process_running = false,
-                _ => {}
-            }
-        }
-        no_process_running
+        !self.processes().values().any(process_running)
+    }
+}
+
+/// Whether `proc` (or an instance started by a `start-first` restart) still runs.
+fn process_running(proc: &Process) -> bool {
+    use ProcessState::*;
+    matches!(proc.state(), Ready | HealthCheck(_) | Healthy | Stopping(_)) || proc.replacement().is_some() || !proc.retiring().is_empty()
+}
+
+/// Name of instance `id` of `program` running `count` instances.
+fn instance_name(program: &str, id: u16, count: u16) -> String {
+    match count > 1 {
+        true => format!("{program}_{id}"),
+        false => program.to_owned(),
     }
 }
 
+/// Index of `name` if it is a suffixed instance of `program`, i.e. `program_<index>`.
+fn instance_index(name: &str, program: &str) -> Option<u16> {
+    name.strip_prefix(program)?.strip_prefix('_')?.parse::<u16>().ok()
+}
+
 async fn monitor_state(procs: &mut HashMap<String, Process>) {
+    let stats = proc::procfs::Snapshot::default();
     for proc in procs.values_mut() {
         proc.desire();
-        proc.monitor().await;
+        proc.monitor(&stats).await;
+        proc.sample_resources(&stats);
+        proc.check_watchdogs();
+        proc.check_watch();
     }
 }
 
@@ -346,20 +790,17 @@ async fn handle_client(mut socket: UnixStream, sender: Arc<tokio::sync::mpsc::Se
     }
 }
 
-static mut RELOAD: AtomicBool = AtomicBool::new(false);
-
-fn handler_reload() {
-    unsafe {
-        #[allow(static_mut_refs)]
-        RELOAD.store(true, Ordering::Relaxed);
-    }
-}
-
 #[cfg(test)]
 mod tests {
 
     use crate::conf::proc::ProcessConfig;
-    use crate::conf::proc::types::{AutoRestart, CommandHealthCheck, HealthCheck, HealthCheckType, UptimeHealthCheck};
+    use std::time::UNIX_EPOCH;
+
+    use crate::conf::proc::types::{
+        self, AutoRestart, Backoff, CommandHealthCheck, HealthCheck, HealthCheckType, Hook, HookKind, OverlapPolicy, Schedule, SocketConfig, StartLimit,
+        TimeZone, UptimeHealthCheck,
+    };
+    use crate::jsonrpc::short_process::ShortProcess;
 
     use super::conf::Config;
 
@@ -398,7 +839,9 @@ mod tests {
     #[tokio::test]
     async fn healthcheck_to_completed_uptime() {
         let mut hc = HealthCheck::default();
-        let hc = hc.set_check(HealthCheckType::Uptime(UptimeHealthCheck { starttime: 2 }));
+        let hc = hc.set_check(HealthCheckType::Uptime(UptimeHealthCheck {
+            starttime: types::Duration::from_secs(2),
+        }));
         let mut proc = ProcessConfig::default();
         let proc = proc
             .set_cmd("sleep")
@@ -419,7 +862,9 @@ mod tests {
     #[tokio::test]
     async fn healthcheck_to_failed_uptime() {
         let mut hc = HealthCheck::default();
-        let hc = hc.set_check(HealthCheckType::Uptime(UptimeHealthCheck { starttime: 2 }));
+        let hc = hc.set_check(HealthCheckType::Uptime(UptimeHealthCheck {
+            starttime: types::Duration::from_secs(2),
+        }));
         let mut proc = ProcessConfig::default();
         let proc = proc
             .set_cmd("sh")
@@ -443,7 +888,9 @@ mod tests {
     async fn healthcheck_to_failed_retry() {
         let mut hc = HealthCheck::default();
         let hc = hc
-            .set_check(HealthCheckType::Uptime(UptimeHealthCheck { starttime: 2 }))
+            .set_check(HealthCheckType::Uptime(UptimeHealthCheck {
+                starttime: types::Duration::from_secs(2),
+            }))
             .set_backoff(1)
             .set_retries(2);
         let mut proc = ProcessConfig::default();
@@ -477,7 +924,9 @@ mod tests {
     async fn healthcheck_to_stopped_max_retries_reached() {
         let mut hc = HealthCheck::default();
         let hc = hc
-            .set_check(HealthCheckType::Uptime(UptimeHealthCheck { starttime: 2 }))
+            .set_check(HealthCheckType::Uptime(UptimeHealthCheck {
+                starttime: types::Duration::from_secs(2),
+            }))
             .set_backoff(1)
             .set_retries(0);
         let mut proc = ProcessConfig::default();
@@ -520,7 +969,9 @@ mod tests {
     #[tokio::test]
     async fn healthy_to_failed() {
         let mut hc = HealthCheck::default();
-        let hc = hc.set_check(HealthCheckType::Uptime(UptimeHealthCheck { starttime: 1 }));
+        let hc = hc.set_check(HealthCheckType::Uptime(UptimeHealthCheck {
+            starttime: types::Duration::from_secs(1),
+        }));
         let mut proc = ProcessConfig::default();
         let proc = proc
             .set_cmd("sh")
@@ -565,7 +1016,9 @@ mod tests {
     async fn healthy_to_failed_max_retries_reached() {
         let mut hc = HealthCheck::default();
         let hc = hc
-            .set_check(HealthCheckType::Uptime(UptimeHealthCheck { starttime: 1 }))
+            .set_check(HealthCheckType::Uptime(UptimeHealthCheck {
+                starttime: types::Duration::from_secs(1),
+            }))
             .set_retries(1)
             .set_backoff(1);
         let mut proc = ProcessConfig::default();
@@ -667,8 +1120,7 @@ mod tests {
         assert_eq!(d.processes().get("sleep").unwrap().state(), ProcessState::Healthy);
 
         // Push desired Stopped state, run once to update state.
-        let _ = d
-            .processes_mut()
+        d.processes_mut()
             .get_mut("sleep")
             .unwrap()
             .push_desired_state(ProcessState::Stopped);
@@ -687,13 +1139,533 @@ mod tests {
         d.shutdown();
     }
 
+    #[tokio::test]
+    async fn halt_stops_all_processes() {
+        let mut proc = ProcessConfig::default();
+        let proc = proc.set_cmd("sleep").set_args(vec!["10".to_string()]).set_stoptime(1);
+        let mut conf = Config::random();
+        conf.add_process("sleep", proc.to_owned());
+        let mut d = Daemon::from_config(conf, "path".to_string());
+
+        let _ = d.run_once().await;
+        assert!(matches!(d.processes().get("sleep").unwrap().state(), ProcessState::HealthCheck(_)));
+
+        d.halt();
+        assert!(d.shutting_down());
+
+        let _ = d.run_once().await;
+        assert!(matches!(d.processes().get("sleep").unwrap().state(), ProcessState::Stopping(_)));
+    }
+
+    #[tokio::test]
+    async fn exit_code_from_process_outcomes() {
+        let mut ok = ProcessConfig::default();
+        let ok = ok.set_cmd("true");
+        let mut failing = ProcessConfig::default();
+        let failing = failing.set_cmd("sh").set_args(vec!["-c".to_string(), "exit 3".to_string()]);
+        let mut conf = Config::random();
+        conf.add_process("a", ok.to_owned()).add_process("b", failing.to_owned());
+        let mut d = Daemon::from_config(conf, "path".to_string());
+        assert_eq!(d.exit_code(), 0);
+
+        let _ = d.run_once().await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let _ = d.run_once().await;
+
+        assert_eq!(d.processes()["a"].exit_code(), Some(0));
+        assert_eq!(d.exit_code(), 3);
+    }
+
+    #[tokio::test]
+    async fn instances_are_ordered_by_index() {
+        let mut proc = ProcessConfig::default();
+        let proc = proc.set_cmd("sleep").set_processes(11);
+        let mut conf = Config::random();
+        let conf = conf.add_process("web", proc.clone()).add_process("webserver", proc.clone());
+        let d = Daemon::from_config(conf.clone(), "path".to_string());
+
+        let instances = d.instances("web");
+        assert_eq!(instances.len(), 11);
+        assert_eq!(instances[..3], ["web_0", "web_1", "web_2"]);
+        assert_eq!(instances[10], "web_10");
+    }
+
+    #[tokio::test]
+    async fn scale_keeps_instance_names_consistent() {
+        let mut proc = ProcessConfig::default();
+        let proc = proc.set_cmd("sleep").set_args(vec!["10".to_string()]);
+        let mut conf = Config::random();
+        let conf = conf.add_process("sleep", proc.clone());
+        let mut d = Daemon::from_config(conf.clone(), "path".to_string());
+
+        let _ = d.run_once().await;
+        let pid = d.processes()["sleep"].id();
+        assert!(pid.is_some());
+
+        assert_eq!(d.scale("sleep", 3), Ok(1));
+        assert_eq!(d.instances("sleep"), ["sleep_0", "sleep_1", "sleep_2"]);
+        // The running instance is renamed, not restarted.
+        assert_eq!(d.processes()["sleep_0"].id(), pid);
+        assert_eq!(d.processes()["sleep_0"].name(), "sleep_0");
+        assert!(d.processes().values().all(|p| p.config().processes() == 3));
+
+        let _ = d.run_once().await;
+        assert!(d.processes()["sleep_2"].id().is_some());
+
+        assert_eq!(d.scale("sleep", 1), Ok(3));
+        assert_eq!(d.instances("sleep"), ["sleep"]);
+        assert_eq!(d.processes()["sleep"].id(), pid);
+        assert!(d.scale("sleep", 2).is_err(), "surplus instances are still stopping");
+
+        for _ in 0..10 {
+            let _ = d.run_once().await;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(d.processes().keys().collect::<Vec<&String>>(), ["sleep"]);
+        assert_eq!(d.scale("sleep", 0), Err("cannot scale sleep to 0 instances, stop it instead".to_string()));
+        d.kill_all();
+    }
+
+    /// Runs `d` until the state of `name` satisfies `done`, for at most 3 seconds.
+    async fn run_until(d: &mut Daemon, name: &str, done: impl Fn(&ProcessState) -> bool) {
+        for _ in 0..300 {
+            if done(&d.processes()[name].state()) {
+                return;
+            }
+            let _ = d.run_once().await;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn backoff_grows_with_consecutive_failures() {
+        let mut hc = HealthCheck::new();
+        let hc = hc
+            .set_check(HealthCheckType::Uptime(UptimeHealthCheck {
+                starttime: types::Duration::from_secs(0),
+            }))
+            .set_backoff_policy(Backoff::exponential(types::Duration::from_millis(100), types::Duration::from_secs(1), 4.0));
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("/bin/sh")
+            .set_args(vec!["-c".to_string(), "sleep 0.1; exit 1".to_string()])
+            .set_workingdir("/tmp")
+            .set_autorestart(AutoRestart {
+                mode: "on-failure".to_string(),
+                max_retries: Some(5),
+            })
+            .set_healthcheck(hc.to_owned());
+        let mut conf = Config::random();
+        conf.add_process("crash", proc.to_owned());
+        let mut d = Daemon::from_config(conf, "path".to_string());
+
+        let mut delays = vec![];
+        for _ in 0..300 {
+            if delays.len() == 2 {
+                break;
+            }
+            let _ = d.run_once().await;
+            if let ProcessState::WaitingForRetry(retry_at) = d.processes()["crash"].state()
+                && delays.len() < d.processes()["crash"].runtime_failures()
+            {
+                delays.push(retry_at.duration_since(Instant::now()));
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert!(delays[0] <= Duration::from_millis(100), "{delays:?}");
+        assert!(delays[1] > Duration::from_millis(300) && delays[1] <= Duration::from_millis(400), "{delays:?}");
+    }
+
+    #[tokio::test]
+    async fn always_backs_off_after_crashing() {
+        let mut hc = HealthCheck::new();
+        let hc = hc
+            .set_check(HealthCheckType::Uptime(UptimeHealthCheck {
+                starttime: types::Duration::from_secs(0),
+            }))
+            .set_backoff_policy(Backoff::exponential(types::Duration::from_millis(100), types::Duration::from_secs(1), 4.0));
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("/bin/sh")
+            .set_args(vec!["-c".to_string(), "sleep 0.1; exit 1".to_string()])
+            .set_autorestart(AutoRestart {
+                mode: "always".to_string(),
+                max_retries: None,
+            })
+            .set_healthcheck(hc.to_owned());
+        let mut conf = Config::random();
+        conf.add_process("crash", proc.to_owned());
+        let mut d = Daemon::from_config(conf, "path".to_string());
+
+        let mut delays = vec![];
+        for _ in 0..300 {
+            if delays.len() == 2 {
+                break;
+            }
+            let _ = d.run_once().await;
+            if let ProcessState::WaitingForRetry(retry_at) = d.processes()["crash"].state()
+                && delays.len() < d.processes()["crash"].runtime_failures()
+            {
+                delays.push(retry_at.duration_since(Instant::now()));
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        // Restarted after each crash, waiting longer every time.
+        assert_eq!(d.processes()["crash"].restarts(), 1);
+        assert!(delays[0] <= Duration::from_millis(100), "{delays:?}");
+        assert!(delays[1] > Duration::from_millis(300) && delays[1] <= Duration::from_millis(400), "{delays:?}");
+    }
+
+    #[tokio::test]
+    async fn failures_reset_once_stable() {
+        let mut backoff = Backoff::exponential(types::Duration::from_secs(1), types::Duration::from_secs(60), 2.0);
+        let mut hc = HealthCheck::new();
+        let hc = hc
+            .set_check(HealthCheckType::Uptime(UptimeHealthCheck {
+                starttime: types::Duration::from_secs(0),
+            }))
+            .set_backoff_policy(backoff.set_reset_after(types::Duration::from_millis(100)).to_owned());
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("sleep")
+            .set_args(vec!["10".to_string()])
+            .set_healthcheck(hc.to_owned());
+        let mut conf = Config::random();
+        conf.add_process("sleep", proc.to_owned());
+        let mut d = Daemon::from_config(conf, "path".to_string());
+        d.processes_mut().get_mut("sleep").unwrap().set_runtime_failures(3);
+
+        let _ = d.run_once().await;
+        let _ = d.run_once().await;
+        assert_eq!(d.processes()["sleep"].state(), ProcessState::Healthy);
+        assert_eq!(d.processes()["sleep"].runtime_failures(), 3);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let _ = d.run_once().await;
+        assert_eq!(d.processes()["sleep"].runtime_failures(), 0);
+        d.kill_all();
+    }
+
+    #[tokio::test]
+    async fn crash_loop_becomes_fatal() {
+        let mut hc = HealthCheck::new();
+        let hc = hc.set_check(HealthCheckType::Uptime(UptimeHealthCheck {
+            starttime: types::Duration::from_secs(0),
+        }));
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("true")
+            .set_autorestart(AutoRestart {
+                mode: "always".to_string(),
+                max_retries: None,
+            })
+            .set_startlimit(StartLimit::new(2, types::Duration::from_secs(60)))
+            .set_healthcheck(hc.to_owned());
+        let mut conf = Config::random();
+        conf.add_process("loop", proc.to_owned());
+        let mut d = Daemon::from_config(conf, "path".to_string());
+
+        run_until(&mut d, "loop", |state| matches!(state, ProcessState::Fatal(_))).await;
+        assert_eq!(d.processes()["loop"].state(), ProcessState::Fatal("failed 3 time(s) within 60s".to_string()));
+
+        // Neither autorestart nor a restart leave the fatal state.
+        d.processes_mut()
+            .get_mut("loop")
+            .unwrap()
+            .push_desired_state(ProcessState::Ready);
+        for _ in 0..5 {
+            let _ = d.run_once().await;
+        }
+        assert!(matches!(d.processes()["loop"].state(), ProcessState::Fatal(_)));
+
+        assert!(d.processes_mut().get_mut("loop").unwrap().reset_failed());
+        let _ = d.run_once().await;
+        assert_eq!(d.processes()["loop"].state(), ProcessState::Stopped);
+        assert!(!d.processes_mut().get_mut("loop").unwrap().reset_failed());
+    }
+
+    #[tokio::test]
+    async fn failed_restart_counts_once_towards_startlimit() {
+        let script = format!("/tmp/{}-vanishing.sh", std::process::id());
+        std::fs::write(&script, "#!/bin/sh\nrm -f \"$0\"\n").unwrap();
+        std::fs::set_permissions(&script, std::os::unix::fs::PermissionsExt::from_mode(0o755)).unwrap();
+
+        let mut hc = HealthCheck::new();
+        let hc = hc
+            .set_check(HealthCheckType::Uptime(UptimeHealthCheck {
+                starttime: types::Duration::from_secs(0),
+            }))
+            .set_backoff(10);
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd(&script)
+            .set_autorestart(AutoRestart {
+                mode: "always".to_string(),
+                max_retries: None,
+            })
+            .set_startlimit(StartLimit::new(1, types::Duration::from_secs(60)))
+            .set_healthcheck(hc.to_owned());
+        let mut conf = Config::random();
+        conf.add_process("vanishing", proc.to_owned());
+        let mut d = Daemon::from_config(conf, "path".to_string());
+
+        // The script removes itself, restarting it after it completed fails. Both only
+        // count as one failure.
+        run_until(&mut d, "vanishing", |state| matches!(state, ProcessState::WaitingForRetry(_) | ProcessState::Fatal(_))).await;
+        assert!(matches!(d.processes()["vanishing"].state(), ProcessState::WaitingForRetry(_)));
+    }
+
+    #[tokio::test]
+    async fn fatal_stops_a_process_failing_its_healthcheck() {
+        let mut hc = HealthCheck::new();
+        let hc = hc
+            .set_check(HealthCheckType::Command(CommandHealthCheck {
+                cmd: "false".to_string(),
+                args: vec![],
+                timeout: types::Duration::from_secs(1),
+            }))
+            .set_backoff(0);
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("sleep")
+            .set_args(vec!["10".to_string()])
+            .set_stoptime(1)
+            .set_startlimit(StartLimit::new(0, types::Duration::from_secs(60)))
+            .set_healthcheck(hc.to_owned());
+        let mut conf = Config::random();
+        conf.add_process("sleep", proc.to_owned());
+        let mut d = Daemon::from_config(conf, "path".to_string());
+
+        run_until(&mut d, "sleep", |state| matches!(state, ProcessState::HealthCheck(_))).await;
+        let pid = d.processes()["sleep"].id().unwrap();
+
+        // The healthcheck fails while the process keeps running, it is stopped before
+        // being given up on.
+        run_until(&mut d, "sleep", |state| matches!(state, ProcessState::Fatal(_))).await;
+        assert_eq!(d.processes()["sleep"].state(), ProcessState::Fatal("failed 1 time(s) within 60s".to_string()));
+        assert_eq!(d.processes()["sleep"].pid(), None);
+        assert_ne!(unsafe { libc::kill(pid as i32, 0) }, 0);
+
+        assert!(d.processes_mut().get_mut("sleep").unwrap().reset_failed());
+        d.processes_mut()
+            .get_mut("sleep")
+            .unwrap()
+            .push_desired_state(ProcessState::Healthy);
+        run_until(&mut d, "sleep", |state| matches!(state, ProcessState::HealthCheck(_))).await;
+        assert_ne!(d.processes()["sleep"].id(), Some(pid));
+        d.kill_all();
+    }
+
+    #[tokio::test]
+    async fn hooks_run_around_start_and_stop() {
+        let out = format!("/tmp/{}-hooks.out", std::process::id());
+        let _ = std::fs::remove_file(&out);
+        let mut hook = Hook::new("/bin/sh", vec!["-c".to_string(), format!("echo $TASKMASTER_HOOK $TASKMASTER_PROCESS_NAME $FOO >> {out}")]);
+        let hook = hook.set_env(vec![("FOO".to_string(), "bar".to_string())]);
+        let mut hc = HealthCheck::new();
+        let hc = hc.set_check(HealthCheckType::Uptime(UptimeHealthCheck {
+            starttime: types::Duration::from_secs(0),
+        }));
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("sleep")
+            .set_args(vec!["10".to_string()])
+            .set_stoptime(1)
+            .set_healthcheck(hc.to_owned());
+        for kind in [HookKind::PreStart, HookKind::PostStart, HookKind::PreStop, HookKind::PostStop] {
+            proc.set_hook(kind, hook.to_owned());
+        }
+        let mut conf = Config::random();
+        conf.add_process("api", proc.to_owned());
+        let mut d = Daemon::from_config(conf, "path".to_string());
+
+        run_until(&mut d, "api", |state| *state == ProcessState::Healthy).await;
+        assert_eq!(d.processes()["api"].state(), ProcessState::Healthy);
+
+        d.processes_mut()
+            .get_mut("api")
+            .unwrap()
+            .push_desired_state(ProcessState::Stopped);
+        run_until(&mut d, "api", |state| *state == ProcessState::Stopped).await;
+        assert_eq!(d.processes()["api"].state(), ProcessState::Stopped);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "pre_start api bar\npost_start api bar\npre_stop api bar\npost_stop api bar\n");
+        let _ = std::fs::remove_file(&out);
+    }
+
+    #[tokio::test]
+    async fn failing_pre_start_blocks_start() {
+        let out = format!("/tmp/{}-pre_start.out", std::process::id());
+        let _ = std::fs::remove_file(&out);
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("/bin/sh")
+            .set_args(vec!["-c".to_string(), format!("touch {out}; sleep 10")])
+            .set_hook(HookKind::PreStart, Hook::new("/bin/sh", vec!["-c".to_string(), "exit 3".to_string()]));
+        let mut conf = Config::random();
+        conf.add_process("api", proc.to_owned());
+        let mut d = Daemon::from_config(conf, "path".to_string());
+
+        run_until(&mut d, "api", |state| matches!(state, ProcessState::Failed(_) | ProcessState::WaitingForRetry(_))).await;
+        assert!(matches!(d.processes()["api"].state(), ProcessState::Failed(_) | ProcessState::WaitingForRetry(_)));
+        assert!(d.processes()["api"].id().is_none());
+        assert!(!std::path::Path::new(&out).exists());
+    }
+
+    #[tokio::test]
+    async fn scheduled_runs() {
+        let mut hc = HealthCheck::new();
+        let hc = hc.set_check(HealthCheckType::Uptime(UptimeHealthCheck {
+            starttime: types::Duration::from_secs(0),
+        }));
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("sleep")
+            .set_args(vec!["0.3".to_string()])
+            .set_autostart(false)
+            .set_schedule(Schedule::new("*/5 * * * *", TimeZone::utc(), OverlapPolicy::Queue))
+            .set_healthcheck(hc.to_owned());
+        let mut conf = Config::random();
+        conf.add_process("job", proc.to_owned());
+        let mut d = Daemon::from_config(conf, "path".to_string());
+
+        let now = SystemTime::now();
+        d.processes_mut().get_mut("job").unwrap().advance_schedule(now);
+        let next_run = d.processes()["job"].next_run().unwrap();
+        assert!(next_run > now && next_run <= now + Duration::from_secs(300));
+        assert!(d.processes()["job"].desired_states().is_empty());
+        assert_eq!(
+            ShortProcess::from_process(&d.processes()["job"]).next_run(),
+            Some(
+                TimeZone::utc()
+                    .format(next_run.duration_since(UNIX_EPOCH).unwrap().as_secs() as i64)
+                    .as_str()
+            )
+        );
+
+        // Due, then due again while the first run is still going.
+        d.processes_mut().get_mut("job").unwrap().advance_schedule(next_run);
+        for _ in 0..3 {
+            let _ = d.run_once().await;
+        }
+        assert!(matches!(d.processes()["job"].state(), ProcessState::HealthCheck(_) | ProcessState::Healthy));
+        let second_run = d.processes()["job"].next_run().unwrap();
+        d.processes_mut().get_mut("job").unwrap().advance_schedule(second_run);
+        assert!(d.processes()["job"].desired_states().is_empty());
+
+        run_until(&mut d, "job", |state| *state == ProcessState::Completed).await;
+        assert_eq!(d.processes()["job"].state(), ProcessState::Completed);
+
+        // The queued run starts once the first one completed.
+        d.processes_mut().get_mut("job").unwrap().advance_schedule(second_run);
+        assert_eq!(d.processes()["job"].desired_states().front(), Some(&ProcessState::Ready));
+        for _ in 0..3 {
+            let _ = d.run_once().await;
+        }
+        assert!(matches!(d.processes()["job"].state(), ProcessState::HealthCheck(_) | ProcessState::Healthy));
+        run_until(&mut d, "job", |state| *state == ProcessState::Completed).await;
+
+        // Without `catch_up`, runs noticed too late are dropped.
+        let third_run = d.processes()["job"].next_run().unwrap();
+        d.processes_mut()
+            .get_mut("job")
+            .unwrap()
+            .advance_schedule(third_run + Duration::from_secs(120));
+        assert!(d.processes()["job"].desired_states().is_empty());
+        assert!(d.processes()["job"].next_run().unwrap() > third_run + Duration::from_secs(120));
+    }
+
+    #[tokio::test]
+    async fn sockets_are_passed_and_outlive_the_process() {
+        let dir = format!("/tmp/{}", std::process::id());
+        let _ = std::fs::create_dir(&dir);
+        let sock = format!("{dir}/activated.sock");
+        let out = format!("{dir}/activated.out");
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("/bin/sh")
+            .set_args(vec![
+                "-c".to_string(),
+                format!("echo $LISTEN_FDS $LISTEN_FDNAMES $LISTEN_PID $$ $(readlink /proc/$$/fd/3) > {out}; sleep 10"),
+            ])
+            .set_stoptime(1)
+            .set_socket("api", SocketConfig::unix(&sock));
+        let mut conf = Config::random();
+        conf.add_process("web", proc.to_owned());
+        let mut d = Daemon::from_config(conf, "path".to_string());
+
+        let _ = d.run_once().await;
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        let pid = d.processes()["web"].id().unwrap();
+        let fields = std::fs::read_to_string(&out)
+            .unwrap()
+            .split_whitespace()
+            .map(String::from)
+            .collect::<Vec<String>>();
+        assert_eq!(fields[..4], ["1".to_string(), "api".to_string(), pid.to_string(), pid.to_string()]);
+        assert!(fields[4].starts_with("socket:"));
+
+        d.processes_mut()
+            .get_mut("web")
+            .unwrap()
+            .push_desired_state(ProcessState::Stopped);
+        run_until(&mut d, "web", |state| *state == ProcessState::Stopped).await;
+        assert_eq!(d.processes()["web"].state(), ProcessState::Stopped);
+
+        // Queued by the kernel until the process is started again.
+        assert!(std::os::unix::net::UnixStream::connect(&sock).is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn reload_toggles_config_watcher() {
+        let path = format!("/tmp/{}-watch_config.toml", std::process::id());
+        let conf = |watch_config: bool| {
+            format!("watch_config = {watch_config}\n[processes.sleep]\ncmd = \"/usr/bin/sleep\"\nargs = [\"10\"]\nworkingdir = \"/tmp\"\n")
+        };
+        std::fs::write(&path, conf(false)).unwrap();
+        let mut d = Daemon::from_config(Config::from_file(&path).unwrap(), path.clone());
+        let mut config_watcher = None;
+
+        std::fs::write(&path, conf(true)).unwrap();
+        d.reload().unwrap();
+        d.update_config_watcher(&mut config_watcher);
+        assert!(config_watcher.is_some());
+
+        std::fs::write(&path, conf(false)).unwrap();
+        d.reload().unwrap();
+        d.update_config_watcher(&mut config_watcher);
+        assert!(config_watcher.is_none());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn kill_all() {
+        let mut proc = ProcessConfig::default();
+        let proc = proc.set_cmd("sleep").set_args(vec!["10".to_string()]);
+        let mut conf = Config::random();
+        conf.add_process("sleep", proc.to_owned());
+        let mut d = Daemon::from_config(conf, "path".to_string());
+
+        let _ = d.run_once().await;
+        assert!(d.processes().get("sleep").unwrap().id().is_some());
+
+        d.kill_all();
+        assert!(d.shutting_down());
+        assert!(d.processes().get("sleep").unwrap().id().is_none());
+    }
+
     #[tokio::test]
     async fn healthcheck_to_healthy_command() {
         let mut hc = HealthCheck::default();
         let hc = hc.set_check(HealthCheckType::Command(CommandHealthCheck {
             cmd: "sleep".to_string(),
             args: vec!["1".to_string()],
-            timeout: 10,
+            timeout: types::Duration::from_secs(10),
         }));
         let mut proc = ProcessConfig::default();
         let proc = proc
@@ -723,7 +1695,7 @@ mod tests {
         let hc = hc.set_check(HealthCheckType::Command(CommandHealthCheck {
             cmd: "sleep".to_string(),
             args: vec!["2".to_string()],
-            timeout: 1,
+            timeout: types::Duration::from_secs(1),
         }));
         let mut proc = ProcessConfig::default();
         let proc = proc
@@ -753,7 +1725,7 @@ mod tests {
         let hc = hc.set_check(HealthCheckType::Command(CommandHealthCheck {
             cmd: "sleep".to_string(),
             args: vec!["asd".to_string()], // Will fail right away.
-            timeout: 1,
+            timeout: types::Duration::from_secs(1),
         }));
         let mut proc = ProcessConfig::default();
         let proc = proc
@@ -783,7 +1755,7 @@ mod tests {
         let hc = hc.set_check(HealthCheckType::Command(CommandHealthCheck {
             cmd: "sleep".to_string(),
             args: vec!["10".to_string()],
-            timeout: 10,
+            timeout: types::Duration::from_secs(10),
         }));
         let mut proc = ProcessConfig::default();
         let proc = proc
@@ -813,7 +1785,7 @@ mod tests {
         let hc = hc.set_check(HealthCheckType::Command(CommandHealthCheck {
             cmd: "sleep".to_string(),
             args: vec!["10".to_string()],
-            timeout: 10,
+            timeout: types::Duration::from_secs(10),
         }));
         let mut proc = ProcessConfig::default();
         let proc = proc
@@ -0,0 +1,174 @@
+use std::{collections::HashSet, ffi::OsString, io, path::Path, time::Duration};
+
+use tokio::{
+    io::unix::AsyncFd,
+    time::{Instant, sleep_until},
+};
+
+use crate::run::inotify::Inotify;
+
+/// How long the watched files have to stay untouched before a change is reported.
+/// Editors and config management tools rarely write a file in a single step (truncate
+/// then write, write to a temporary file then rename it over the original, ...).
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// # `ConfigWatcher`
+/// `src/run/daemon/config_watcher.rs`
+///
+/// Watches the config file for changes using inotify.
+///
+/// The parent directories are watched rather than the files themselves, a file being
+/// replaced by a rename would otherwise silently drop its watch.
+pub struct ConfigWatcher {
+    inotify: AsyncFd<Inotify>,
+    /// File names (relative to their watched directory) whose events are relevant.
+    names: HashSet<OsString>,
+    /// Set once a relevant event was read, [`ConfigWatcher::changed`] returns when
+    /// it passes without further events.
+    deadline: Option<Instant>,
+}
+
+impl ConfigWatcher {
+    pub fn new(paths: &[&str]) -> Result<Self, io::Error> {
+        let inotify = Inotify::new()?;
+
+        let mut names = HashSet::new();
+        for path in paths {
+            let path = Path::new(path);
+            let (dir, name) = match (path.parent(), path.file_name()) {
+                (Some(dir), Some(name)) if !dir.as_os_str().is_empty() => (dir, name),
+                (_, Some(name)) => (Path::new("."), name),
+                _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("invalid config path: '{}'", path.display()))),
+            };
+
+            let mask = libc::IN_CLOSE_WRITE | libc::IN_MOVED_TO | libc::IN_CREATE | libc::IN_MODIFY | libc::IN_DELETE;
+            inotify.add_watch(dir, mask)?;
+            names.insert(name.to_owned());
+        }
+
+        Ok(Self {
+            inotify: AsyncFd::new(inotify)?,
+            names,
+            deadline: None,
+        })
+    }
+
+    /// Resolves once one of the watched files changed and was left alone for
+    /// [`DEBOUNCE`].
+    ///
+    /// Cancel safe, the debounce deadline is kept across calls so this can be polled
+    /// from a `select!` loop.
+    pub async fn changed(&mut self) -> Result<(), io::Error> {
+        loop {
+            match self.deadline {
+                Some(deadline) => {
+                    tokio::select! {
+                        _ = sleep_until(deadline) => {
+                            self.deadline = None;
+                            return Ok(());
+                        }
+                        relevant = self.read_events() => {
+                            if relevant? {
+                                self.deadline = Some(Instant::now() + DEBOUNCE);
+                            }
+                        }
+                    }
+                }
+                None => {
+                    if self.read_events().await? {
+                        self.deadline = Some(Instant::now() + DEBOUNCE);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Waits for the inotify fd to become readable and reads the pending events. Returns whether any
+    /// of the events concerned a watched file.
+    async fn read_events(&self) -> Result<bool, io::Error> {
+        loop {
+            let mut guard = self.inotify.readable().await?;
+            let events = match guard.try_io(|inotify| inotify.get_ref().read_events()) {
+                Ok(events) => events?,
+                Err(_would_block) => continue,
+            };
+            return Ok(events.iter().any(|event| self.names.contains(&event.name)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rand::{Rng, distr::Alphanumeric};
+
+    use super::*;
+
+    fn random_dir() -> String {
+        let name = rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(8)
+            .map(char::from)
+            .collect::<String>();
+        let dir = format!("/tmp/{name}");
+        fs::create_dir(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn write_is_reported() {
+        let dir = random_dir();
+        let path = format!("{dir}/config.toml");
+        fs::write(&path, "a").unwrap();
+        let mut watcher = ConfigWatcher::new(&[&path]).unwrap();
+
+        fs::write(&path, "b").unwrap();
+
+        assert!(tokio::time::timeout(Duration::from_secs(2), watcher.changed()).await.is_ok());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn rename_over_is_reported() {
+        let dir = random_dir();
+        let path = format!("{dir}/config.toml");
+        fs::write(&path, "a").unwrap();
+        let mut watcher = ConfigWatcher::new(&[&path]).unwrap();
+
+        fs::write(format!("{dir}/config.toml.tmp"), "b").unwrap();
+        fs::rename(format!("{dir}/config.toml.tmp"), &path).unwrap();
+
+        assert!(tokio::time::timeout(Duration::from_secs(2), watcher.changed()).await.is_ok());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn unrelated_file_is_ignored() {
+        let dir = random_dir();
+        let path = format!("{dir}/config.toml");
+        fs::write(&path, "a").unwrap();
+        let mut watcher = ConfigWatcher::new(&[&path]).unwrap();
+
+        fs::write(format!("{dir}/other.toml"), "b").unwrap();
+
+        assert!(tokio::time::timeout(Duration::from_secs(1), watcher.changed()).await.is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn burst_of_writes_is_reported_once() {
+        let dir = random_dir();
+        let path = format!("{dir}/config.toml");
+        fs::write(&path, "a").unwrap();
+        let mut watcher = ConfigWatcher::new(&[&path]).unwrap();
+
+        fs::write(&path, "").unwrap();
+        fs::write(&path, "b").unwrap();
+        fs::write(&path, "c").unwrap();
+
+        assert!(tokio::time::timeout(Duration::from_secs(2), watcher.changed()).await.is_ok());
+        assert!(tokio::time::timeout(Duration::from_secs(1), watcher.changed()).await.is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    os::{fd::AsRawFd, unix::fs::OpenOptionsExt},
+};
+
+use libc::{EWOULDBLOCK, LOCK_EX, LOCK_NB, LOCK_UN, flock};
+
+/// # `PidFile`
+/// `src/run/daemon/pidfile.rs`
+///
+/// PID file holding an exclusive `flock` for as long as it is alive. The lock is
+/// released by the kernel when the file descriptor is closed, so a crashed daemon
+/// never leaves a stale lock behind, even if the file itself is still there.
+///
+/// The file is never removed: another daemon may already have opened it and be
+/// waiting to lock it, it would then lock the unlinked file while a third one
+/// creates and locks a new one at the same path.
+#[derive(Debug)]
+pub struct PidFile {
+    path: String,
+    /// Kept open so the lock is held until `PidFile` is dropped.
+    file: File,
+}
+
+fn try_lock(file: &File) -> Result<bool, std::io::Error> {
+    if unsafe { flock(file.as_raw_fd(), LOCK_EX | LOCK_NB) } == 0 {
+        return Ok(true);
+    }
+
+    let err = std::io::Error::last_os_error();
+    match err.raw_os_error() {
+        Some(EWOULDBLOCK) => Ok(false),
+        _ => Err(err),
+    }
+}
+
+impl PidFile {
+    /// Opens (or creates) the file at `path`, locks it and writes the PID of the
+    /// calling process to it.
+    ///
+    /// Fails if another live process currently holds the lock.
+    pub fn acquire(path: &str) -> Result<Self, String> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .mode(0o644)
+            .open(path)
+            .map_err(|e| format!("could not open pid file at path '{path}': {e}"))?;
+
+        match try_lock(&file) {
+            Ok(true) => {}
+            Ok(false) => {
+                let mut pid = String::new();
+                let _ = file.read_to_string(&mut pid);
+                return Err(format!("another taskmaster instance (PID {}) is already running with pid file '{path}'", pid.trim()));
+            }
+            Err(e) => return Err(format!("could not lock pid file at path '{path}': {e}")),
+        }
+
+        let pid = unsafe { libc::getpid() };
+        file.set_len(0)
+            .map_err(|e| format!("could not truncate pid file at path '{path}': {e}"))?;
+        file.seek(SeekFrom::Start(0))
+            .map_err(|e| format!("could not seek pid file at path '{path}': {e}"))?;
+        file.write_all(pid.to_string().as_bytes())
+            .map_err(|e| format!("could not write pid file at path '{path}': {e}"))?;
+
+        Ok(Self { path: path.to_owned(), file })
+    }
+
+    /// Returns `true` if a live process currently holds the lock on the pid file at `path`.
+    pub fn is_locked(path: &str) -> bool {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(_) => return false,
+        };
+
+        match try_lock(&file) {
+            Ok(true) => {
+                unsafe { flock(file.as_raw_fd(), LOCK_UN) };
+                false
+            }
+            Ok(false) => true,
+            Err(_) => false,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, distr::Alphanumeric};
+
+    use super::*;
+
+    fn random_path() -> String {
+        let name = rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(8)
+            .map(char::from)
+            .collect::<String>();
+        format!("/tmp/{name}.pid")
+    }
+
+    #[test]
+    fn acquire_writes_pid() {
+        let path = random_path();
+        let _pidfile = PidFile::acquire(&path).unwrap();
+
+        let pid = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(pid, std::process::id().to_string());
+    }
+
+    #[test]
+    fn acquire_twice_fails() {
+        let path = random_path();
+        let _pidfile = PidFile::acquire(&path).unwrap();
+
+        assert!(PidFile::acquire(&path).is_err());
+    }
+
+    #[test]
+    fn is_locked() {
+        let path = random_path();
+        assert!(!PidFile::is_locked(&path));
+
+        let pidfile = PidFile::acquire(&path).unwrap();
+        assert!(PidFile::is_locked(&path));
+
+        drop(pidfile);
+        assert!(!PidFile::is_locked(&path));
+    }
+
+    #[test]
+    fn drop_keeps_the_file() {
+        let path = random_path();
+        drop(PidFile::acquire(&path).unwrap());
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+        let _pidfile = PidFile::acquire(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), std::process::id().to_string());
+    }
+
+    #[test]
+    fn stale_file_is_not_locked() {
+        let path = random_path();
+        std::fs::write(&path, "999999").unwrap();
+
+        assert!(!PidFile::is_locked(&path));
+        let _pidfile = PidFile::acquire(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), std::process::id().to_string());
+    }
+}
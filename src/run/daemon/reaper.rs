@@ -0,0 +1,102 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    log_info,
+    run::proc::procfs::{self, Stat},
+};
+
+/// How often `/proc` is scanned for orphaned zombies.
+const REAP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a zombie has to be around before it is considered orphaned. Children
+/// taskmaster spawned itself (healthcheck commands, ...) are reaped by whoever spawned
+/// them right after exiting, reaping them here would steal their exit status.
+const REAP_GRACE: Duration = Duration::from_secs(1);
+
+/// # `Reaper`
+/// `src/run/daemon/reaper.rs`
+///
+/// Reaps zombies which were reparented to taskmaster, as happens to every orphaned
+/// process in a container where taskmaster runs as PID 1, and to every orphaned
+/// descendant of a supervised process once taskmaster became a child subreaper for
+/// `killmode = "tree"`. Without it, their entries in the process table are never freed.
+pub struct Reaper {
+    /// Whether taskmaster runs as PID 1, orphans are only reparented to it otherwise
+    /// once it is a child subreaper.
+    init: bool,
+    /// Zombies seen during previous sweeps, with the time they were first seen.
+    zombies: HashMap<u32, Instant>,
+    last_sweep: Option<Instant>,
+}
+
+impl Reaper {
+    pub fn new(init: bool) -> Self {
+        Self {
+            init,
+            zombies: HashMap::new(),
+            last_sweep: None,
+        }
+    }
+
+    pub fn due(&self) -> bool {
+        self.last_sweep.is_none_or(|at| at.elapsed() >= REAP_INTERVAL)
+    }
+
+    /// Reaps the orphaned zombies, `children` being the PIDs of the supervised
+    /// processes, which are always left to their `Process`.
+    pub fn sweep(&mut self, children: &BTreeSet<u32>) {
+        let now = Instant::now();
+        self.last_sweep = Some(now);
+        if !self.init && !subreaper() {
+            return;
+        }
+
+        let zombies = orphaned_zombies(&procfs::all(), std::process::id(), children);
+
+        self.zombies.retain(|pid, _| zombies.contains(pid));
+        for pid in zombies {
+            let first_seen = *self.zombies.entry(pid).or_insert(now);
+            if now.duration_since(first_seen) < REAP_GRACE {
+                continue;
+            }
+
+            let mut status = 0;
+            if unsafe { libc::waitpid(pid as i32, &mut status, libc::WNOHANG) } == pid as i32 {
+                log_info!("reaped orphaned process",; pid = pid, status = status);
+                self.zombies.remove(&pid);
+            }
+        }
+    }
+}
+
+/// Whether taskmaster is a child subreaper, which it becomes once a process with
+/// `killmode = "tree"` is spawned.
+fn subreaper() -> bool {
+    let mut subreaper: libc::c_int = 0;
+    unsafe { libc::prctl(libc::PR_GET_CHILD_SUBREAPER, &mut subreaper as *mut libc::c_int) };
+    subreaper != 0
+}
+
+fn orphaned_zombies(stats: &[Stat], daemon: u32, children: &BTreeSet<u32>) -> BTreeSet<u32> {
+    stats
+        .iter()
+        .filter(|s| s.ppid == daemon && s.is_zombie() && !children.contains(&s.pid))
+        .map(|s| s.pid)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_foreign_zombies_are_orphans() {
+        let stat = |pid, state, ppid| Stat { pid, state, ppid, pgrp: pid };
+        let stats = vec![stat(10, 'Z', 1), stat(11, 'Z', 1), stat(12, 'S', 1), stat(13, 'Z', 2)];
+
+        assert_eq!(orphaned_zombies(&stats, 1, &BTreeSet::from([10])), BTreeSet::from([11]));
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use crate::{
+    jsonrpc::rolling_restart_progress::{InstanceStatus, RollingRestartProgress, RolloutStatus},
+    log_info, log_warn, proc_info,
+    run::{
+        proc::{ManualOverride, Process},
+        statemachine::states::ProcessState,
+    },
+};
+
+/// # `RollingRestart`
+/// `src/run/daemon/rolling_restart.rs`
+///
+/// Restarts the instances of a program (`<program>_0`, `<program>_1`, ...) in batches,
+/// only moving on to the next batch once every instance of the previous one is
+/// `Healthy` again.
+struct RollingRestart {
+    progress: RollingRestartProgress,
+    /// PID of each instance when it was restarted, a `Healthy` instance is only done once
+    /// it runs under a new one.
+    previous_ids: HashMap<String, Option<u32>>,
+}
+
+/// # `RollingRestarts`
+/// `src/run/daemon/rolling_restart.rs`
+///
+/// The rolling restarts started through the `rolling_restart` request, by program.
+/// Finished ones are kept around until the next one for the same program, so their
+/// outcome can still be queried.
+#[derive(Default)]
+pub struct RollingRestarts {
+    restarts: HashMap<String, RollingRestart>,
+}
+
+impl RollingRestarts {
+    /// `instances` being the names of the instances of `program`, in the order they are
+    /// restarted in.
+    pub fn start(
+        &mut self,
+        program: &str,
+        instances: Vec<String>,
+        batch_size: usize,
+        max_unavailable: usize,
+        abort_on_failure: bool,
+    ) -> Result<&RollingRestartProgress, String> {
+        if batch_size == 0 || max_unavailable == 0 {
+            return Err("batch size and max unavailable must be at least 1".to_string());
+        }
+        if self.restarts.get(program).is_some_and(|r| !r.progress.finished()) {
+            return Err(format!("a rolling restart of {program} is already running"));
+        }
+
+        log_info!("starting rolling restart",; program = program, instances = instances.len(), batch_size = batch_size, max_unavailable = max_unavailable);
+
+        let restart = RollingRestart {
+            progress: RollingRestartProgress::new(program, batch_size, max_unavailable, abort_on_failure, &instances),
+            previous_ids: HashMap::new(),
+        };
+        self.restarts.insert(program.to_owned(), restart);
+        Ok(&self.restarts[program].progress)
+    }
+
+    pub fn progress(&self, program: &str) -> Option<&RollingRestartProgress> {
+        self.restarts.get(program).map(|r| &r.progress)
+    }
+
+    /// Checks on the instances being restarted and starts the next batch once they
+    /// are all done.
+    pub fn advance(&mut self, processes: &mut HashMap<String, Process>) {
+        for restart in self.restarts.values_mut().filter(|r| !r.progress.finished()) {
+            restart.advance(processes);
+        }
+    }
+}
+
+impl RollingRestart {
+    fn advance(&mut self, processes: &mut HashMap<String, Process>) {
+        for instance in self.progress.instances_mut() {
+            if *instance.status() != InstanceStatus::Restarting {
+                continue;
+            }
+            let status = match processes.get(instance.name()) {
+                Some(process) => restart_status(process, self.previous_ids[instance.name()]),
+                None => InstanceStatus::Failed("no longer configured".to_string()),
+            };
+            if let InstanceStatus::Failed(reason) = &status {
+                log_warn!("rolling restart: instance failed",; process = instance.name(), reason = reason);
+            }
+            instance.set_status(status);
+        }
+
+        let failed = self
+            .progress
+            .instances()
+            .iter()
+            .any(|i| matches!(i.status(), InstanceStatus::Failed(_)));
+        if failed && self.progress.abort_on_failure() {
+            log_warn!("aborting rolling restart",; program = self.progress.program());
+            self.progress.set_status(RolloutStatus::Aborted);
+            return;
+        }
+
+        // The previous batch is still in progress.
+        if self
+            .progress
+            .instances()
+            .iter()
+            .any(|i| *i.status() == InstanceStatus::Restarting)
+        {
+            return;
+        }
+
+        // Restarting an instance an operator stopped would start it again.
+        for instance in self.progress.instances_mut() {
+            if *instance.status() == InstanceStatus::Pending
+                && processes
+                    .get(instance.name())
+                    .is_some_and(|p| p.manual_override() == Some(ManualOverride::Stopped))
+            {
+                log_info!("rolling restart: skipping instance stopped by an operator",; process = instance.name());
+                instance.set_status(InstanceStatus::Skipped);
+            }
+        }
+
+        let pending = self
+            .progress
+            .instances()
+            .iter()
+            .filter(|i| *i.status() == InstanceStatus::Pending)
+            .map(|i| i.name().to_owned())
+            .collect::<Vec<String>>();
+        if pending.is_empty() {
+            log_info!("rolling restart completed",; program = self.progress.program());
+            self.progress.set_status(RolloutStatus::Completed);
+            return;
+        }
+
+        // Instances which are still to be restarted do not count, restarting them does
+        // not make anything less available. Neither do the ones an operator stopped.
+        let unavailable = self
+            .progress
+            .instances()
+            .iter()
+            .filter(|i| !matches!(i.status(), InstanceStatus::Pending | InstanceStatus::Skipped))
+            .filter_map(|i| processes.get(i.name()))
+            .filter(|p| p.state() != ProcessState::Healthy)
+            .collect::<Vec<&Process>>();
+        let batch = self
+            .progress
+            .batch_size()
+            .min(self.progress.max_unavailable().saturating_sub(unavailable.len()));
+
+        // Instances which are only briefly unavailable, e.g. while retrying their
+        // healthcheck, free up room again by themselves, the others never do.
+        if batch == 0 && unavailable.iter().all(|p| stuck(p)) {
+            let reason = format!(
+                "{} instance(s) are down without being restarted, max unavailable {} leaves no room to restart {} more",
+                unavailable.len(),
+                self.progress.max_unavailable(),
+                pending.len()
+            );
+            log_warn!("rolling restart failed",; program = self.progress.program(), reason = reason);
+            self.progress.set_status(RolloutStatus::Failed(reason));
+            return;
+        }
+
+        for name in pending.into_iter().take(batch) {
+            let Some(process) = processes.get_mut(&name) else {
+                continue;
+            };
+            self.previous_ids.insert(name.clone(), process.id());
+            process.set_manual_override(Some(ManualOverride::Started));
+            let status = match process.restart() {
+                Ok(strategy) => {
+                    proc_info!(process, "rolling restart: restarting",; strategy = format!("{strategy:?}"));
+                    InstanceStatus::Restarting
+                }
+                Err(e) => InstanceStatus::Failed(e.to_string()),
+            };
+            if let Some(instance) = self.progress.instances_mut().iter_mut().find(|i| i.name() == name) {
+                instance.set_status(status);
+            }
+        }
+    }
+}
+
+/// Whether `process` stays down until someone intervenes.
+fn stuck(process: &Process) -> bool {
+    use ProcessState::*;
+    process.desired_states().is_empty() && matches!(process.state(), Completed | Stopped | Idle | Fatal(_))
+}
+
+/// Where an instance restarted by a rolling restart is at, `previous_id` being its PID
+/// before the restart.
+fn restart_status(process: &Process, previous_id: Option<u32>) -> InstanceStatus {
+    if let Some(reason) = process.restart_failure() {
+        return InstanceStatus::Failed(reason.to_owned());
+    }
+    // Still stopping, or waiting for its replacement to become healthy.
+    if !process.desired_states().is_empty() || process.replacement().is_some() {
+        return InstanceStatus::Restarting;
+    }
+
+    use ProcessState::*;
+    match process.state() {
+        Healthy if process.id().is_some() && process.id() != previous_id => InstanceStatus::Done,
+        // A failed healthcheck goes through `Failed` and `WaitingForRetry` for every
+        // retry, the instance only failed once it gave up (`Stopped`) or became `Fatal`.
+        Ready | HealthCheck(_) | Healthy | Stopping(_) | Failed(_) | WaitingForRetry(_) => InstanceStatus::Restarting,
+        state @ (Completed | Stopped | Idle | Fatal(_)) => InstanceStatus::Failed(state.to_string()),
+    }
+}
@@ -0,0 +1,55 @@
+use tokio::signal::unix::{Signal, SignalKind, signal};
+
+/// Signals the daemon reacts to, see [`Signals::recv`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DaemonSignal {
+    /// `SIGHUP`: reload the configuration.
+    Reload,
+    /// `SIGINT` or `SIGTERM`: stop all processes gracefully, then exit.
+    Shutdown,
+    /// `SIGQUIT`: kill all processes forcefully and exit immediately.
+    Quit,
+    /// `SIGUSR1`: reopen the log file, e.g. after it was rotated.
+    ReopenLogs,
+    /// `SIGUSR2`: dump the daemon's internal state to the log.
+    DumpState,
+}
+
+/// # `Signals`
+/// `src/run/daemon/signals.rs`
+///
+/// Bundles the tokio signal streams the daemon listens on, so they can be awaited
+/// as a single branch of the main loop's `select!`.
+pub struct Signals {
+    hup: Signal,
+    int: Signal,
+    term: Signal,
+    quit: Signal,
+    usr1: Signal,
+    usr2: Signal,
+}
+
+impl Signals {
+    pub fn new() -> Result<Self, std::io::Error> {
+        Ok(Self {
+            hup: signal(SignalKind::hangup())?,
+            int: signal(SignalKind::interrupt())?,
+            term: signal(SignalKind::terminate())?,
+            quit: signal(SignalKind::quit())?,
+            usr1: signal(SignalKind::user_defined1())?,
+            usr2: signal(SignalKind::user_defined2())?,
+        })
+    }
+
+    /// Waits for the next signal and maps it to the action the daemon should take.
+    pub async fn recv(&mut self) -> DaemonSignal {
+        tokio::select! {
+            _ = self.hup.recv() => DaemonSignal::Reload,
+            _ = self.int.recv() => DaemonSignal::Shutdown,
+            _ = self.term.recv() => DaemonSignal::Shutdown,
+            _ = self.quit.recv() => DaemonSignal::Quit,
+            _ = self.usr1.recv() => DaemonSignal::ReopenLogs,
+            _ = self.usr2.recv() => DaemonSignal::DumpState,
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    collections::VecDeque,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{conf::Config, jsonrpc::daemon_info::SnapshotInfo};
+
+#[derive(Clone)]
+pub struct ConfigSnapshot {
+    /// Seconds since the UNIX epoch.
+    timestamp: u64,
+    config: Config,
+}
+
+impl ConfigSnapshot {
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn info(&self) -> SnapshotInfo {
+        SnapshotInfo::new(self.timestamp, self.config.hash())
+    }
+}
+
+/// # `ConfigSnapshots`
+/// `src/run/daemon/snapshots.rs`
+///
+/// History of the configurations the daemon successfully applied, oldest first. The
+/// last snapshot is always the configuration currently in use.
+///
+/// The number of snapshots kept is the `snapshots` setting of the active configuration.
+pub struct ConfigSnapshots {
+    snapshots: VecDeque<ConfigSnapshot>,
+}
+
+impl ConfigSnapshots {
+    pub fn new(initial: Config) -> Self {
+        let mut snapshots = Self { snapshots: VecDeque::new() };
+        snapshots.push(initial);
+        snapshots
+    }
+
+    /// Records `config` as the active configuration. Reloading an unchanged config
+    /// file does not create a new snapshot.
+    pub fn push(&mut self, config: Config) {
+        if self.snapshots.back().is_some_and(|s| s.config.hash() == config.hash()) {
+            return;
+        }
+
+        let max = config.snapshots().max(1);
+        self.snapshots.push_back(ConfigSnapshot {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            config,
+        });
+
+        while self.snapshots.len() > max {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Returns the snapshot `steps` configurations before the active one.
+    pub fn get(&self, steps: usize) -> Result<&ConfigSnapshot, String> {
+        let available = self.snapshots.len() - 1;
+        if steps == 0 || steps > available {
+            return Err(format!("cannot roll back {steps} configuration(s), {available} earlier configuration(s) available"));
+        }
+
+        Ok(&self.snapshots[available - steps])
+    }
+
+    /// Drops the `steps` most recent snapshots, making the one before them active.
+    pub fn rewind(&mut self, steps: usize) {
+        let keep = self.snapshots.len().saturating_sub(steps).max(1);
+        self.snapshots.truncate(keep);
+    }
+
+    pub fn infos(&self) -> Vec<SnapshotInfo> {
+        self.snapshots.iter().map(ConfigSnapshot::info).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(processes: u8) -> Config {
+        let conf_str = format!("[processes.sleep]\ncmd = \"/usr/bin/sleep\"\nworkingdir = \"/tmp\"\nprocesses = {processes}\n");
+        Config::from_str(&conf_str).unwrap()
+    }
+
+    fn config_keeping(snapshots: usize, processes: u8) -> Config {
+        let conf_str = format!("snapshots = {snapshots}\n[processes.sleep]\ncmd = \"/usr/bin/sleep\"\nworkingdir = \"/tmp\"\nprocesses = {processes}\n");
+        Config::from_str(&conf_str).unwrap()
+    }
+
+    #[test]
+    fn unchanged_config_is_not_recorded() {
+        let mut snapshots = ConfigSnapshots::new(config(1));
+        snapshots.push(config(1));
+
+        assert_eq!(snapshots.infos().len(), 1);
+    }
+
+    #[test]
+    fn oldest_snapshots_are_dropped() {
+        let mut snapshots = ConfigSnapshots::new(config(1));
+        for i in 2..=15 {
+            snapshots.push(config(i));
+        }
+
+        assert_eq!(snapshots.infos().len(), 10);
+        assert_eq!(snapshots.get(9).unwrap().config().processes()["sleep"].processes(), 6);
+    }
+
+    #[test]
+    fn configured_number_of_snapshots_is_kept() {
+        let mut snapshots = ConfigSnapshots::new(config_keeping(3, 1));
+        for i in 2..=5 {
+            snapshots.push(config_keeping(3, i));
+        }
+
+        assert_eq!(snapshots.infos().len(), 3);
+        assert_eq!(snapshots.get(2).unwrap().config().processes()["sleep"].processes(), 3);
+        assert!(snapshots.get(3).is_err());
+
+        // Lowering the limit prunes the history right away.
+        snapshots.push(config_keeping(1, 6));
+        assert_eq!(snapshots.infos().len(), 1);
+        assert!(snapshots.get(1).is_err());
+    }
+
+    #[test]
+    fn get_and_rewind() {
+        let mut snapshots = ConfigSnapshots::new(config(1));
+        snapshots.push(config(2));
+        snapshots.push(config(3));
+
+        assert!(snapshots.get(0).is_err());
+        assert!(snapshots.get(3).is_err());
+        assert_eq!(snapshots.get(2).unwrap().config().processes()["sleep"].processes(), 1);
+
+        snapshots.rewind(1);
+        assert_eq!(snapshots.infos().len(), 2);
+        assert_eq!(snapshots.get(1).unwrap().config().processes()["sleep"].processes(), 1);
+    }
+}
@@ -16,18 +16,24 @@ pub struct AsyncUnixSocket {
     stream: Option<UnixStream>,
 }
 
+/// Returns `None` if the group does not exist. `getgrnam` also returns null when the
+/// lookup itself fails, which is only told apart from a missing group by `errno`.
 #[cfg(not(test))]
-fn get_group_id(group_name: &str) -> Result<u32, String> {
+fn get_group_id(group_name: &str) -> Result<Option<u32>, String> {
     let c_group = std::ffi::CString::new(group_name).map_err(|e| format!("{e}"))?;
 
     unsafe {
+        *libc::__errno_location() = 0;
         let grp_ptr = libc::getgrnam(c_group.as_ptr());
-        if grp_ptr.is_null() {
-            Err(format!("Group '{group_name}' not found"))
-        } else {
-            Ok((*grp_ptr).gr_gid)
+        if !grp_ptr.is_null() {
+            return Ok(Some((*grp_ptr).gr_gid));
         }
     }
+
+    match std::io::Error::last_os_error() {
+        e if e.raw_os_error() == Some(0) => Ok(None),
+        e => Err(format!("could not look up group '{group_name}': {e}")),
+    }
 }
 
 #[cfg(not(test))]
@@ -49,6 +55,13 @@ fn set_permissions(socketpath: &str, authgroup: &str, gid: u32) -> Result<(), St
     fs::set_permissions(socketpath, fs::Permissions::from_mode(0o660)).map_err(|e| format!("could not set permissions: {e}"))
 }
 
+#[cfg(not(test))]
+fn set_owner_only_permissions(socketpath: &str) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(socketpath, fs::Permissions::from_mode(0o600)).map_err(|e| format!("could not set permissions: {e}"))
+}
+
 impl AsyncUnixSocket {
     pub fn new(socketpath: &str, authgroup: &str) -> Result<Self, String> {
         if fs::metadata(socketpath).is_ok() {
@@ -62,10 +75,19 @@ impl AsyncUnixSocket {
             }
         };
 
+        // Minimal container images usually do not have the auth group, fall back to
+        // only allowing the daemon's own user instead of refusing to start.
         #[cfg(not(test))]
-        let gid = get_group_id(authgroup)?;
+        let permissions = match get_group_id(authgroup) {
+            Ok(Some(gid)) => set_permissions(socketpath, authgroup, gid),
+            Ok(None) => {
+                crate::log_warn!("auth group not found, only the daemon's user can connect to the socket",; authgroup = authgroup);
+                set_owner_only_permissions(socketpath)
+            }
+            Err(e) => Err(e),
+        };
         #[cfg(not(test))]
-        if let Err(e) = set_permissions(socketpath, authgroup, gid) {
+        if let Err(e) = permissions {
             return Err(format!("could not create UNIX socket at path {socketpath}: {e}"));
         }
 
@@ -0,0 +1,88 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    conf::proc::types::SocketConfig,
+    log_info, proc_error,
+    run::proc::{Process, sockets::ListeningSocket},
+};
+
+/// How long to wait before binding again after a socket could not be bound.
+const BIND_RETRY: Duration = Duration::from_secs(1);
+
+/// # `SocketRegistry`
+/// `src/run/daemon/sockets.rs`
+///
+/// The listening sockets bound on behalf of the processes. Processes configuring the
+/// same socket share it, which keeps it open when the config of a process is reloaded
+/// or it is scaled up, as long as the socket itself did not change.
+#[derive(Default)]
+pub struct SocketRegistry {
+    sockets: HashMap<SocketConfig, Arc<ListeningSocket>>,
+    last_bind_failure: Option<Instant>,
+}
+
+impl SocketRegistry {
+    /// Hands every process which is not running the sockets it is configured with,
+    /// binding them if needed. Sockets no process uses anymore are closed first, a
+    /// changed socket might be bound to the same address.
+    pub fn provide<'a>(&mut self, processes: impl Iterator<Item = &'a mut Process>) {
+        let mut outdated = processes
+            .filter(|p| p.id().is_none() && p.sockets_outdated())
+            .collect::<Vec<&mut Process>>();
+        for proc in outdated.iter_mut() {
+            proc.set_sockets(vec![]);
+        }
+        self.close_unused();
+
+        for proc in outdated {
+            if self.last_bind_failure.is_some_and(|at| at.elapsed() < BIND_RETRY) {
+                return;
+            }
+
+            let mut sockets = vec![];
+            for (name, conf) in proc.config().sockets().iter() {
+                match self.get(conf) {
+                    Ok(socket) => sockets.push((name.to_owned(), socket)),
+                    Err(e) => {
+                        proc_error!(proc, "could not bind socket",; socket = name, error = e.to_string());
+                        self.last_bind_failure = Some(Instant::now());
+                        break;
+                    }
+                }
+            }
+
+            // Partially bound sockets are of no use, the process cannot be started anyway.
+            if sockets.len() == proc.config().sockets().len() {
+                proc.set_sockets(sockets);
+            }
+        }
+    }
+
+    /// Closes the sockets no process holds anymore, e.g. after it was removed from
+    /// the configuration.
+    pub fn close_unused(&mut self) {
+        self.sockets.retain(|conf, socket| {
+            let used = Arc::strong_count(socket) > 1;
+            if !used {
+                log_info!("closing listening socket, no process uses it anymore",; socket = format!("{conf:?}"));
+            }
+            used
+        });
+    }
+
+    fn get(&mut self, conf: &SocketConfig) -> Result<Arc<ListeningSocket>, std::io::Error> {
+        if let Some(socket) = self.sockets.get(conf) {
+            return Ok(Arc::clone(socket));
+        }
+
+        let socket = Arc::new(ListeningSocket::bind(conf)?);
+        log_info!("listening on behalf of processes",; socket = format!("{conf:?}"));
+        self.sockets.insert(conf.clone(), Arc::clone(&socket));
+
+        Ok(socket)
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::ErrorKind,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::run::{
+    proc::{ManualOverride, Process},
+    statemachine::states::ProcessState,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PersistedProcess {
+    #[serde(default)]
+    manual_override: Option<ManualOverride>,
+    #[serde(default)]
+    runtime_failures: usize,
+    #[serde(default)]
+    healthcheck_failures: usize,
+    /// Unix timestamp of the last scheduled run.
+    #[serde(default)]
+    last_scheduled_run: Option<u64>,
+}
+
+/// # `PersistedState`
+/// `src/run/daemon/statefile.rs`
+///
+/// The part of the processes' state which has to survive a restart of the daemon:
+/// operator overrides, failure counters and when the last scheduled run was started.
+/// Processes with nothing worth persisting are left out.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PersistedState {
+    processes: BTreeMap<String, PersistedProcess>,
+}
+
+impl PersistedState {
+    pub fn from_processes(processes: &HashMap<String, Process>) -> Self {
+        let processes = processes
+            .iter()
+            .map(|(name, proc)| {
+                (
+                    name.to_owned(),
+                    PersistedProcess {
+                        manual_override: proc.manual_override(),
+                        runtime_failures: proc.runtime_failures(),
+                        healthcheck_failures: proc.healthcheck().failures(),
+                        last_scheduled_run: proc
+                            .last_scheduled_run()
+                            .and_then(|at| at.duration_since(UNIX_EPOCH).ok())
+                            .map(|since_epoch| since_epoch.as_secs()),
+                    },
+                )
+            })
+            .filter(|(_, persisted)| *persisted != PersistedProcess::default())
+            .collect();
+
+        Self { processes }
+    }
+
+    /// Reads the state file at `path`. A missing file is not an error, the daemon
+    /// simply never persisted anything there.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("could not read state file at '{path}': {e}")),
+        };
+
+        serde_json::from_str(&content).map_err(|e| format!("could not parse state file at '{path}': {e}"))
+    }
+
+    /// Writes the state to a temporary file first and renames it over `path`, so a
+    /// crash never leaves a truncated state file behind.
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let content = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        let tmp = format!("{path}.tmp");
+
+        fs::write(&tmp, content).map_err(|e| format!("could not write state file at '{tmp}': {e}"))?;
+        fs::rename(&tmp, path).map_err(|e| format!("could not move state file to '{path}': {e}"))
+    }
+
+    /// Restores the persisted state onto freshly created processes. Entries for
+    /// processes which are not configured anymore are ignored.
+    pub fn apply(&self, processes: &mut HashMap<String, Process>) {
+        for (name, persisted) in self.processes.iter() {
+            let Some(proc) = processes.get_mut(name) else {
+                continue;
+            };
+
+            proc.set_manual_override(persisted.manual_override);
+            proc.set_runtime_failures(persisted.runtime_failures);
+            proc.healthcheck_mut().set_failures(persisted.healthcheck_failures);
+            proc.set_last_scheduled_run(persisted.last_scheduled_run.map(|secs| UNIX_EPOCH + Duration::from_secs(secs)));
+
+            match persisted.manual_override {
+                // Drops the `Ready` queued because of `autostart`.
+                Some(ManualOverride::Stopped) => proc.desired_states_mut().clear(),
+                Some(ManualOverride::Started) if proc.desired_states().is_empty() => proc.push_desired_state(ProcessState::Healthy),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn processes(&self) -> &BTreeMap<String, PersistedProcess> {
+        &self.processes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, distr::Alphanumeric};
+
+    use super::*;
+    use crate::conf::proc::ProcessConfig;
+
+    fn random_path() -> String {
+        let name = rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(8)
+            .map(char::from)
+            .collect::<String>();
+        format!("/tmp/{name}.state")
+    }
+
+    fn processes() -> HashMap<String, Process> {
+        let mut proc = ProcessConfig::default();
+        let proc = proc.set_cmd("sleep").set_args(vec!["2".to_string()]).set_autostart(true);
+
+        HashMap::from([
+            ("worker".to_string(), Process::from_process_config(proc.clone(), "worker")),
+            ("other".to_string(), Process::from_process_config(proc.clone(), "other")),
+        ])
+    }
+
+    #[test]
+    fn missing_file_is_empty_state() {
+        assert_eq!(PersistedState::load(&random_path()).unwrap(), PersistedState::default());
+    }
+
+    #[test]
+    fn only_non_default_processes_are_persisted() {
+        let mut procs = processes();
+        procs
+            .get_mut("worker")
+            .unwrap()
+            .set_manual_override(Some(ManualOverride::Stopped));
+
+        let state = PersistedState::from_processes(&procs);
+        assert_eq!(state.processes().keys().collect::<Vec<_>>(), vec!["worker"]);
+    }
+
+    #[test]
+    fn save_load_apply() {
+        let path = random_path();
+        let mut procs = processes();
+        procs
+            .get_mut("worker")
+            .unwrap()
+            .set_manual_override(Some(ManualOverride::Stopped));
+        procs.get_mut("other").unwrap().set_runtime_failures(3);
+        procs
+            .get_mut("other")
+            .unwrap()
+            .set_last_scheduled_run(Some(UNIX_EPOCH + Duration::from_secs(1_750_000_000)));
+        PersistedState::from_processes(&procs).save(&path).unwrap();
+
+        let mut restarted = processes();
+        PersistedState::load(&path).unwrap().apply(&mut restarted);
+
+        assert_eq!(restarted["worker"].manual_override(), Some(ManualOverride::Stopped));
+        assert!(restarted["worker"].desired_states().is_empty());
+        assert_eq!(restarted["other"].runtime_failures(), 3);
+        assert_eq!(restarted["other"].last_scheduled_run(), Some(UNIX_EPOCH + Duration::from_secs(1_750_000_000)));
+        assert_eq!(restarted["other"].desired_states().front(), Some(&ProcessState::Ready));
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,89 @@
+use std::{
+    ffi::{CString, OsString},
+    io,
+    os::{
+        fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+        unix::ffi::{OsStrExt, OsStringExt},
+    },
+    path::Path,
+};
+
+/// # `Inotify`
+/// `src/run/inotify.rs`
+///
+/// Non-blocking inotify instance, shared by the daemon's config watcher and the file
+/// watchers of processes.
+#[derive(Debug)]
+pub(crate) struct Inotify {
+    fd: OwnedFd,
+}
+
+/// A single event read from an [`Inotify`] instance.
+#[derive(Debug)]
+pub(crate) struct Event {
+    /// Watch descriptor returned by [`Inotify::add_watch`] for the watched directory.
+    pub wd: i32,
+    pub mask: u32,
+    /// Name of the file inside the watched directory, empty for events about the
+    /// directory itself.
+    pub name: OsString,
+}
+
+impl Inotify {
+    pub fn new() -> Result<Self, io::Error> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+        })
+    }
+
+    /// Watches `path` for the events in `mask`, returning the watch descriptor. Watching
+    /// the same path again yields the same descriptor.
+    pub fn add_watch(&self, path: &Path, mask: u32) -> Result<i32, io::Error> {
+        let path = CString::new(path.as_os_str().as_bytes()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let wd = unsafe { libc::inotify_add_watch(self.fd.as_raw_fd(), path.as_ptr(), mask) };
+        if wd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(wd)
+    }
+
+    /// Reads one batch of pending events. Fails with [`io::ErrorKind::WouldBlock`] if
+    /// there are none.
+    pub fn read_events(&self) -> Result<Vec<Event>, io::Error> {
+        // Aligned for `inotify_event`, big enough for a few events with long names.
+        #[repr(C, align(4))]
+        struct Buffer([u8; 4096]);
+        let mut buf = Buffer([0; 4096]);
+
+        let n = unsafe { libc::read(self.fd.as_raw_fd(), buf.0.as_mut_ptr() as *mut libc::c_void, buf.0.len()) };
+        if n < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let n = n as usize;
+        let mut events = vec![];
+        let mut offset = 0;
+        let header = std::mem::size_of::<libc::inotify_event>();
+        while offset + header <= n {
+            let event = unsafe { std::ptr::read_unaligned(buf.0.as_ptr().add(offset) as *const libc::inotify_event) };
+            let name = &buf.0[offset + header..offset + header + event.len as usize];
+            events.push(Event {
+                wd: event.wd,
+                mask: event.mask,
+                name: OsString::from_vec(name.split(|b| *b == 0).next().unwrap_or_default().to_vec()),
+            });
+            offset += header + event.len as usize;
+        }
+        Ok(events)
+    }
+}
+
+impl AsRawFd for Inotify {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
@@ -1,27 +1,58 @@
 use std::{
-    collections::VecDeque,
+    collections::{BTreeMap, BTreeSet, VecDeque},
     error::Error,
-    ffi::CString,
+    ffi::{CString, OsString},
     fs::File,
     os::unix::process::{CommandExt, ExitStatusExt},
     process::{Child, Command, ExitStatus, Stdio},
-    time::{self, Duration, Instant},
+    sync::Arc,
+    time::{self, Duration, Instant, SystemTime},
 };
 
 use crate::{
     conf::{
         self,
-        proc::{ProcessConfig, types::WritableFile},
+        proc::{
+            ProcessConfig,
+            types::{HookKind, KillMode, StopStep, Watch, WritableFile},
+        },
     },
     log_error, proc_info,
 };
 pub use error::ProcessError;
+use hooks::RunningHook;
 use libc::{gid_t, setgid, setgroups, setuid, umask};
+use serde::{Deserialize, Serialize};
+use sockets::{ListenExec, ListeningSocket};
 
 use super::statemachine::{healthcheck::HealthCheckRunner, states::ProcessState};
 
 mod error;
+pub mod history;
+mod hooks;
+pub mod procfs;
+mod replacement;
+pub mod resources;
+mod schedule;
+pub mod sockets;
 mod tests;
+mod watch;
+mod watchdog;
+
+/// Last `start` or `stop` an operator requested for a process. Persisted across daemon
+/// restarts, taking precedence over `autostart`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManualOverride {
+    Started,
+    Stopped,
+}
+
+/// How often descendants of processes with `killmode = "tree"` are looked up.
+const TRACK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the members left behind by a stopping process are looked up.
+const MEMBERS_INTERVAL: Duration = Duration::from_millis(100);
 
 #[allow(unused)]
 #[derive(Debug)]
@@ -29,9 +60,78 @@ pub struct Process {
     id: Option<u32>,
     name: String,
     child: Option<Child>,
+    /// Children killed with `SIGKILL` which did not exit yet, reaped on a later tick
+    /// rather than waited for.
+    killed: Vec<Child>,
     conf: ProcessConfig,
     healthcheck: HealthCheckRunner,
     runtime_failures: usize,
+    manual_override: Option<ManualOverride>,
+    /// Outcome of the last run: the exit code, `128 + signal` if it was killed by a
+    /// signal taskmaster did not send, or `127` if it could not be spawned.
+    exit_code: Option<i32>,
+    /// Descendants seen while running with `killmode = "tree"`, kept so they can still
+    /// be found after being orphaned.
+    tracked: BTreeSet<u32>,
+    tracked_at: Option<Instant>,
+    /// When the members of the stopping process were last looked up, and whether any
+    /// were still running, see `has_running_members`.
+    members_checked: Option<(Instant, bool)>,
+    /// Listening sockets passed to the process, by name. Provided by the daemon, see
+    /// [`Process::sockets_outdated`].
+    sockets: Vec<(String, Arc<ListeningSocket>)>,
+    /// New instance started next to this one by a `start-first` restart, taking its
+    /// place once healthy.
+    replacement: Option<Box<Process>>,
+    /// Instances which were replaced (or failed to replace this one) and are being
+    /// stopped.
+    retiring: Vec<Process>,
+    /// Why the last `start-first` restart failed, cleared by the next restart.
+    restart_failure: Option<String>,
+    /// Since when the process is `Healthy`, its failure counters are reset once it
+    /// stayed healthy for `backoff.reset_after`.
+    healthy_since: Option<Instant>,
+    /// When the process failed within the last `startlimit.interval`, oldest first.
+    recent_failures: VecDeque<Instant>,
+    /// Why the process was given up on while still running, it becomes `Fatal` once
+    /// stopped, see `give_up`.
+    fatal_after_stop: Option<String>,
+    /// Index of the step of the stop sequence being run while `Stopping`, and when it
+    /// was started.
+    stop_step: Option<(usize, Instant)>,
+    /// Stop sequence resolved when the stop began, so a reload changing `stop` does not
+    /// affect a stop which is under way.
+    stop_sequence: Vec<StopStep>,
+    /// `pre_start` or `pre_stop` hook the start or stop of the process is waiting for.
+    hook: Option<(HookKind, RunningHook)>,
+    /// When the next run of a process with a `schedule` is due.
+    next_run: Option<SystemTime>,
+    /// When the last scheduled run was started.
+    last_scheduled_run: Option<SystemTime>,
+    /// A scheduled run is waiting for the current one to finish, see `overlap`.
+    run_queued: bool,
+    /// Last runs of a `oneshot` process, oldest first.
+    history: VecDeque<history::Run>,
+    /// How often the process was spawned, the id of the last run of a `oneshot` process.
+    starts: u64,
+    /// When the process was last spawned.
+    started_at: Option<SystemTime>,
+    /// How the process last exited, including exits caused by taskmaster stopping it.
+    last_exit: Option<history::RunOutcome>,
+    current_run: Option<history::StartedRun>,
+    /// What the next spawn of the process was requested by.
+    next_trigger: Option<history::RunTrigger>,
+    /// Last sample of the resources used by the process, see `sample_resources`.
+    resources: Option<resources::Resources>,
+    /// PID, time and CPU ticks of the last sample, to compute the CPU usage since.
+    sampled: Option<(u32, Instant, u64)>,
+    /// Since when the `restart_if` limits are exceeded, see `check_watchdogs`.
+    exceeded: watchdog::Exceeded,
+    /// Why the process was last restarted by its `restart_if` watchdogs.
+    watchdog_restart: Option<String>,
+    /// Watches the `watch.paths` of the process, see `check_watch`. The `watch` config it
+    /// could not be set up for, not retried until the config changes.
+    watcher: Option<Result<watch::FileWatcher, Watch>>,
     state: ProcessState,
     desired_states: VecDeque<ProcessState>,
 }
@@ -44,9 +144,39 @@ impl Process {
             id: None,
             name: proc_name.to_string(),
             child: None,
+            killed: Vec::new(),
             conf,
             healthcheck: HealthCheckRunner::from_healthcheck_config(&healthcheck),
             runtime_failures: 0,
+            manual_override: None,
+            exit_code: None,
+            tracked: BTreeSet::new(),
+            tracked_at: None,
+            members_checked: None,
+            sockets: Vec::new(),
+            replacement: None,
+            retiring: Vec::new(),
+            restart_failure: None,
+            healthy_since: None,
+            recent_failures: VecDeque::new(),
+            fatal_after_stop: None,
+            stop_step: None,
+            stop_sequence: Vec::new(),
+            hook: None,
+            next_run: None,
+            last_scheduled_run: None,
+            run_queued: false,
+            history: VecDeque::new(),
+            starts: 0,
+            started_at: None,
+            last_exit: None,
+            current_run: None,
+            next_trigger: is_autostart.then_some(history::RunTrigger::Autostart),
+            resources: None,
+            sampled: None,
+            exceeded: watchdog::Exceeded::default(),
+            watchdog_restart: None,
+            watcher: None,
             state: ProcessState::Idle,
             desired_states: match is_autostart {
                 true => VecDeque::from([ProcessState::Ready]),
@@ -55,12 +185,23 @@ impl Process {
         }
     }
 
-    pub async fn monitor(&mut self) {
-        let new_state = match self.state.clone().monitor(self).await {
+    pub async fn monitor(&mut self, stats: &procfs::Snapshot) {
+        self.reap_killed();
+        self.monitor_replacement(stats).await;
+
+        if self.child.is_some() && self.conf.killmode() == KillMode::Tree && self.tracked_at.is_none_or(|at| at.elapsed() >= TRACK_INTERVAL) {
+            self.track_tree(stats.stats());
+        }
+
+        // `exited` may already have marked the process as `Stopped`, the hooks are run
+        // based on the state it was in before.
+        let old_state = self.state.clone();
+        let new_state = match old_state.clone().monitor(self, stats).await {
             Some(new_state) => new_state,
             None => return,
         };
-        self.state = new_state;
+        self.state = old_state;
+        self.set_state(new_state);
     }
 
     pub fn desire(&mut self) {
@@ -68,7 +209,104 @@ impl Process {
             Some(new_state) => new_state,
             None => return,
         };
-        self.state = new_state; // desired state
+        self.set_state(new_state); // desired state
+    }
+
+    fn set_state(&mut self, state: ProcessState) {
+        use ProcessState::*;
+        let state = match (&self.state, state) {
+            (Stopping(_), state @ Stopping(_)) => state,
+            (Stopping(_), state) => self.fatal_after_stop.take().map(Fatal).unwrap_or(state),
+            (_, state) => state,
+        };
+        match (&self.state, &state) {
+            (Healthy, Healthy) => {}
+            (_, Healthy) => {
+                let _ = self.run_hook(HookKind::PostStart);
+            }
+            (HealthCheck(_) | Healthy | Stopping(_), Stopped | Completed) | (Stopping(_), Fatal(_)) => {
+                let _ = self.run_hook(HookKind::PostStop);
+            }
+            _ => {}
+        }
+        self.hook = self
+            .hook
+            .take()
+            .filter(|(kind, _)| *kind == HookKind::PreStop && matches!(state, Stopping(_)));
+
+        self.healthy_since = match state {
+            ProcessState::Healthy => self.healthy_since.or_else(|| Some(Instant::now())),
+            _ => None,
+        };
+        if !matches!(state, ProcessState::Stopping(_)) {
+            self.stop_step = None;
+            self.stop_sequence.clear();
+            self.members_checked = None;
+        }
+        self.state = state;
+    }
+
+    /// Clears the failure counters once the process stayed `Healthy` for
+    /// `backoff.reset_after`, so the next failure starts over from the initial backoff.
+    pub fn reset_failures_once_stable(&mut self) {
+        let healthy_since = *self.healthy_since.get_or_insert_with(Instant::now);
+        if healthy_since.elapsed() < self.healthcheck.backoff().reset_after().get() {
+            return;
+        }
+        if self.runtime_failures > 0 || self.healthcheck.failures() > 0 {
+            proc_info!(self, "stable, resetting failure counters",; runtime_failures = self.runtime_failures, healthcheck_failures = self.healthcheck.failures());
+            self.runtime_failures = 0;
+            self.healthcheck.clear_failures();
+        }
+    }
+
+    /// Records a failure, returning why the process should be given up on if it failed
+    /// more often than its `startlimit` allows.
+    pub fn record_failure(&mut self) -> Option<String> {
+        let startlimit = self.conf.startlimit()?;
+        let now = Instant::now();
+        self.recent_failures.push_back(now);
+        while self
+            .recent_failures
+            .front()
+            .is_some_and(|failed_at| now.duration_since(*failed_at) > startlimit.interval().get())
+        {
+            self.recent_failures.pop_front();
+        }
+
+        if self.recent_failures.len() > startlimit.burst() as usize {
+            return Some(format!("failed {} time(s) within {}", self.recent_failures.len(), startlimit.interval()));
+        }
+        None
+    }
+
+    /// Gives up on the process after it failed more often than its `startlimit`
+    /// allows. A process which is still running (e.g. failing its command healthcheck)
+    /// is stopped through its stop sequence first, and only becomes `Fatal` once it
+    /// exited.
+    pub fn give_up(&mut self, reason: String) -> ProcessState {
+        if self.kill_gracefully().is_err() {
+            return ProcessState::Fatal(reason);
+        }
+        self.fatal_after_stop = Some(reason);
+        ProcessState::Stopping(Instant::now())
+    }
+
+    /// Clears the `Fatal` state along with everything counting towards it, leaving the
+    /// process `Stopped`. Returns whether the process was `Fatal`, or being stopped to
+    /// become `Fatal`.
+    pub fn reset_failed(&mut self) -> bool {
+        let stopping = self.fatal_after_stop.take().is_some();
+        if !stopping && !matches!(self.state, ProcessState::Fatal(_)) {
+            return false;
+        }
+        self.recent_failures.clear();
+        self.runtime_failures = 0;
+        self.healthcheck.clear_failures();
+        if !stopping {
+            self.set_state(ProcessState::Stopped);
+        }
+        true
     }
 }
 
@@ -86,10 +324,23 @@ impl Process {
         self.id
     }
 
+    /// PID of the running process. Unlike `id`, `None` once it exited.
+    pub fn pid(&self) -> Option<u32> {
+        self.child.as_ref().and(self.id)
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// Renames a running process, see `Daemon::scale`.
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_owned();
+        if let Some(replacement) = self.replacement.as_mut() {
+            replacement.set_name(name);
+        }
+    }
+
     pub fn config(&self) -> &ProcessConfig {
         &self.conf
     }
@@ -98,6 +349,19 @@ impl Process {
         &mut self.conf
     }
 
+    /// Replaces the configuration, including the settings the healthcheck runner
+    /// copied from it. Does not touch the running child.
+    pub fn set_config(&mut self, conf: ProcessConfig) {
+        self.healthcheck
+            .set_healthcheck(conf.healthcheck().healthcheck().clone())
+            .set_backoff(conf.healthcheck().backoff().clone())
+            .set_retries(conf.healthcheck().retries());
+        if conf.schedule() != self.conf.schedule() {
+            self.next_run = None;
+        }
+        self.conf = conf;
+    }
+
     pub fn desired_states(&self) -> &VecDeque<ProcessState> {
         &self.desired_states
     }
@@ -110,6 +374,60 @@ impl Process {
         self.runtime_failures
     }
 
+    pub fn sockets(&self) -> &[(String, Arc<ListeningSocket>)] {
+        &self.sockets
+    }
+
+    pub fn set_sockets(&mut self, sockets: Vec<(String, Arc<ListeningSocket>)>) {
+        self.sockets = sockets;
+    }
+
+    /// Whether the sockets held do not match the configured ones, e.g. because they
+    /// were never bound or the configuration changed.
+    pub fn sockets_outdated(&self) -> bool {
+        self.sockets.len() != self.conf.sockets().len()
+            || self
+                .sockets
+                .iter()
+                .zip(self.conf.sockets())
+                .any(|((name, socket), (conf_name, conf))| name != conf_name || socket.config() != conf)
+    }
+
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    pub fn last_exit(&self) -> Option<history::RunOutcome> {
+        self.last_exit
+    }
+
+    pub fn started_at(&self) -> Option<SystemTime> {
+        self.started_at
+    }
+
+    /// How long the process has been running, `None` if it is not.
+    pub fn uptime(&self) -> Option<Duration> {
+        self.child.as_ref()?;
+        self.started_at?.elapsed().ok()
+    }
+
+    /// How often the process was spawned again after its first start.
+    pub fn restarts(&self) -> u64 {
+        self.starts.saturating_sub(1)
+    }
+
+    pub fn manual_override(&self) -> Option<ManualOverride> {
+        self.manual_override
+    }
+
+    pub fn set_manual_override(&mut self, manual_override: Option<ManualOverride>) {
+        self.manual_override = manual_override;
+    }
+
+    pub fn set_runtime_failures(&mut self, runtime_failures: usize) {
+        self.runtime_failures = runtime_failures;
+    }
+
     pub fn clear_runtime_failures(&mut self) {
         self.runtime_failures = 0;
     }
@@ -143,8 +461,9 @@ impl Process {
         &mut self.healthcheck
     }
 
-    pub fn retry_at(&self) -> time::Instant {
-        Instant::now() + Duration::from_secs(self.healthcheck().backoff() as u64)
+    /// When to retry after `failures` consecutive failures, see [`crate::conf::proc::types::Backoff`].
+    pub fn retry_at(&self, failures: usize) -> time::Instant {
+        Instant::now() + self.healthcheck().backoff().jittered_delay(failures)
     }
 
     pub fn start_healthcheck(&mut self) {
@@ -152,7 +471,7 @@ impl Process {
     }
 
     pub fn passed_starttime(&self, started_at: time::Instant) -> bool {
-        Instant::now().duration_since(started_at).as_secs() >= self.healthcheck.starttime() as u64
+        Instant::now().duration_since(started_at) >= self.healthcheck.starttime()
     }
 
     fn get_group_id(group_name: &str) -> Result<u32, String> {
@@ -196,6 +515,15 @@ impl Process {
     }
 
     async fn spawn(&self) -> Result<Child, Box<dyn Error + Send + Sync>> {
+        if self.conf.killmode() == KillMode::Tree {
+            // Orphaned descendants get reparented to taskmaster instead of init, so
+            // they can still be found and signaled. The daemon's `Reaper` reaps them
+            // once they exited.
+            unsafe {
+                libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0);
+            }
+        }
+
         let stdout = Self::create_or_null(self.conf.stdout())?;
         let stderr = Self::create_or_null(self.conf.stderr())?;
 
@@ -209,6 +537,32 @@ impl Process {
             None => None,
         };
 
+        let listen_fds = self.sockets.iter().map(|(_, socket)| socket.fd()).collect::<Vec<i32>>();
+        let mut scratch = listen_fds.clone();
+        let mut listen_exec = match listen_fds.is_empty() {
+            true => None,
+            false => {
+                let mut env = std::env::vars_os().collect::<BTreeMap<OsString, OsString>>();
+                env.extend(
+                    self.conf
+                        .env()
+                        .iter()
+                        .map(|(key, value)| (OsString::from(key), OsString::from(value))),
+                );
+                env.insert("LISTEN_FDS".into(), listen_fds.len().to_string().into());
+                env.insert(
+                    "LISTEN_FDNAMES".into(),
+                    self.sockets
+                        .iter()
+                        .map(|(name, _)| name.as_str())
+                        .collect::<Vec<&str>>()
+                        .join(":")
+                        .into(),
+                );
+                Some(ListenExec::new(&cmd_path, &args, env)?)
+            }
+        };
+
         let mut child = unsafe {
             Command::new(cmd_path)
                 .args(args)
@@ -217,9 +571,19 @@ impl Process {
                 .stdout(stdout)
                 .stderr(stderr)
                 .pre_exec(move || {
+                    // Own process group, so the whole group can be signaled on stop.
+                    if libc::setpgid(0, 0) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    if !listen_fds.is_empty() {
+                        sockets::pass_fds(&listen_fds, &mut scratch)?;
+                    }
                     Process::deescalate_privileges(uid)?;
                     umask(umask_val);
-                    Ok(())
+                    match listen_exec.as_mut() {
+                        Some(listen_exec) => Err(listen_exec.exec()),
+                        None => Ok(()),
+                    }
                 })
                 .current_dir(working_dir)
                 .spawn()
@@ -236,19 +600,166 @@ impl Process {
 
         assert_ne!(self.state(), ProcessState::Healthy);
 
+        if self.sockets_outdated() {
+            return Err(ProcessError::CouldNotSpawn("listening sockets are not bound".to_string()));
+        }
+
+        match self.hook.take() {
+            Some((HookKind::PreStart, mut hook)) => match hook.result() {
+                None => {
+                    self.hook = Some((HookKind::PreStart, hook));
+                    return Err(ProcessError::HookRunning);
+                }
+                Some(Err(e)) => return Err(ProcessError::HookFailed(e)),
+                Some(Ok(())) => {}
+            },
+            _ => match self.run_hook(HookKind::PreStart) {
+                None => {}
+                Some(Ok(hook)) => {
+                    self.hook = Some((HookKind::PreStart, hook));
+                    return Err(ProcessError::HookRunning);
+                }
+                Some(Err(e)) => return Err(ProcessError::HookFailed(e)),
+            },
+        }
+
         self.child = match self.spawn().await {
             Ok(child) => Some(child),
-            Err(e) => return Err(ProcessError::CouldNotSpawn(e.to_string())),
+            Err(e) => {
+                self.exit_code = Some(127);
+                return Err(ProcessError::CouldNotSpawn(e.to_string()));
+            }
         };
 
         self.id = Some(self.child.as_ref().unwrap().id());
+        self.exit_code = None;
+        self.begin_run();
+        self.tracked.clear();
+        self.tracked_at = None;
 
         Ok(())
     }
 
+    /// Refreshes the descendants tracked for `killmode = "tree"`. Tracked processes
+    /// which were orphaned (and reparented to taskmaster) are kept, dead ones are
+    /// reaped and forgotten.
+    pub fn track_tree(&mut self, stats: &[procfs::Stat]) {
+        let Some(pid) = self.id else {
+            return;
+        };
+
+        let daemon = std::process::id();
+        let mut tracked = procfs::descendants(stats, pid);
+        tracked.extend(procfs::group_members(stats, pid));
+
+        for tracked_pid in self.tracked.iter() {
+            match stats.iter().find(|s| s.pid == *tracked_pid) {
+                Some(s) if s.ppid == daemon && s.is_zombie() => unsafe {
+                    libc::waitpid(s.pid as i32, std::ptr::null_mut(), libc::WNOHANG);
+                },
+                Some(s) if s.ppid == daemon && !s.is_zombie() => {
+                    tracked.insert(s.pid);
+                }
+                _ => {}
+            }
+        }
+
+        tracked.remove(&pid);
+        self.tracked = tracked;
+        self.tracked_at = Some(Instant::now());
+    }
+
+    /// Returns the live processes belonging to this process according to its
+    /// `killmode`, including the process itself.
+    pub fn members(&self) -> BTreeSet<u32> {
+        self.members_in(&procfs::all())
+    }
+
+    /// [`Process::members`], looked up in `stats`.
+    fn members_in(&self, stats: &[procfs::Stat]) -> BTreeSet<u32> {
+        let Some(pid) = self.id else {
+            return BTreeSet::new();
+        };
+
+        let mut members = match self.conf.killmode() {
+            KillMode::Process => BTreeSet::new(),
+            KillMode::Group => procfs::group_members(stats, pid),
+            KillMode::Tree => {
+                let mut members = procfs::group_members(stats, pid);
+                members.extend(procfs::descendants(stats, pid));
+                members.extend(
+                    self.tracked
+                        .iter()
+                        .filter(|p| stats.iter().any(|s| s.pid == **p && !s.is_zombie())),
+                );
+                members
+            }
+        };
+
+        if stats.iter().any(|s| s.pid == pid && !s.is_zombie()) {
+            members.insert(pid);
+        }
+        members
+    }
+
+    /// Returns whether any member other than the process itself is still alive. Called
+    /// on every tick while `Stopping`, the members are only looked up once per
+    /// `MEMBERS_INTERVAL`.
+    pub fn has_running_members(&mut self, stats: &procfs::Snapshot) -> bool {
+        let Some(pid) = self.id else {
+            return false;
+        };
+        if let Some((checked_at, running)) = self.members_checked
+            && checked_at.elapsed() < MEMBERS_INTERVAL
+        {
+            return running;
+        }
+
+        let running = self.members_in(stats.stats()).iter().any(|p| *p != pid);
+        self.members_checked = Some((Instant::now(), running));
+        running
+    }
+
+    /// Whether the process itself exited, without reaping it.
+    pub fn leader_exited(&self) -> bool {
+        let Some(pid) = self.id else {
+            return true;
+        };
+
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        let ret = unsafe { libc::waitid(libc::P_PID, pid, &mut info, libc::WEXITED | libc::WNOHANG | libc::WNOWAIT) };
+        ret != 0 || unsafe { info.si_pid() } != 0
+    }
+
+    fn signal_members(&self, sig: i32) {
+        let Some(pid) = self.id else {
+            return;
+        };
+
+        unsafe {
+            match self.conf.killmode() {
+                KillMode::Process => {
+                    libc::kill(pid as i32, sig);
+                }
+                KillMode::Group => {
+                    libc::kill(-(pid as i32), sig);
+                }
+                KillMode::Tree => {
+                    libc::kill(-(pid as i32), sig);
+                    for member in self.members() {
+                        libc::kill(member as i32, sig);
+                    }
+                }
+            }
+        }
+    }
+
     fn check_signal(&mut self, status: ExitStatus, pid: u32) -> Option<i32> {
         if let Some(signal) = status.signal() {
             proc_info!(self, "terminated",; pid = pid, signal = signal);
+            if !matches!(self.state, ProcessState::Stopping(_)) {
+                self.exit_code = Some(128 + signal);
+            }
         } else if self.state != ProcessState::Stopped {
             proc_info!(self, "terminated without exit or signal information",; pid = pid)
         }
@@ -266,10 +777,13 @@ impl Process {
         match self.child.as_mut().unwrap().try_wait() {
             Ok(Some(status)) => match status.code() {
                 Some(code) => {
+                    self.end_run(status);
                     self.child = None;
+                    self.exit_code = Some(code);
                     Ok(code)
                 }
                 None => {
+                    self.end_run(status);
                     self.child = None;
                     self.check_signal(status, pid);
                     Err(ProcessError::NoExitInformation)
@@ -290,21 +804,72 @@ impl Process {
             _ => return Err("process not running"),
         }
 
-        let child = match &self.child {
-            Some(c) => c,
+        let pid = match &self.child {
+            Some(c) => c.id(),
             None => return Err("child is None"),
         };
 
-        for sig in self.config().stopsignals() {
-            unsafe {
-                libc::kill(child.id() as i32, sig.signal());
-            }
+        if self.conf.killmode() == KillMode::Tree {
+            self.track_tree(&procfs::all());
+        }
+
+        proc_info!(self, "attempting graceful shutdown",; pid = pid, killmode = self.config().killmode());
+        self.stop_sequence = self.conf.stop_sequence();
+        match self.run_hook(HookKind::PreStop) {
+            Some(Ok(hook)) => self.hook = Some((HookKind::PreStop, hook)),
+            _ => self.run_stop_step(0),
         }
-        proc_info!(self, "attempting graceful shutdown",; pid = child.id(), signals = self.config().stopsignals());
 
         Ok(())
     }
 
+    /// Moves on to the next step of the stop sequence once the current one's `wait`
+    /// passed, killing the process forcefully after the last one. Returns whether it was
+    /// killed.
+    ///
+    /// The sequence only starts once the `pre_stop` hook exited, whether it succeeded or
+    /// not.
+    pub fn escalate_stop(&mut self) -> bool {
+        if let Some((HookKind::PreStop, hook)) = self.hook.as_mut() {
+            if hook.result().is_none() {
+                return false;
+            }
+            self.hook = None;
+            self.run_stop_step(0);
+        }
+
+        while let Some((step, started_at)) = self.stop_step {
+            let wait = self.stop_sequence.get(step).and_then(StopStep::wait).unwrap_or_default();
+            if started_at.elapsed() < wait.get() {
+                return false;
+            }
+            if step + 1 >= self.stop_sequence.len() {
+                proc_info!(self, "still running after the stop sequence, killing forcefully");
+                let _ = self.kill_forcefully();
+                return true;
+            }
+            self.run_stop_step(step + 1);
+        }
+        false
+    }
+
+    fn run_stop_step(&mut self, step: usize) {
+        let steps = self.stop_sequence.len();
+        let Some(current) = self.stop_sequence.get(step).copied() else {
+            return;
+        };
+
+        self.stop_step = Some((step, Instant::now()));
+        let wait = current.wait().unwrap_or_default();
+        match current.signal() {
+            Some(signal) => {
+                self.signal_members(signal.signal());
+                proc_info!(self, "stop sequence: sent signal",; step = step + 1, steps = steps, signal = signal, wait = wait.to_string());
+            }
+            None => proc_info!(self, "stop sequence: waiting",; step = step + 1, steps = steps, wait = wait.to_string()),
+        }
+    }
+
     pub fn kill_forcefully(&mut self) -> Result<(), &str> {
         use ProcessState::*;
         match self.state() {
@@ -312,15 +877,40 @@ impl Process {
             _ => return Err("process not running or in stopping state"),
         }
 
+        self.kill_replacements();
+
+        // Before taking the child, the members are looked up through its PID.
+        self.signal_members(libc::SIGKILL);
         let mut child = match self.child.take() {
             Some(c) => c,
             None => return Err("child is None"),
         };
 
-        child.kill();
+        let _ = child.kill();
         proc_info!(self, "killed, PID {}", child.id());
         self.id.take();
 
+        // `SIGKILL` cannot be caught, which is how the run ended. Waiting for the child
+        // to exit could block the daemon for as long as it is stuck in the kernel, it is
+        // reaped on a later tick instead.
+        match child.try_wait() {
+            Ok(Some(status)) => self.end_run(status),
+            _ => {
+                self.end_run(ExitStatus::from_raw(libc::SIGKILL));
+                self.killed.push(child);
+            }
+        }
+
         Ok(())
     }
+
+    /// Reaps the children killed by `kill_forcefully` which exited since.
+    fn reap_killed(&mut self) {
+        self.killed.retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+    }
+
+    /// Whether children killed by `kill_forcefully` still have to be reaped.
+    pub fn has_unreaped_children(&self) -> bool {
+        !self.killed.is_empty()
+    }
 }
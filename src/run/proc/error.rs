@@ -6,6 +6,8 @@ pub enum ProcessError {
     AlreadyRunning,
     NoChildProcess,
     NoExitInformation,
+    HookRunning,
+    HookFailed(String),
 }
 
 impl fmt::Display for ProcessError {
@@ -16,6 +18,8 @@ impl fmt::Display for ProcessError {
             ProcessError::AlreadyRunning => write!(f, "process is already running"),
             ProcessError::NoChildProcess => write!(f, "no child process running"),
             ProcessError::NoExitInformation => write!(f, "no exit information available"),
+            ProcessError::HookRunning => write!(f, "pre_start hook is still running"),
+            ProcessError::HookFailed(msg) => write!(f, "pre_start hook failed: {msg}"),
         }
     }
 }
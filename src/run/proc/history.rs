@@ -0,0 +1,210 @@
+use std::{
+    fmt,
+    os::unix::process::ExitStatusExt,
+    process::ExitStatus,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::Process;
+use crate::{conf::proc::types::ProcessType, run::statemachine::states::ProcessState};
+
+/// How many runs of a `oneshot` process are kept.
+const HISTORY_SIZE: usize = 20;
+
+/// What started a run.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunTrigger {
+    Autostart,
+    /// `start`, `restart` or `run`.
+    Manual,
+    Schedule,
+    /// Started again by taskmaster, e.g. by `autorestart` or after a reload.
+    Restart,
+}
+
+impl fmt::Display for RunTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunTrigger::Autostart => write!(f, "autostart"),
+            RunTrigger::Manual => write!(f, "manual"),
+            RunTrigger::Schedule => write!(f, "schedule"),
+            RunTrigger::Restart => write!(f, "restart"),
+        }
+    }
+}
+
+/// How a run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunOutcome {
+    Exited(i32),
+    Signaled(i32),
+}
+
+impl RunOutcome {
+    fn from_status(status: ExitStatus) -> Self {
+        match status.code() {
+            Some(code) => Self::Exited(code),
+            None => Self::Signaled(status.signal().unwrap_or_default()),
+        }
+    }
+
+    /// Exit code of the run, `128 + signal` if it was killed.
+    pub fn code(&self) -> i32 {
+        match self {
+            RunOutcome::Exited(code) => *code,
+            RunOutcome::Signaled(signal) => 128 + signal,
+        }
+    }
+}
+
+impl fmt::Display for RunOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunOutcome::Exited(code) => write!(f, "exited with code {code}"),
+            RunOutcome::Signaled(signal) => write!(f, "killed by signal {signal}"),
+        }
+    }
+}
+
+/// A finished run of a `oneshot` process.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Run {
+    /// Counts the runs of the process since taskmaster started, starting at 1.
+    id: u64,
+    trigger: RunTrigger,
+    /// Unix timestamps.
+    started_at: u64,
+    ended_at: u64,
+    duration_ms: u64,
+    outcome: RunOutcome,
+    /// Whether it exited with one of the process' `exitcodes`.
+    success: bool,
+}
+
+impl Run {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn trigger(&self) -> RunTrigger {
+        self.trigger
+    }
+
+    pub fn started_at(&self) -> u64 {
+        self.started_at
+    }
+
+    pub fn ended_at(&self) -> u64 {
+        self.ended_at
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        self.duration_ms
+    }
+
+    pub fn outcome(&self) -> RunOutcome {
+        self.outcome
+    }
+
+    pub fn success(&self) -> bool {
+        self.success
+    }
+}
+
+impl fmt::Display for Run {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} ({}): {}, {} after {:.1}s",
+            self.id,
+            self.trigger,
+            if self.success { "succeeded" } else { "failed" },
+            self.outcome,
+            self.duration_ms as f64 / 1000.0
+        )
+    }
+}
+
+/// The run of a `oneshot` process in progress.
+#[derive(Debug)]
+pub(super) struct StartedRun {
+    id: u64,
+    trigger: RunTrigger,
+    started_at: SystemTime,
+    since: Instant,
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+/// Run history of `oneshot` processes.
+impl Process {
+    /// Has the process started with `trigger` the next time it is spawned, returning the
+    /// id that run is going to get.
+    pub fn request_run(&mut self, trigger: RunTrigger) -> u64 {
+        self.next_trigger = Some(trigger);
+        self.push_desired_state(ProcessState::Ready);
+        self.starts + 1
+    }
+
+    /// Sets what the next run is started by, without starting it.
+    pub fn set_next_trigger(&mut self, trigger: RunTrigger) {
+        self.next_trigger = Some(trigger);
+    }
+
+    /// Whether the process is running or about to, including retries after a failure.
+    pub fn run_in_progress(&self) -> bool {
+        use ProcessState::*;
+        matches!(self.state, Ready | HealthCheck(_) | Healthy | Failed(_) | WaitingForRetry(_) | Stopping(_)) || !self.desired_states.is_empty()
+    }
+
+    pub fn history(&self) -> impl Iterator<Item = &Run> {
+        self.history.iter()
+    }
+
+    /// Called once the process was spawned.
+    pub(super) fn begin_run(&mut self) {
+        self.starts += 1;
+        self.started_at = Some(SystemTime::now());
+
+        let trigger = self.next_trigger.take().unwrap_or(RunTrigger::Restart);
+        if self.conf.process_type() != ProcessType::Oneshot {
+            return;
+        }
+
+        self.current_run = Some(StartedRun {
+            id: self.starts,
+            trigger,
+            started_at: SystemTime::now(),
+            since: Instant::now(),
+        });
+    }
+
+    /// Called once the process was reaped.
+    pub(super) fn end_run(&mut self, status: ExitStatus) {
+        let outcome = RunOutcome::from_status(status);
+        self.last_exit = Some(outcome);
+
+        let Some(started) = self.current_run.take() else {
+            return;
+        };
+
+        self.history.push_back(Run {
+            id: started.id,
+            trigger: started.trigger,
+            started_at: unix_secs(started.started_at),
+            ended_at: unix_secs(SystemTime::now()),
+            duration_ms: started.since.elapsed().as_millis() as u64,
+            outcome,
+            success: matches!(outcome, RunOutcome::Exited(code) if self.conf.exitcodes().contains(&code)),
+        });
+        while self.history.len() > HISTORY_SIZE {
+            self.history.pop_front();
+        }
+    }
+}
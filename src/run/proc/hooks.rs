@@ -0,0 +1,129 @@
+use std::{os::unix::process::ExitStatusExt, process::Stdio};
+
+use tokio::sync::oneshot::{self, error::TryRecvError};
+
+use crate::{
+    conf::proc::types::{Hook, HookKind},
+    log_info, log_warn,
+};
+
+use super::Process;
+
+/// # `RunningHook`
+/// `src/run/proc/hooks.rs`
+///
+/// A hook command running in the background. Its outcome is logged once it exited,
+/// whether or not anyone waits for it.
+#[derive(Debug)]
+pub struct RunningHook {
+    receiver: oneshot::Receiver<Result<(), String>>,
+}
+
+impl RunningHook {
+    /// Spawns `hook` from `workingdir`, with `vars` added to its environment.
+    fn spawn(process: &str, kind: HookKind, hook: &Hook, workingdir: &str, vars: Vec<(String, String)>) -> Result<Self, String> {
+        let uid = match hook.user().map(Process::get_group_id) {
+            Some(Ok(uid)) => Some(uid),
+            Some(Err(e)) => return Err(format!("could not get uid:gid: {e}")),
+            None => None,
+        };
+
+        let mut command = tokio::process::Command::new(hook.cmd().path());
+        command
+            .args(hook.args())
+            .envs(hook.env().iter().cloned())
+            .envs(vars)
+            .current_dir(workingdir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        unsafe {
+            command.pre_exec(move || Process::deescalate_privileges(uid));
+        }
+
+        let child = command
+            .spawn()
+            .map_err(|e| format!("could not spawn {}: {e}", hook.cmd().path()))?;
+        log_info!("running hook",; process = process, hook = kind.to_string(), cmd = hook.cmd().path());
+
+        let (sender, receiver) = oneshot::channel();
+        let process = process.to_owned();
+        let timeout = hook.timeout().get();
+        tokio::spawn(async move {
+            let result = match tokio::time::timeout(timeout, child.wait_with_output()).await {
+                Ok(Ok(output)) if output.status.success() => Ok(()),
+                Ok(Ok(output)) => Err(match output.status.code() {
+                    Some(code) => format!("exited with code {code}, stderr: {}", String::from_utf8_lossy(&output.stderr).trim()),
+                    None => format!("killed by signal {}", output.status.signal().unwrap_or_default()),
+                }),
+                Ok(Err(e)) => Err(e.to_string()),
+                Err(_) => Err(format!("timed out after {timeout:?}")),
+            };
+            match &result {
+                Ok(()) => log_info!("hook succeeded",; process = process, hook = kind.to_string()),
+                Err(reason) => log_warn!("hook failed",; process = process, hook = kind.to_string(), reason = reason),
+            }
+            let _ = sender.send(result);
+        });
+
+        Ok(Self { receiver })
+    }
+
+    /// `None` while the hook is still running.
+    pub fn result(&mut self) -> Option<Result<(), String>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Closed) => Some(Err("hook task ended unexpectedly".to_string())),
+        }
+    }
+}
+
+impl Process {
+    /// Whether the process is waiting for its `pre_start` hook before being spawned.
+    pub fn pre_start_running(&self) -> bool {
+        matches!(self.hook, Some((HookKind::PreStart, _)))
+    }
+
+    /// Starts the `kind` hook if one is configured.
+    pub fn run_hook(&self, kind: HookKind) -> Option<Result<RunningHook, String>> {
+        let hook = self.conf.hooks().get(kind)?;
+
+        let mut vars = vec![("TASKMASTER_HOOK".to_string(), kind.to_string()), ("TASKMASTER_PROCESS_NAME".to_string(), self.name.clone())];
+        if let Some(pid) = self.id {
+            vars.push(("TASKMASTER_PID".to_string(), pid.to_string()));
+        }
+        if let Some(code) = self.exit_code {
+            vars.push(("TASKMASTER_EXIT_CODE".to_string(), code.to_string()));
+        }
+
+        let result = RunningHook::spawn(&self.name, kind, hook, self.conf.workingdir().path(), vars);
+        if let Err(e) = &result {
+            log_warn!("could not run hook",; process = self.name(), hook = kind.to_string(), error = e);
+        }
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn outcome(mut hook: RunningHook) -> Result<(), String> {
+        loop {
+            if let Some(result) = hook.result() {
+                return result;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn failure_reason_contains_stderr() {
+        let hook = Hook::new("/bin/sh", vec!["-c".to_string(), "echo out; echo something broke >&2; exit 3".to_string()]);
+        let running = RunningHook::spawn("api", HookKind::PreStart, &hook, "/tmp", vec![]).unwrap();
+
+        assert_eq!(outcome(running).await, Err("exited with code 3, stderr: something broke".to_string()));
+    }
+}
@@ -0,0 +1,272 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    sync::OnceLock,
+};
+
+/// The fields of `/proc/<pid>/stat` taskmaster cares about.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stat {
+    pub pid: u32,
+    /// Single character state, `Z` for zombies.
+    pub state: char,
+    pub ppid: u32,
+    pub pgrp: u32,
+}
+
+impl Stat {
+    pub fn is_zombie(&self) -> bool {
+        self.state == 'Z' || self.state == 'X'
+    }
+}
+
+/// Parses `/proc/<pid>/stat`. Returns `None` if the process does not exist (anymore).
+pub fn stat(pid: u32) -> Option<Stat> {
+    parse_stat(pid, &fs::read_to_string(format!("/proc/{pid}/stat")).ok()?)
+}
+
+fn parse_stat(pid: u32, content: &str) -> Option<Stat> {
+    // The command name is in parentheses and may itself contain spaces or parentheses,
+    // everything after the last `)` is space separated.
+    let fields = content.get(content.rfind(')')? + 2..)?.split(' ').collect::<Vec<&str>>();
+
+    Some(Stat {
+        pid,
+        state: fields.first()?.chars().next()?,
+        ppid: fields.get(1)?.parse().ok()?,
+        pgrp: fields.get(2)?.parse().ok()?,
+    })
+}
+
+/// Resources used by a process, read from `/proc/<pid>/{stat,status,io,fd}`. Values
+/// which could not be read are left at `0`, e.g. `io` of processes owned by another
+/// user.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Usage {
+    /// CPU time spent in user and kernel mode, in clock ticks.
+    pub cpu_ticks: u64,
+    pub rss_bytes: u64,
+    pub threads: u64,
+    /// Bytes read from and written to storage.
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    pub fds: u64,
+}
+
+impl std::ops::AddAssign for Usage {
+    fn add_assign(&mut self, other: Self) {
+        self.cpu_ticks += other.cpu_ticks;
+        self.rss_bytes += other.rss_bytes;
+        self.threads += other.threads;
+        self.read_bytes += other.read_bytes;
+        self.write_bytes += other.write_bytes;
+        self.fds += other.fds;
+    }
+}
+
+/// Reads the resources used by `pid`. Returns `None` if the process does not exist
+/// (anymore).
+pub fn usage(pid: u32) -> Option<Usage> {
+    let cpu_ticks = parse_cpu_ticks(&fs::read_to_string(format!("/proc/{pid}/stat")).ok()?)?;
+    let (rss_bytes, threads) = fs::read_to_string(format!("/proc/{pid}/status"))
+        .ok()
+        .map(|status| parse_status(&status))
+        .unwrap_or_default();
+    let (read_bytes, write_bytes) = fs::read_to_string(format!("/proc/{pid}/io"))
+        .ok()
+        .map(|io| parse_io(&io))
+        .unwrap_or_default();
+    let fds = fs::read_dir(format!("/proc/{pid}/fd"))
+        .map(|fds| fds.count() as u64)
+        .unwrap_or_default();
+
+    Some(Usage {
+        cpu_ticks,
+        rss_bytes,
+        threads,
+        read_bytes,
+        write_bytes,
+        fds,
+    })
+}
+
+/// Clock ticks per second, the unit of [`Usage::cpu_ticks`].
+pub fn ticks_per_second() -> u64 {
+    match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+        ticks if ticks > 0 => ticks as u64,
+        _ => 100,
+    }
+}
+
+/// Sum of `utime` and `stime` of `/proc/<pid>/stat`.
+fn parse_cpu_ticks(content: &str) -> Option<u64> {
+    let fields = content.get(content.rfind(')')? + 2..)?.split(' ').collect::<Vec<&str>>();
+    let utime = fields.get(11)?.parse::<u64>().ok()?;
+    let stime = fields.get(12)?.parse::<u64>().ok()?;
+    Some(utime + stime)
+}
+
+/// `VmRSS` (in bytes) and `Threads` of `/proc/<pid>/status`.
+fn parse_status(content: &str) -> (u64, u64) {
+    let field = |name: &str| -> u64 {
+        content
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .and_then(|value| value.split_whitespace().next()?.parse().ok())
+            .unwrap_or_default()
+    };
+    (field("VmRSS") * 1024, field("Threads"))
+}
+
+/// `read_bytes` and `write_bytes` of `/proc/<pid>/io`.
+fn parse_io(content: &str) -> (u64, u64) {
+    let field = |name: &str| -> u64 {
+        content
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or_default()
+    };
+    (field("read_bytes"), field("write_bytes"))
+}
+
+/// Returns the stats of every process currently visible in `/proc`.
+pub fn all() -> Vec<Stat> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return vec![];
+    };
+
+    entries
+        .filter_map(|e| e.ok()?.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(stat)
+        .collect()
+}
+
+/// The stats of every process, read from `/proc` once on first use. Taken once per
+/// daemon tick and shared by all processes, so `/proc` is not scanned for each of them.
+#[derive(Debug, Default)]
+pub struct Snapshot {
+    stats: OnceLock<Vec<Stat>>,
+}
+
+impl Snapshot {
+    pub fn stats(&self) -> &[Stat] {
+        self.stats.get_or_init(all)
+    }
+}
+
+/// Returns the live (non-zombie) members of the process group `pgid`.
+pub fn group_members(stats: &[Stat], pgid: u32) -> BTreeSet<u32> {
+    stats
+        .iter()
+        .filter(|s| s.pgrp == pgid && !s.is_zombie())
+        .map(|s| s.pid)
+        .collect()
+}
+
+/// Returns the live (non-zombie) descendants of `pid`, not including `pid` itself.
+pub fn descendants(stats: &[Stat], pid: u32) -> BTreeSet<u32> {
+    let mut children: HashMap<u32, Vec<&Stat>> = HashMap::new();
+    for s in stats {
+        children.entry(s.ppid).or_default().push(s);
+    }
+
+    let mut found = BTreeSet::new();
+    let mut queue = vec![pid];
+    while let Some(parent) = queue.pop() {
+        for child in children.get(&parent).map(Vec::as_slice).unwrap_or_default() {
+            if found.insert(child.pid) {
+                queue.push(child.pid);
+            }
+        }
+    }
+
+    found.retain(|pid| stats.iter().any(|s| s.pid == *pid && !s.is_zombie()));
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_stat_with_spaces_in_comm() {
+        let stat = parse_stat(42, "42 (my (weird) cmd) S 1 42 42 0 -1 4194560").unwrap();
+        assert_eq!(
+            stat,
+            Stat {
+                pid: 42,
+                state: 'S',
+                ppid: 1,
+                pgrp: 42
+            }
+        );
+    }
+
+    #[test]
+    fn parse_usage() {
+        let stat = "19305 (cat) R 19299 19305 19299 0 -1 4194304 78 0 0 0 7 3 0 0 20 0 1 0 743113 2703360 305";
+        assert_eq!(parse_cpu_ticks(stat), Some(10));
+
+        let status = "Name:\tcat\nVmRSS:\t    1684 kB\nThreads:\t3\n";
+        assert_eq!(parse_status(status), (1684 * 1024, 3));
+
+        let io = "rchar: 3980\nwchar: 0\nread_bytes: 4096\nwrite_bytes: 8192\ncancelled_write_bytes: 0\n";
+        assert_eq!(parse_io(io), (4096, 8192));
+    }
+
+    #[test]
+    fn own_usage() {
+        let usage = usage(std::process::id()).unwrap();
+        assert!(usage.rss_bytes > 0);
+        assert!(usage.threads > 0);
+        assert!(usage.fds > 0);
+    }
+
+    #[test]
+    fn own_process_is_found() {
+        let me = std::process::id();
+        let stat = stat(me).unwrap();
+        assert_eq!(stat.ppid, std::os::unix::process::parent_id());
+        assert!(all().iter().any(|s| s.pid == me));
+    }
+
+    #[test]
+    fn descendants_and_group_members() {
+        let stats = vec![
+            Stat {
+                pid: 10,
+                state: 'S',
+                ppid: 1,
+                pgrp: 10,
+            },
+            Stat {
+                pid: 11,
+                state: 'S',
+                ppid: 10,
+                pgrp: 10,
+            },
+            Stat {
+                pid: 12,
+                state: 'S',
+                ppid: 11,
+                pgrp: 12,
+            },
+            Stat {
+                pid: 13,
+                state: 'Z',
+                ppid: 10,
+                pgrp: 10,
+            },
+            Stat {
+                pid: 20,
+                state: 'S',
+                ppid: 1,
+                pgrp: 20,
+            },
+        ];
+
+        assert_eq!(descendants(&stats, 10), BTreeSet::from([11, 12]));
+        assert_eq!(group_members(&stats, 10), BTreeSet::from([10, 11]));
+    }
+}
@@ -0,0 +1,148 @@
+use std::time::Instant;
+
+use super::{Process, procfs, watchdog};
+use crate::{conf::proc::types::RestartStrategy, proc_info, proc_warning, run::statemachine::states::ProcessState};
+
+/// `start-first` restarts, see [`crate::conf::proc::types::RestartStrategy`].
+impl Process {
+    /// Restarts the process according to its `restart_strategy`, returning the
+    /// strategy that was used. Processes which are not `Healthy` have nothing to keep
+    /// running and are always restarted `stop-first`.
+    pub fn restart(&mut self) -> Result<RestartStrategy, &'static str> {
+        self.watchdog_restart = None;
+        if self.conf.restart_strategy() == RestartStrategy::StartFirst && self.state == ProcessState::Healthy {
+            self.start_replacement()?;
+            return Ok(RestartStrategy::StartFirst);
+        }
+
+        self.restart_failure = None;
+        self.push_desired_state(ProcessState::Ready);
+        Ok(RestartStrategy::StopFirst)
+    }
+
+    /// Starts a new instance next to the running one. It goes through the regular
+    /// healthcheck and takes over once `Healthy`, see [`Process::monitor_replacement`].
+    pub fn start_replacement(&mut self) -> Result<(), &'static str> {
+        if self.state != ProcessState::Healthy {
+            return Err("only a healthy process can be replaced");
+        }
+        if self.replacement.is_some() {
+            return Err("a replacement is already starting");
+        }
+
+        let mut replacement = Process::from_process_config(self.conf.clone(), &self.name);
+        replacement.desired_states.clear();
+        replacement.push_desired_state(ProcessState::Ready);
+        replacement.sockets = self.sockets.clone();
+
+        self.replacement = Some(Box::new(replacement));
+        self.restart_failure = None;
+
+        Ok(())
+    }
+
+    pub fn replacement(&self) -> Option<&Process> {
+        self.replacement.as_deref()
+    }
+
+    pub fn retiring(&self) -> &[Process] {
+        &self.retiring
+    }
+
+    pub fn restart_failure(&self) -> Option<&str> {
+        self.restart_failure.as_deref()
+    }
+
+    /// Advances the replacement and the retiring instances by one step.
+    pub(super) async fn monitor_replacement(&mut self, stats: &procfs::Snapshot) {
+        for retiring in self.retiring.iter_mut() {
+            retiring.desire();
+            Box::pin(retiring.monitor(stats)).await;
+        }
+        let (stopping, stopped): (Vec<Process>, Vec<Process>) = std::mem::take(&mut self.retiring)
+            .into_iter()
+            .partition(|p| matches!(p.state, ProcessState::Stopping(_)));
+        self.retiring = stopping;
+        for mut instance in stopped {
+            self.killed.append(&mut instance.killed);
+        }
+
+        let Some(mut replacement) = self.replacement.take() else {
+            return;
+        };
+
+        // Anything else happening to this process (stop, restart, ...) cancels the
+        // replacement.
+        if self.state != ProcessState::Healthy || !self.desired_states.is_empty() {
+            proc_info!(self, "cancelling start-first restart");
+            self.retire(*replacement);
+            return;
+        }
+
+        replacement.desire();
+        Box::pin(replacement.monitor(stats)).await;
+
+        use ProcessState::*;
+        match replacement.state() {
+            Healthy => self.promote(*replacement),
+            // Gave up on its healthcheck, or exited.
+            Stopping(_) | Stopped | Completed | Idle => {
+                let reason = format!("replacement {}", replacement.state());
+                proc_warning!(self, "start-first restart failed, keeping the running instance",; reason = reason);
+                self.restart_failure = Some(reason);
+                self.retire(*replacement);
+            }
+            _ => self.replacement = Some(replacement),
+        }
+    }
+
+    /// Swaps the running instance with the healthy `replacement` and stops the old one.
+    fn promote(&mut self, mut replacement: Process) {
+        std::mem::swap(&mut self.child, &mut replacement.child);
+        std::mem::swap(&mut self.id, &mut replacement.id);
+        std::mem::swap(&mut self.healthcheck, &mut replacement.healthcheck);
+        std::mem::swap(&mut self.tracked, &mut replacement.tracked);
+        std::mem::swap(&mut self.tracked_at, &mut replacement.tracked_at);
+        std::mem::swap(&mut self.started_at, &mut replacement.started_at);
+        self.starts += replacement.starts;
+        self.exit_code = None;
+        // Sampled from the previous instance.
+        self.resources = None;
+        self.sampled = None;
+        self.exceeded = watchdog::Exceeded::default();
+
+        proc_info!(self, "replacement is healthy, stopping the previous instance",; pid = self.id, previous_pid = replacement.id);
+        self.retire(replacement);
+    }
+
+    /// Stops `instance` gracefully, it is dropped once it exited.
+    fn retire(&mut self, mut instance: Process) {
+        instance.desired_states.clear();
+        if instance.kill_gracefully().is_ok() {
+            instance.state = ProcessState::Stopping(Instant::now());
+        }
+
+        self.killed.append(&mut instance.killed);
+        match instance.state {
+            ProcessState::Stopping(_) => self.retiring.push(instance),
+            // E.g. waiting to retry its healthcheck, not worth waiting for.
+            _ if instance.child.is_some() => {
+                instance.signal_members(libc::SIGKILL);
+                if let Some(mut child) = instance.child.take() {
+                    let _ = child.kill();
+                    self.killed.push(child);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Kills the replacement and the retiring instances, without waiting for them.
+    pub(super) fn kill_replacements(&mut self) {
+        let replacement = self.replacement.take().map(|r| *r);
+        for mut instance in replacement.into_iter().chain(std::mem::take(&mut self.retiring)) {
+            let _ = instance.kill_forcefully();
+            self.killed.append(&mut instance.killed);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{Process, procfs};
+
+/// How often the resources used by running processes are sampled.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Resources used by a running process and its descendants, summed over all of them.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Resources {
+    /// Share of a single CPU used since the previous sample, above 100 if more than one
+    /// core is used.
+    cpu_percent: f64,
+    rss_bytes: u64,
+    threads: u64,
+    fds: u64,
+    /// Bytes read from and written to storage since the processes were started.
+    read_bytes: u64,
+    write_bytes: u64,
+    /// How many processes the values were summed over.
+    processes: usize,
+}
+
+impl Resources {
+    pub fn cpu_percent(&self) -> f64 {
+        self.cpu_percent
+    }
+
+    pub fn rss_bytes(&self) -> u64 {
+        self.rss_bytes
+    }
+
+    pub fn threads(&self) -> u64 {
+        self.threads
+    }
+
+    pub fn fds(&self) -> u64 {
+        self.fds
+    }
+
+    pub fn read_bytes(&self) -> u64 {
+        self.read_bytes
+    }
+
+    pub fn write_bytes(&self) -> u64 {
+        self.write_bytes
+    }
+
+    pub fn processes(&self) -> usize {
+        self.processes
+    }
+}
+
+/// Formats `bytes` with a binary unit, e.g. `1.5GiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if bytes < 1024 {
+        return format!("{bytes}B");
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1}{}", UNITS[unit])
+}
+
+impl fmt::Display for Resources {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cpu {:.1}%, rss {}, {} thread(s), {} fd(s), read {}, written {}",
+            self.cpu_percent,
+            format_bytes(self.rss_bytes),
+            self.threads,
+            self.fds,
+            format_bytes(self.read_bytes),
+            format_bytes(self.write_bytes)
+        )?;
+        if self.processes > 1 {
+            write!(f, " ({} processes)", self.processes)?;
+        }
+        Ok(())
+    }
+}
+
+/// Resource sampling, see [`Resources`].
+impl Process {
+    /// Reads the resources used by the process and its descendants from `/proc`, at most
+    /// once per `SAMPLE_INTERVAL`. Called on every tick, after the state of the process
+    /// was updated.
+    pub fn sample_resources(&mut self, stats: &procfs::Snapshot) {
+        let Some(pid) = self.pid() else {
+            self.resources = None;
+            self.sampled = None;
+            return;
+        };
+        // The first sample is taken once the process ran for `SAMPLE_INTERVAL`.
+        let previous = self.sampled.filter(|(sampled_pid, _, _)| *sampled_pid == pid);
+        let since_last_sample = match previous {
+            Some((_, at, _)) => at.elapsed(),
+            None => self.uptime().unwrap_or_default(),
+        };
+        if since_last_sample < SAMPLE_INTERVAL {
+            return;
+        }
+
+        let mut pids = procfs::descendants(stats.stats(), pid);
+        pids.insert(pid);
+        pids.extend(self.tracked.iter());
+
+        let mut usage = procfs::Usage::default();
+        let mut processes = 0;
+        for usage_of_pid in pids.into_iter().filter_map(procfs::usage) {
+            usage += usage_of_pid;
+            processes += 1;
+        }
+
+        let now = Instant::now();
+        let (ticks, elapsed) = match previous {
+            Some((_, at, ticks)) => (usage.cpu_ticks.saturating_sub(ticks), now.duration_since(at)),
+            None => (usage.cpu_ticks, self.uptime().unwrap_or_default()),
+        };
+        let cpu_percent = match elapsed.is_zero() {
+            true => 0.0,
+            false => ticks as f64 / procfs::ticks_per_second() as f64 / elapsed.as_secs_f64() * 100.0,
+        };
+
+        self.sampled = Some((pid, now, usage.cpu_ticks));
+        self.resources = Some(Resources {
+            cpu_percent,
+            rss_bytes: usage.rss_bytes,
+            threads: usage.threads,
+            fds: usage.fds,
+            read_bytes: usage.read_bytes,
+            write_bytes: usage.write_bytes,
+            processes,
+        });
+    }
+
+    /// Resources used as of the last sample, `None` if the process is not running.
+    pub fn resources(&self) -> Option<&Resources> {
+        self.pid().and(self.resources.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes() {
+        assert_eq!(format_bytes(0), "0B");
+        assert_eq!(format_bytes(1023), "1023B");
+        assert_eq!(format_bytes(1536), "1.5KiB");
+        assert_eq!(format_bytes(2 * 1024 * 1024 * 1024), "2.0GiB");
+    }
+}
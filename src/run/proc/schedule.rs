@@ -0,0 +1,136 @@
+use std::time::{Duration, SystemTime};
+
+use super::{ManualOverride, Process, history::RunTrigger};
+use crate::{conf::proc::types::OverlapPolicy, proc_info, proc_warning, run::statemachine::states::ProcessState};
+
+/// How late a scheduled run may be noticed before it counts as missed, see `catch_up`.
+const MISSED_AFTER: Duration = Duration::from_secs(60);
+
+/// Scheduled runs, see [`crate::conf::proc::types::Schedule`].
+impl Process {
+    /// Starts the run which is due at `now` (or was queued), if any. Called on every tick
+    /// of the daemon.
+    pub fn advance_schedule(&mut self, now: SystemTime) {
+        let Some(schedule) = self.conf.schedule().cloned() else {
+            self.next_run = None;
+            return;
+        };
+
+        if self.run_queued && !self.run_in_progress() {
+            self.run_queued = false;
+            proc_info!(self, "starting queued scheduled run");
+            self.start_scheduled_run(now);
+        }
+
+        let next_run = match self.next_run {
+            Some(next_run) => next_run,
+            None => {
+                // Runs missed while taskmaster was down are only due with `catch_up`.
+                let from = match (schedule.catch_up(), self.last_scheduled_run) {
+                    (true, Some(last)) => last,
+                    _ => now,
+                };
+                match schedule.next_after(from) {
+                    Some(next_run) => *self.next_run.insert(next_run),
+                    None => return,
+                }
+            }
+        };
+        if now < next_run {
+            return;
+        }
+
+        self.next_run = schedule.next_after(now);
+
+        let scheduled = schedule.format(next_run);
+        if now.duration_since(next_run).unwrap_or_default() > MISSED_AFTER && !schedule.catch_up() {
+            proc_warning!(self, "missed scheduled run, skipping",; scheduled = scheduled);
+            return;
+        }
+
+        if self.manual_override == Some(ManualOverride::Stopped) {
+            proc_info!(self, "stopped by an operator, skipping scheduled run",; scheduled = scheduled);
+            return;
+        }
+        if matches!(self.state, ProcessState::Fatal(_)) {
+            proc_warning!(self, "fatal, skipping scheduled run",; scheduled = scheduled);
+            return;
+        }
+        if !self.run_in_progress() {
+            proc_info!(self, "starting scheduled run",; scheduled = scheduled);
+            self.start_scheduled_run(now);
+            return;
+        }
+
+        match schedule.overlap() {
+            OverlapPolicy::Skip => proc_warning!(self, "previous run still in progress, skipping scheduled run",; scheduled = scheduled),
+            OverlapPolicy::Queue => {
+                proc_info!(self, "previous run still in progress, queueing scheduled run",; scheduled = scheduled);
+                self.run_queued = true;
+            }
+            OverlapPolicy::Replace => {
+                proc_info!(self, "previous run still in progress, replacing it",; scheduled = scheduled);
+                self.start_scheduled_run(now);
+            }
+        }
+    }
+
+    /// Only runs which were actually started count as done for `catch_up`, skipped
+    /// ones are made up for after a restart.
+    fn start_scheduled_run(&mut self, now: SystemTime) {
+        self.last_scheduled_run = Some(now);
+        self.request_run(RunTrigger::Schedule);
+    }
+
+    /// When the next scheduled run is due, `None` without a `schedule`.
+    pub fn next_run(&self) -> Option<SystemTime> {
+        self.next_run
+    }
+
+    /// When the last scheduled run was started. Persisted, so runs missed while the
+    /// daemon was down can be caught up on.
+    pub fn last_scheduled_run(&self) -> Option<SystemTime> {
+        self.last_scheduled_run
+    }
+
+    pub fn set_last_scheduled_run(&mut self, last_scheduled_run: Option<SystemTime>) {
+        self.last_scheduled_run = last_scheduled_run;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conf::proc::{
+        ProcessConfig,
+        types::{Schedule, TimeZone},
+    };
+
+    #[test]
+    fn skipped_overlap_is_caught_up_on() {
+        let mut schedule = Schedule::new("*/5 * * * *", TimeZone::utc(), OverlapPolicy::Skip);
+        let schedule = schedule.set_catch_up(true);
+        let mut proc = ProcessConfig::default();
+        let proc = proc.set_cmd("sleep").set_autostart(false).set_schedule(schedule.to_owned());
+        let mut process = Process::from_process_config(proc.to_owned(), "job");
+
+        process.advance_schedule(SystemTime::now());
+        let first_run = process.next_run().unwrap();
+        process.advance_schedule(first_run);
+        assert_eq!(process.last_scheduled_run(), Some(first_run));
+
+        // Still running the first one.
+        let second_run = process.next_run().unwrap();
+        process.advance_schedule(second_run);
+        assert_eq!(process.desired_states().len(), 1);
+        assert_eq!(process.last_scheduled_run(), Some(first_run));
+
+        // After a restart, the skipped run is made up for.
+        let mut restarted = Process::from_process_config(proc.to_owned(), "job");
+        restarted.set_last_scheduled_run(process.last_scheduled_run());
+        let now = second_run + Duration::from_secs(120);
+        restarted.advance_schedule(now);
+        assert_eq!(restarted.desired_states().front(), Some(&ProcessState::Ready));
+        assert_eq!(restarted.last_scheduled_run(), Some(now));
+    }
+}
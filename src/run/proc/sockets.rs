@@ -0,0 +1,182 @@
+use std::{
+    collections::BTreeMap,
+    ffi::{CString, OsString},
+    fs, io,
+    net::TcpListener,
+    os::{
+        fd::{AsRawFd, OwnedFd, RawFd},
+        unix::{ffi::OsStrExt, fs::PermissionsExt, net::UnixListener},
+    },
+};
+
+use crate::conf::proc::types::SocketConfig;
+
+/// First fd number passed to the process, `SD_LISTEN_FDS_START` in systemd's terms.
+pub const LISTEN_FDS_START: RawFd = 3;
+
+/// # `ListeningSocket`
+/// `src/run/proc/sockets.rs`
+///
+/// A socket bound and listening on behalf of a process. It is owned by taskmaster, the
+/// process only ever gets a copy of the fd, so it keeps accepting (queueing)
+/// connections while the process is down.
+#[derive(Debug)]
+pub struct ListeningSocket {
+    config: SocketConfig,
+    fd: OwnedFd,
+}
+
+impl ListeningSocket {
+    pub fn bind(config: &SocketConfig) -> Result<Self, io::Error> {
+        let (fd, backlog): (OwnedFd, u16) = match config {
+            SocketConfig::Tcp(tcp) => (TcpListener::bind(tcp.tcp)?.into(), tcp.backlog),
+            SocketConfig::Unix(unix) => {
+                if fs::metadata(&unix.unix).is_ok() {
+                    fs::remove_file(&unix.unix)?;
+                }
+                let listener = UnixListener::bind(&unix.unix)?;
+                fs::set_permissions(&unix.unix, fs::Permissions::from_mode(unix.mode.mode()))?;
+                (listener.into(), unix.backlog)
+            }
+        };
+
+        // The standard library listens with its own backlog, listening again only
+        // updates it.
+        if unsafe { libc::listen(fd.as_raw_fd(), backlog as i32) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self { config: config.clone(), fd })
+    }
+
+    pub fn config(&self) -> &SocketConfig {
+        &self.config
+    }
+
+    pub fn fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl Drop for ListeningSocket {
+    fn drop(&mut self) {
+        if let SocketConfig::Unix(unix) = &self.config {
+            let _ = fs::remove_file(&unix.unix);
+        }
+    }
+}
+
+/// Moves `fds` to `LISTEN_FDS_START..` in the child.
+///
+/// # Safety
+/// Must only be called in the child, after forking and before `exec`, as it replaces
+/// whatever is open at `LISTEN_FDS_START..`. `scratch` has to be at least as long as
+/// `fds`, so nothing is allocated.
+pub unsafe fn pass_fds(fds: &[RawFd], scratch: &mut [RawFd]) -> Result<(), io::Error> {
+    let first_free = LISTEN_FDS_START + fds.len() as RawFd;
+
+    unsafe {
+        // Duplicated out of the way first, a source fd could be in the target range.
+        for (i, fd) in fds.iter().enumerate() {
+            scratch[i] = libc::fcntl(*fd, libc::F_DUPFD_CLOEXEC, first_free);
+            if scratch[i] < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        // `dup2` clears `FD_CLOEXEC` on the new fd, so these survive the `exec`.
+        for (i, fd) in scratch[..fds.len()].iter().enumerate() {
+            if libc::dup2(*fd, LISTEN_FDS_START + i as RawFd) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// # `ListenExec`
+/// `src/run/proc/sockets.rs`
+///
+/// `LISTEN_PID` has to be the PID of the process, which is only known after forking,
+/// when the environment `Command` passes to `exec` is already prepared. Processes with
+/// sockets are therefore `exec`ed from `pre_exec` instead, with everything prepared
+/// beforehand so the child does not have to allocate.
+pub struct ListenExec {
+    program: CString,
+    /// NUL terminated arguments, starting with the program.
+    argv: Vec<Vec<u8>>,
+    /// NUL terminated `KEY=value` pairs, the last one being `LISTEN_PID`.
+    envp: Vec<Vec<u8>>,
+    /// Empty, with room for the pointers to `argv` and `envp` (and their terminating
+    /// null pointers).
+    ptrs: Vec<*const libc::c_char>,
+}
+
+// The raw pointers are only ever written and read in the forked child.
+unsafe impl Send for ListenExec {}
+unsafe impl Sync for ListenExec {}
+
+impl ListenExec {
+    pub fn new(program: &str, args: &[String], env: BTreeMap<OsString, OsString>) -> Result<Self, io::Error> {
+        let nul_terminated = |bytes: &[u8]| -> Result<Vec<u8>, io::Error> {
+            if bytes.contains(&0) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "nul byte in argument or environment"));
+            }
+            Ok([bytes, &[0]].concat())
+        };
+
+        let argv = std::iter::once(program)
+            .chain(args.iter().map(String::as_str))
+            .map(|arg| nul_terminated(arg.as_bytes()))
+            .collect::<Result<Vec<Vec<u8>>, io::Error>>()?;
+        let mut envp = env
+            .iter()
+            .filter(|(key, _)| key.as_bytes() != b"LISTEN_PID")
+            .map(|(key, value)| nul_terminated(&[key.as_bytes(), b"=", value.as_bytes()].concat()))
+            .collect::<Result<Vec<Vec<u8>>, io::Error>>()?;
+        // Room for any PID, filled in by `exec`.
+        envp.push(b"LISTEN_PID=\0\0\0\0\0\0\0\0\0\0\0".to_vec());
+
+        Ok(Self {
+            program: CString::new(program)?,
+            ptrs: Vec::with_capacity(argv.len() + envp.len() + 2),
+            argv,
+            envp,
+        })
+    }
+
+    /// Replaces the current process, only returns on failure.
+    ///
+    /// # Safety
+    /// Must only be called in the child, after forking.
+    pub unsafe fn exec(&mut self) -> io::Error {
+        // Formatted by hand, `format!` would allocate.
+        let listen_pid = self.envp.last_mut().expect("LISTEN_PID is always set");
+        let mut pid = unsafe { libc::getpid() } as u32;
+        let mut digits = 0;
+        let mut tmp = pid;
+        while tmp > 0 || digits == 0 {
+            digits += 1;
+            tmp /= 10;
+        }
+        for i in (0..digits).rev() {
+            listen_pid[b"LISTEN_PID=".len() + i] = b'0' + (pid % 10) as u8;
+            pid /= 10;
+        }
+
+        self.ptrs.clear();
+        self.ptrs
+            .extend(self.argv.iter().map(|arg| arg.as_ptr() as *const libc::c_char));
+        self.ptrs.push(std::ptr::null());
+        let envp_start = self.ptrs.len();
+        self.ptrs
+            .extend(self.envp.iter().map(|var| var.as_ptr() as *const libc::c_char));
+        self.ptrs.push(std::ptr::null());
+
+        unsafe {
+            libc::execvpe(self.program.as_ptr(), self.ptrs.as_ptr(), self.ptrs.as_ptr().add(envp_start));
+        }
+        io::Error::last_os_error()
+    }
+}
@@ -2,12 +2,16 @@
 use super::*;
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use libc::{SIGABRT, SIGHUP};
     use tokio::{fs::File, io::AsyncReadExt};
 
     use crate::{
-        conf::{Config, proc::types::StopSignal},
+        conf::{
+            Config,
+            proc::types::{KillMode, RestartIf, StopSequence, StopSignal, StopStep, Watch},
+        },
         run::daemon::Daemon,
     };
 
@@ -19,9 +23,39 @@ mod tests {
             id: None,
             name: "name".to_string(),
             child: None,
+            killed: Vec::new(),
             conf: ProcessConfig::testconfig(),
             healthcheck: HealthCheckRunner::uptime(),
             runtime_failures: 0,
+            manual_override: None,
+            exit_code: None,
+            tracked: BTreeSet::new(),
+            tracked_at: None,
+            members_checked: None,
+            sockets: Vec::new(),
+            replacement: None,
+            retiring: Vec::new(),
+            restart_failure: None,
+            healthy_since: None,
+            recent_failures: VecDeque::new(),
+            fatal_after_stop: None,
+            stop_step: None,
+            stop_sequence: Vec::new(),
+            hook: None,
+            next_run: None,
+            last_scheduled_run: None,
+            run_queued: false,
+            history: VecDeque::new(),
+            starts: 0,
+            started_at: None,
+            last_exit: None,
+            current_run: None,
+            next_trigger: None,
+            resources: None,
+            sampled: None,
+            exceeded: watchdog::Exceeded::default(),
+            watchdog_restart: None,
+            watcher: None,
             state: ProcessState::Idle,
             desired_states: VecDeque::new(),
         };
@@ -189,4 +223,273 @@ mod tests {
 
         assert_eq!(daemon.processes()["foo"].state(), ProcessState::Stopped);
     }
+
+    #[tokio::test]
+    async fn stop_sequence_escalates_step_by_step() {
+        let out = format!("/tmp/{}_stop_sequence.out", std::process::id());
+        let conf_str = format!(
+            "[processes.trap]\ncmd = \"/bin/sh\"\nargs = [\"-c\", \"trap 'echo HUP >> {out}' HUP; trap 'echo USR1 >> {out}' USR1; echo started > {out}; while true; do sleep 0.05; done\"]\nworkingdir = \"/tmp\"\nautostart = true\nstop = [{{ signal = \"HUP\", wait = \"300ms\" }}, {{ signal = \"USR1\", wait = \"300ms\" }}]\n"
+        );
+        let mut conf = Config::from_str(&conf_str).unwrap();
+        let conf = conf.set_socketpath(&format!("/tmp/{}_stop_sequence.sock", std::process::id()));
+        let mut daemon = Daemon::from_config(conf.to_owned(), "path".to_string());
+
+        let _ = daemon.run_once().await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        daemon
+            .processes_mut()
+            .get_mut("trap")
+            .unwrap()
+            .push_desired_state(ProcessState::Stopped);
+
+        let _ = daemon.run_once().await;
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let _ = daemon.run_once().await;
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "started\nHUP\n");
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        let _ = daemon.run_once().await;
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "started\nHUP\nUSR1\n");
+        assert!(matches!(daemon.processes()["trap"].state(), ProcessState::Stopping(_)));
+
+        // Still running after the last step.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let _ = daemon.run_once().await;
+        assert_eq!(daemon.processes()["trap"].state(), ProcessState::Stopped);
+        let _ = std::fs::remove_file(&out);
+    }
+
+    #[tokio::test]
+    async fn reload_shortening_the_stop_sequence_while_stopping() {
+        let step = |signal| StopStep::new(Some(StopSignal(signal)), crate::conf::proc::types::Duration::from_millis(300));
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("/bin/sh")
+            .set_args(vec!["-c".into(), "trap '' HUP USR1; while true; do sleep 0.05; done".into()])
+            .set_workingdir("/tmp")
+            .set_stop(StopSequence::new(vec![step(SIGHUP), step(libc::SIGUSR1), step(libc::SIGTERM)]));
+
+        let mut conf = Config::random();
+        let conf = conf.add_process("trap", proc.clone());
+        let mut daemon = Daemon::from_config(conf.clone(), "path".into());
+
+        let _ = daemon.run_once().await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        daemon
+            .processes_mut()
+            .get_mut("trap")
+            .unwrap()
+            .push_desired_state(ProcessState::Stopped);
+        let _ = daemon.run_once().await;
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        let _ = daemon.run_once().await;
+        assert!(matches!(daemon.processes()["trap"].state(), ProcessState::Stopping(_)));
+
+        // Now at the second step, which the new sequence does not have.
+        let mut shortened = proc.clone();
+        let shortened = shortened.set_stop(StopSequence::new(vec![step(SIGHUP)]));
+        daemon.processes_mut().get_mut("trap").unwrap().set_config(shortened.clone());
+
+        for _ in 0..40 {
+            let _ = daemon.run_once().await;
+            if daemon.processes()["trap"].state() == ProcessState::Stopped {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(daemon.processes()["trap"].state(), ProcessState::Stopped);
+    }
+
+    #[tokio::test]
+    async fn forcefully_killed_child_is_reaped_on_a_later_tick() {
+        let mut daemon = daemon_with_workers(KillMode::Process, "sleep 30").await;
+        let workers = daemon.processes_mut().get_mut("workers").unwrap();
+        let pid = workers.id().unwrap();
+        workers.kill_forcefully().unwrap();
+        workers.push_desired_state(ProcessState::Stopped);
+
+        for _ in 0..40 {
+            let _ = daemon.run_once().await;
+            if !daemon.processes()["workers"].has_unreaped_children() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(!daemon.processes()["workers"].has_unreaped_children());
+        assert!(procfs::stat(pid).is_none());
+    }
+
+    fn alive(pid: u32) -> bool {
+        procfs::stat(pid).is_some_and(|s| !s.is_zombie())
+    }
+
+    async fn daemon_with_workers(killmode: KillMode, script: &str) -> Daemon {
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("/usr/bin/sh")
+            .set_args(vec!["-c".into(), script.into()])
+            .set_workingdir("/tmp")
+            .set_stoptime(2)
+            .set_killmode(killmode);
+
+        let mut conf = Config::random();
+        let conf = conf.add_process("workers", proc.clone());
+        let mut daemon = Daemon::from_config(conf.clone(), "path".into());
+
+        let _ = daemon.run_once().await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        daemon
+    }
+
+    async fn stop(daemon: &mut Daemon) {
+        daemon
+            .processes_mut()
+            .get_mut("workers")
+            .unwrap()
+            .push_desired_state(ProcessState::Stopped);
+
+        for _ in 0..40 {
+            let _ = daemon.run_once().await;
+            if daemon.processes()["workers"].state() == ProcessState::Stopped {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("process did not stop");
+    }
+
+    #[tokio::test]
+    async fn killmode_group_stops_background_workers() {
+        let mut daemon = daemon_with_workers(KillMode::Group, "sleep 30 & sleep 30").await;
+        let members = daemon.processes()["workers"].members();
+        assert!(members.len() >= 2);
+
+        stop(&mut daemon).await;
+
+        assert!(members.iter().all(|pid| !alive(*pid)));
+    }
+
+    #[tokio::test]
+    async fn killmode_process_leaves_background_workers() {
+        let mut daemon = daemon_with_workers(KillMode::Process, "sleep 30 & sleep 30").await;
+        let pid = daemon.processes()["workers"].id().unwrap();
+        let stats = procfs::all();
+        let workers = procfs::group_members(&stats, pid);
+
+        stop(&mut daemon).await;
+
+        let survivors = workers.iter().filter(|p| **p != pid && alive(**p)).collect::<Vec<_>>();
+        assert!(!survivors.is_empty());
+        for survivor in survivors {
+            unsafe { libc::kill(*survivor as i32, libc::SIGKILL) };
+        }
+    }
+
+    #[tokio::test]
+    async fn killmode_tree_stops_workers_in_other_sessions() {
+        let mut daemon = daemon_with_workers(KillMode::Tree, "setsid sleep 30 & sleep 30").await;
+        let pid = daemon.processes()["workers"].id().unwrap();
+        let stats = procfs::all();
+        let escaped = procfs::descendants(&stats, pid)
+            .into_iter()
+            .filter(|p| stats.iter().any(|s| s.pid == *p && s.pgrp != pid))
+            .collect::<Vec<_>>();
+        assert!(!escaped.is_empty());
+
+        stop(&mut daemon).await;
+
+        assert!(escaped.iter().all(|pid| !alive(*pid)));
+    }
+
+    #[tokio::test]
+    async fn resources_are_summed_over_the_tree() {
+        let mut daemon = daemon_with_workers(KillMode::Group, "sleep 30 & sleep 30 & wait").await;
+        let workers = daemon.processes_mut().get_mut("workers").unwrap();
+        workers.sample_resources(&procfs::Snapshot::default());
+        assert!(workers.resources().is_none());
+
+        tokio::time::sleep(Duration::from_millis(1000)).await;
+        let workers = daemon.processes_mut().get_mut("workers").unwrap();
+        workers.sample_resources(&procfs::Snapshot::default());
+
+        let resources = *workers.resources().unwrap();
+        assert_eq!(resources.processes(), 3);
+        assert!(resources.rss_bytes() > 0);
+        assert!(resources.threads() >= 3);
+
+        stop(&mut daemon).await;
+        assert!(daemon.processes()["workers"].resources().is_none());
+    }
+
+    #[tokio::test]
+    async fn restart_if_limit_exceeded() {
+        let mut restart_if = RestartIf::default();
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("/usr/bin/sleep")
+            .set_args(vec!["30".into()])
+            .set_workingdir("/tmp")
+            .set_restart_if(*restart_if.set_fds_above(0));
+
+        let mut conf = Config::random();
+        let conf = conf.add_process("workers", proc.clone());
+        let mut daemon = Daemon::from_config(conf.clone(), "path".into());
+
+        for _ in 0..40 {
+            let _ = daemon.run_once().await;
+            if daemon.processes()["workers"].watchdog_restart().is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        let reason = daemon.processes()["workers"].watchdog_restart().map(str::to_owned);
+        assert!(reason.is_some_and(|reason| reason.contains("fd(s) above 0")));
+
+        for _ in 0..40 {
+            let _ = daemon.run_once().await;
+            if daemon.processes()["workers"].restarts() == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(daemon.processes()["workers"].restarts(), 1);
+
+        stop(&mut daemon).await;
+    }
+
+    #[tokio::test]
+    async fn watched_file_change_restarts() {
+        let dir = format!("/tmp/{}_watched_file_change_restarts", std::process::id());
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(format!("{dir}/src")).unwrap();
+
+        let mut watch = Watch::new(vec!["./src".into()]);
+        let mut proc = ProcessConfig::default();
+        let proc = proc
+            .set_cmd("/usr/bin/sleep")
+            .set_args(vec!["30".into()])
+            .set_workingdir(&dir)
+            .set_watch(watch.set_debounce(crate::conf::proc::types::Duration::from_millis(100)).clone());
+
+        let mut conf = Config::random();
+        let conf = conf.add_process("workers", proc.clone());
+        let mut daemon = Daemon::from_config(conf.clone(), "path".into());
+
+        let _ = daemon.run_once().await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        std::fs::write(format!("{dir}/src/main.rs"), "fn main() {}").unwrap();
+
+        for _ in 0..40 {
+            let _ = daemon.run_once().await;
+            if daemon.processes()["workers"].restarts() == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(daemon.processes()["workers"].restarts(), 1);
+
+        stop(&mut daemon).await;
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
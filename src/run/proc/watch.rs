@@ -0,0 +1,245 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::{OsStr, OsString},
+    fs, io,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use super::Process;
+use crate::{
+    conf::proc::types::Watch,
+    proc_error, proc_info, proc_warning,
+    run::{inotify::Inotify, statemachine::states::ProcessState},
+};
+
+/// Directory watched for a [`FileWatcher`], along with the names of the files in it that
+/// are relevant. `None` if all of them are, the directory then being watched recursively.
+#[derive(Debug)]
+struct WatchedDir {
+    path: PathBuf,
+    names: Option<HashSet<OsString>>,
+}
+
+/// # `FileWatcher`
+/// `src/run/proc/watch.rs`
+///
+/// Watches the `watch.paths` of a process for changes using inotify. Like the daemon's
+/// config watcher, files are watched through their parent directory so a file being
+/// replaced by a rename keeps being watched.
+///
+/// Polled on every tick rather than awaited, the inotify fd is non-blocking.
+#[derive(Debug)]
+pub(super) struct FileWatcher {
+    conf: Watch,
+    inotify: Inotify,
+    dirs: HashMap<i32, WatchedDir>,
+    /// Last relevant change, reported once it is `debounce` old.
+    changed: Option<(Instant, PathBuf)>,
+}
+
+impl FileWatcher {
+    /// Watches the `paths` of `conf`, relative ones being resolved from `workingdir`.
+    /// Paths which cannot be watched are skipped and returned along with the error.
+    fn new(conf: &Watch, workingdir: &str) -> Result<(Self, Vec<(PathBuf, io::Error)>), io::Error> {
+        let mut watcher = Self {
+            conf: conf.clone(),
+            inotify: Inotify::new()?,
+            dirs: HashMap::new(),
+            changed: None,
+        };
+
+        let mut errors = vec![];
+        for path in conf.paths() {
+            let path = Path::new(workingdir).join(path).components().collect::<PathBuf>();
+            let watched = match path.is_dir() {
+                true => watcher.add_tree(&path),
+                false => match (path.parent(), path.file_name()) {
+                    (Some(dir), Some(name)) => watcher.add_dir(dir, Some(name)),
+                    _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "not a file or directory")),
+                },
+            };
+            if let Err(e) = watched {
+                errors.push((path, e));
+            }
+        }
+
+        Ok((watcher, errors))
+    }
+
+    /// Watches `dir` for changes to `name`, or to any file if `name` is `None`.
+    fn add_dir(&mut self, dir: &Path, name: Option<&OsStr>) -> Result<(), io::Error> {
+        let mask = libc::IN_CLOSE_WRITE | libc::IN_MOVED_TO | libc::IN_MOVED_FROM | libc::IN_CREATE | libc::IN_MODIFY | libc::IN_DELETE;
+        let wd = self.inotify.add_watch(dir, mask)?;
+
+        // The same directory yields the same watch, it may be watched for a single file
+        // and as a whole at once.
+        let watched = self.dirs.entry(wd).or_insert_with(|| WatchedDir {
+            path: dir.to_owned(),
+            names: Some(HashSet::new()),
+        });
+        match (name, &mut watched.names) {
+            (Some(name), Some(names)) => {
+                names.insert(name.to_owned());
+            }
+            (None, names) => *names = None,
+            (Some(_), None) => {}
+        }
+        Ok(())
+    }
+
+    /// Watches `dir` and all directories below it which are not ignored.
+    fn add_tree(&mut self, dir: &Path) -> Result<(), io::Error> {
+        self.add_dir(dir, None)?;
+        for entry in fs::read_dir(dir)?.flatten() {
+            let ignored = self.conf.ignores(&entry.file_name().to_string_lossy());
+            if !ignored && entry.file_type().is_ok_and(|t| t.is_dir()) {
+                // Directories vanishing or not readable while walking are not worth failing over.
+                let _ = self.add_tree(&entry.path());
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the pending events, returning the last changed file once no other change
+    /// followed it for `debounce`.
+    fn changed(&mut self) -> Option<PathBuf> {
+        if let Some(changed) = self.read_events() {
+            self.changed = Some((Instant::now(), changed));
+        }
+
+        match &self.changed {
+            Some((at, _)) if at.elapsed() >= self.conf.debounce().get() => self.changed.take().map(|(_, path)| path),
+            _ => None,
+        }
+    }
+
+    /// Reads the pending events without blocking. Returns the last relevant changed
+    /// path among them, `None` if there was none.
+    fn read_events(&mut self) -> Option<PathBuf> {
+        let mut changed = None;
+        loop {
+            let events = match self.inotify.read_events() {
+                Ok(events) if !events.is_empty() => events,
+                _ => return changed,
+            };
+
+            for event in events {
+                if event.mask & libc::IN_Q_OVERFLOW != 0 {
+                    changed = Some(PathBuf::new());
+                    continue;
+                }
+                let Some(watched) = self.dirs.get(&event.wd) else {
+                    continue;
+                };
+                let relevant = watched.names.as_ref().is_none_or(|names| names.contains(&event.name));
+                if !relevant || self.conf.ignores(&event.name.to_string_lossy()) {
+                    continue;
+                }
+
+                let path = watched.path.join(&event.name);
+                let recursive = watched.names.is_none();
+                if recursive && event.mask & libc::IN_ISDIR != 0 && event.mask & (libc::IN_CREATE | libc::IN_MOVED_TO) != 0 {
+                    let _ = self.add_tree(&path);
+                }
+                changed = Some(path);
+            }
+        }
+    }
+}
+
+/// Restarts on file changes, see [`crate::conf::proc::types::Watch`].
+impl Process {
+    /// Restarts the process, or sends it the configured `signal`, once one of its
+    /// watched files changed. Called on every tick of the daemon.
+    pub fn check_watch(&mut self) {
+        let Some(watch) = self.conf.watch() else {
+            self.watcher = None;
+            return;
+        };
+
+        let current = match &self.watcher {
+            Some(Ok(watcher)) => Some(&watcher.conf),
+            Some(Err(failed)) => Some(failed),
+            None => None,
+        };
+        // A failed setup is logged once rather than retried on every tick, mostly running
+        // out of inotify instances, which only a config change is likely to help with.
+        if current != Some(watch) {
+            self.watcher = match FileWatcher::new(watch, self.conf.workingdir().path()) {
+                Ok((watcher, errors)) => {
+                    for (path, e) in errors {
+                        proc_warning!(self, "could not watch path",; path = path, error = e.to_string());
+                    }
+                    Some(Ok(watcher))
+                }
+                Err(e) => {
+                    proc_error!(self, "could not set up file watcher, not retrying until `watch` changes",; error = e.to_string());
+                    Some(Err(watch.clone()))
+                }
+            };
+        }
+
+        let Some(path) = self
+            .watcher
+            .as_mut()
+            .and_then(|watcher| watcher.as_mut().ok())
+            .and_then(FileWatcher::changed)
+        else {
+            return;
+        };
+
+        // A process crashing on a broken change is restarted as soon as it is fixed, one
+        // stopped or not started yet is left alone.
+        use ProcessState::*;
+        if !matches!(self.state, HealthCheck(_) | Healthy | WaitingForRetry(_)) {
+            return;
+        }
+
+        match self.conf.watch().and_then(Watch::signal) {
+            Some(_) if self.pid().is_none() => {}
+            Some(signal) => {
+                proc_info!(self, "watched file changed, signaling",; path = path, signal = signal.signal());
+                self.signal_members(signal.signal());
+            }
+            None => {
+                proc_info!(self, "watched file changed, restarting",; path = path);
+                if let Err(e) = self.restart() {
+                    proc_warning!(self, "could not restart",; error = e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, thread::sleep, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn nested_changes_are_reported_unless_ignored() {
+        let dir = format!("/tmp/{}_watch", std::process::id());
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(format!("{dir}/src/nested")).unwrap();
+        let mut conf = Watch::new(vec!["./src".into()]);
+        let conf = conf
+            .set_ignore(vec!["*.swp".into()])
+            .set_debounce(crate::conf::proc::types::Duration::from_millis(100));
+        let (mut watcher, errors) = FileWatcher::new(conf, &dir).unwrap();
+        assert!(errors.is_empty());
+
+        fs::write(format!("{dir}/src/nested/.main.rs.swp"), "a").unwrap();
+        sleep(Duration::from_millis(200));
+        assert_eq!(watcher.changed(), None);
+
+        fs::write(format!("{dir}/src/nested/main.rs"), "a").unwrap();
+        assert_eq!(watcher.changed(), None);
+        sleep(Duration::from_millis(200));
+        assert_eq!(watcher.changed(), Some(PathBuf::from(format!("{dir}/src/nested/main.rs"))));
+        assert_eq!(watcher.changed(), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
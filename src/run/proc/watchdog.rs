@@ -0,0 +1,83 @@
+use std::time::Instant;
+
+use super::{Process, resources::format_bytes};
+use crate::{proc_warning, run::statemachine::states::ProcessState};
+
+/// Since when the last samples of a process exceeded each of its `restart_if` limits.
+#[derive(Debug, Default, Clone, Copy)]
+pub(super) struct Exceeded {
+    rss: Option<Instant>,
+    cpu: Option<Instant>,
+    fds: Option<Instant>,
+}
+
+/// Resource watchdogs, see [`crate::conf::proc::types::RestartIf`].
+impl Process {
+    /// Restarts the process gracefully if it exceeded one of its `restart_if` limits for
+    /// longer than `for`, or ran for longer than `max_lifetime`. Called on every tick,
+    /// after the resources were sampled.
+    pub fn check_watchdogs(&mut self) {
+        let Some(restart_if) = self.conf.restart_if() else {
+            self.exceeded = Exceeded::default();
+            return;
+        };
+        if self.state != ProcessState::Healthy || !self.desired_states.is_empty() {
+            self.exceeded = Exceeded::default();
+            return;
+        }
+
+        if let Some(max_lifetime) = restart_if.max_lifetime()
+            && self.uptime().is_some_and(|uptime| uptime >= max_lifetime.get())
+        {
+            return self.restart_by_watchdog(format!("ran for longer than {max_lifetime}"));
+        }
+
+        let Some(resources) = self.resources().copied() else {
+            return;
+        };
+        let now = Instant::now();
+        let since = |exceeded_since: &mut Option<Instant>, exceeded: bool| {
+            if !exceeded {
+                *exceeded_since = None;
+            }
+            exceeded.then(|| *exceeded_since.get_or_insert(now))
+        };
+
+        let mut reasons = vec![];
+        if let Some(limit) = restart_if.rss_above()
+            && let Some(since) = since(&mut self.exceeded.rss, resources.rss_bytes() > limit.bytes())
+        {
+            reasons.push((since, format!("rss {} above {}", format_bytes(resources.rss_bytes()), format_bytes(limit.bytes()))));
+        }
+        if let Some(limit) = restart_if.cpu_above()
+            && let Some(since) = since(&mut self.exceeded.cpu, resources.cpu_percent() > limit)
+        {
+            reasons.push((since, format!("cpu {:.1}% above {limit:.1}%", resources.cpu_percent())));
+        }
+        if let Some(limit) = restart_if.fds_above()
+            && let Some(since) = since(&mut self.exceeded.fds, resources.fds() > limit)
+        {
+            reasons.push((since, format!("{} fd(s) above {limit}", resources.fds())));
+        }
+
+        let window = restart_if.for_();
+        if let Some((_, reason)) = reasons
+            .into_iter()
+            .find(|(since, _)| now.duration_since(*since) >= window.get())
+        {
+            self.restart_by_watchdog(format!("{reason} for {window}"));
+        }
+    }
+
+    fn restart_by_watchdog(&mut self, reason: String) {
+        proc_warning!(self, "restart_if limit exceeded, restarting",; reason = reason);
+        self.exceeded = Exceeded::default();
+        self.watchdog_restart = Some(reason);
+        self.push_desired_state(ProcessState::Ready);
+    }
+
+    /// Why the process was last restarted by its `restart_if` watchdogs.
+    pub fn watchdog_restart(&self) -> Option<&str> {
+        self.watchdog_restart.as_deref()
+    }
+}
@@ -10,7 +10,7 @@ const REMOVE_DESIRED_STATE: bool = true;
 pub fn desire_idle(proc: &mut Process) -> (Option<ProcessState>, bool) {
     use ProcessState::*;
     match proc.state().clone() {
-        Idle => (None, REMOVE_DESIRED_STATE),
+        Idle | Fatal(_) => (None, REMOVE_DESIRED_STATE),
         Healthy | HealthCheck(_) | Failed(_) => {
             let _ = proc.kill_gracefully();
             (Some(Stopping(Instant::now())), RETAIN_DESIRED_STATE)
@@ -29,6 +29,7 @@ pub fn desire_stopped(proc: &mut Process) -> (Option<ProcessState>, bool) {
         }
         Stopping(_) => (None, RETAIN_DESIRED_STATE),
         Stopped => (Some(Stopped), REMOVE_DESIRED_STATE),
+        Fatal(_) => (None, REMOVE_DESIRED_STATE),
         _ => (Some(Stopped), REMOVE_DESIRED_STATE),
     }
 }
@@ -42,6 +43,7 @@ pub fn desire_ready(proc: &mut Process) -> (Option<ProcessState>, bool) {
             (Some(Stopping(Instant::now())), RETAIN_DESIRED_STATE)
         }
         Stopping(_) => (None, RETAIN_DESIRED_STATE),
+        Fatal(_) => (None, REMOVE_DESIRED_STATE),
         _ => (Some(Ready), REMOVE_DESIRED_STATE),
     }
 }
@@ -49,7 +51,8 @@ pub fn desire_healthy(proc: &mut Process) -> (Option<ProcessState>, bool) {
     use ProcessState::*;
     match proc.state().clone() {
         Idle => (Some(Ready), REMOVE_DESIRED_STATE),
-        Ready | HealthCheck(_) | Healthy => (None, REMOVE_DESIRED_STATE),
+        // `Fatal` is only left through `Process::reset_failed`, see `start`.
+        Ready | HealthCheck(_) | Healthy | Fatal(_) => (None, REMOVE_DESIRED_STATE),
         Stopping(_) => (None, RETAIN_DESIRED_STATE),
         _ => (Some(Ready), REMOVE_DESIRED_STATE),
     }
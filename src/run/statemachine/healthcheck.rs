@@ -6,7 +6,7 @@ use std::{
     vec,
 };
 
-use crate::conf::proc::types::{CommandHealthCheck, HealthCheck, HealthCheckType, UptimeHealthCheck};
+use crate::conf::proc::types::{Backoff, CommandHealthCheck, HealthCheck, HealthCheckType, UptimeHealthCheck};
 
 #[derive(Debug)]
 pub struct HealthCheckRunner {
@@ -15,7 +15,7 @@ pub struct HealthCheckRunner {
     receiver: Option<tokio::sync::oneshot::Receiver<HealthCheckEvent>>,
     check: HealthCheckType,
     retries: usize,
-    backoff: usize,
+    backoff: Backoff,
 }
 
 #[derive(Debug)]
@@ -32,7 +32,7 @@ impl HealthCheckRunner {
             receiver: None,
             check: hc.healthcheck().clone(),
             retries: hc.retries(),
-            backoff: hc.backoff(),
+            backoff: hc.backoff().clone(),
         }
     }
     pub fn set_healthcheck(&mut self, check: HealthCheckType) -> &mut Self {
@@ -45,7 +45,7 @@ impl HealthCheckRunner {
         self
     }
 
-    pub fn set_backoff(&mut self, backoff: usize) -> &mut Self {
+    pub fn set_backoff(&mut self, backoff: Backoff) -> &mut Self {
         self.backoff = backoff;
         self
     }
@@ -72,16 +72,16 @@ impl HealthCheckRunner {
         }
     }
 
-    pub fn timeout(&self) -> usize {
+    pub fn timeout(&self) -> Duration {
         match &self.check {
-            HealthCheckType::Command(CommandHealthCheck { cmd: _, args: _, timeout }) => *timeout,
+            HealthCheckType::Command(CommandHealthCheck { cmd: _, args: _, timeout }) => timeout.get(),
             _ => panic!("timeout() called on an Uptime HealthCheck"),
         }
     }
 
-    pub fn starttime(&self) -> u16 {
+    pub fn starttime(&self) -> Duration {
         match &self.check {
-            HealthCheckType::Uptime(UptimeHealthCheck { starttime }) => *starttime,
+            HealthCheckType::Uptime(UptimeHealthCheck { starttime }) => starttime.get(),
             _ => panic!("starttime() called on a Command HealthCheck"),
         }
     }
@@ -94,14 +94,18 @@ impl HealthCheckRunner {
         self.retries
     }
 
-    pub fn backoff(&self) -> usize {
-        self.backoff
+    pub fn backoff(&self) -> &Backoff {
+        &self.backoff
     }
 
     pub fn increment_failures(&mut self) {
         self.failures = self.failures.saturating_add(1);
     }
 
+    pub fn set_failures(&mut self, failures: usize) {
+        self.failures = failures;
+    }
+
     pub fn clear_failures(&mut self) {
         self.failures = 0;
     }
@@ -127,7 +131,7 @@ impl HealthCheckRunner {
             Ok(Ok(output)) if output.status.success() => HealthCheckEvent::Passed,
             Ok(Ok(output)) => HealthCheckEvent::Failed(format!("exit code: {}, stderr: {}", output.status, String::from_utf8_lossy(&output.stderr))),
             Ok(Err(e)) => HealthCheckEvent::Failed(e.to_string()),
-            Err(_) => HealthCheckEvent::Failed(format!("timed out after {} seconds", timeout.as_secs())),
+            Err(_) => HealthCheckEvent::Failed(format!("timed out after {timeout:?}")),
         }
     }
 
@@ -139,7 +143,7 @@ impl HealthCheckRunner {
 
         let cmd = self.cmd().clone();
         let args = self.args().to_vec();
-        let timeout = Duration::from_secs(self.timeout() as u64);
+        let timeout = self.timeout();
 
         let handle = tokio::task::spawn(async move {
             let result = HealthCheckRunner::spawn(&cmd, &args, timeout).await;
@@ -162,24 +166,26 @@ mod tests {
                 check: HealthCheckType::Command(CommandHealthCheck {
                     cmd: "/usr/bin/echo".to_string(),
                     args: Vec::new(),
-                    timeout: 5,
+                    timeout: crate::conf::proc::types::Duration::from_secs(5),
                 }),
                 failures: 0,
                 task: None,
                 receiver: None,
                 retries: 5,
-                backoff: 5,
+                backoff: Backoff::fixed(crate::conf::proc::types::Duration::from_secs(5)),
             }
         }
 
         pub fn uptime() -> Self {
             Self {
-                check: HealthCheckType::Uptime(UptimeHealthCheck { starttime: 5 }),
+                check: HealthCheckType::Uptime(UptimeHealthCheck {
+                    starttime: crate::conf::proc::types::Duration::from_secs(5),
+                }),
                 failures: 0,
                 task: None,
                 receiver: None,
                 retries: 5,
-                backoff: 5,
+                backoff: Backoff::fixed(crate::conf::proc::types::Duration::from_secs(5)),
             }
         }
     }
@@ -215,7 +221,7 @@ mod tests {
     #[test]
     fn backoff() {
         let hc = HealthCheckRunner::command();
-        assert_eq!(hc.backoff(), 5);
+        assert_eq!(hc.backoff().delay(1), Duration::from_secs(5));
     }
 
     #[test]
@@ -227,13 +233,13 @@ mod tests {
     #[test]
     fn timeout_on_command_healthcheck() {
         let hc = HealthCheckRunner::command();
-        assert_eq!(hc.timeout(), 5);
+        assert_eq!(hc.timeout(), Duration::from_secs(5));
     }
 
     #[test]
     fn starttime_on_uptime_healthcheck() {
         let hc = HealthCheckRunner::uptime();
-        assert_eq!(hc.starttime(), 5);
+        assert_eq!(hc.starttime(), Duration::from_secs(5));
     }
 
     #[test]
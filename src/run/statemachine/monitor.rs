@@ -3,7 +3,7 @@ use std::time::Instant;
 use crate::{
     proc_error, proc_info, proc_warning,
     run::{
-        proc::{Process, ProcessError},
+        proc::{Process, ProcessError, procfs},
         statemachine::healthcheck::HealthCheckEvent,
     },
 };
@@ -21,6 +21,7 @@ pub async fn monitor_ready(p: &mut Process) -> Option<ProcessState> {
             proc_info!(&p, "spawned",; pid = pid);
             Some(ProcessState::HealthCheck(Instant::now()))
         }
+        Err(ProcessError::HookRunning) => None,
         Err(err) => {
             proc_error!(&p, "failed to start: {err}");
             p.increment_healthcheck_failures();
@@ -103,7 +104,7 @@ fn healthcheck_starttime(started_at: &Instant, p: &mut Process) -> Option<Proces
     }
 
     if p.passed_starttime(*started_at) {
-        proc_info!(&p, "has been running for {} seconds, marking as healthy", p.healthcheck().starttime());
+        proc_info!(&p, "has been running for {:?}, marking as healthy", p.healthcheck().starttime());
 
         return Some(ProcessState::Healthy);
     }
@@ -119,12 +120,19 @@ pub fn monitor_healthcheck(started_at: &Instant, p: &mut Process) -> Option<Proc
 }
 
 pub fn monitor_healthy(p: &mut Process) -> Option<ProcessState> {
+    p.reset_failures_once_stable();
     exited_state(p)
 }
 
 pub fn failed_healthy(p: &mut Process) -> Option<ProcessState> {
     match p.config().autorestart().mode() {
-        "always" => Some(ProcessState::HealthCheck(Instant::now())),
+        "always" => {
+            p.increment_runtime_failures();
+            let retry_at = p.retry_at(p.runtime_failures());
+            proc_warning!(&p, "retrying in {:.1?}", retry_at.duration_since(Instant::now()));
+
+            Some(ProcessState::WaitingForRetry(retry_at))
+        }
         "on-failure" => {
             let max_retries = p.config().autorestart().max_retries();
 
@@ -134,11 +142,11 @@ pub fn failed_healthy(p: &mut Process) -> Option<ProcessState> {
             }
 
             let rem_attempts = max_retries as usize - p.runtime_failures();
-            let backoff = p.healthcheck().backoff();
-            proc_warning!(&p, "retrying in {} second(s) ({} attempt(s) left)", backoff, rem_attempts);
-
             p.increment_runtime_failures();
-            Some(ProcessState::WaitingForRetry(p.retry_at()))
+            let retry_at = p.retry_at(p.runtime_failures());
+            proc_warning!(&p, "retrying in {:.1?} ({} attempt(s) left)", retry_at.duration_since(Instant::now()), rem_attempts);
+
+            Some(ProcessState::WaitingForRetry(retry_at))
         }
         _ => None,
     }
@@ -153,8 +161,9 @@ pub fn failed_healthcheck(p: &mut Process) -> Option<ProcessState> {
         proc_warning!(p, "not healthy after {} attempts, giving up", p.healthcheck().retries());
         None
     } else {
-        proc_info!(p, "retrying healthcheck in {} seconds", p.healthcheck().backoff());
-        Some(ProcessState::WaitingForRetry(p.retry_at()))
+        let retry_at = p.retry_at(p.healthcheck_failures());
+        proc_info!(p, "retrying healthcheck in {:.1?}", retry_at.duration_since(Instant::now()));
+        Some(ProcessState::WaitingForRetry(retry_at))
     }
 }
 
@@ -162,6 +171,11 @@ pub fn monitor_failed(p: &mut Process) -> Option<ProcessState> {
     if let ProcessState::Failed(prev_state) = p.state().clone() {
         assert!(matches!(*prev_state, ProcessState::HealthCheck(_) | ProcessState::Healthy));
 
+        if let Some(reason) = p.record_failure() {
+            proc_error!(p, "{reason}, giving up until started again");
+            return Some(p.give_up(reason));
+        }
+
         match *prev_state {
             ProcessState::Healthy => failed_healthy(p),
             ProcessState::HealthCheck(_) => failed_healthcheck(p),
@@ -184,6 +198,7 @@ pub async fn monitor_waiting_for_retry(retry_at: &Instant, p: &mut Process) -> O
         }
         Err(e) => match e {
             ProcessError::AlreadyRunning => Some(ProcessState::HealthCheck(Instant::now())),
+            ProcessError::HookRunning => None,
             _ => {
                 proc_warning!(&p, "failed to start: {}", e);
                 Some(ProcessState::Failed(Box::new(ProcessState::HealthCheck(Instant::now()))))
@@ -197,19 +212,40 @@ pub async fn monitor_completed(p: &mut Process) -> Option<ProcessState> {
         return None;
     }
 
+    // Only counted once, not on every check while the `pre_start` hook is running.
+    if !p.pre_start_running()
+        && let Some(reason) = p.record_failure()
+    {
+        proc_error!(p, "{reason}, giving up until started again");
+        return Some(ProcessState::Fatal(reason));
+    }
+
     match p.start().await {
         Ok(()) => {
             proc_info!(p, "spawned, PID {}", p.id().expect("if the process started, its id should be set"));
             Some(ProcessState::HealthCheck(Instant::now()))
         }
+        Err(ProcessError::HookRunning) => None,
         Err(err) => {
             proc_warning!(p, "failed to start: {}", err);
-            Some(ProcessState::Failed(Box::new(ProcessState::HealthCheck(Instant::now()))))
+            // Not through `Failed`, `monitor_failed` would count this iteration towards
+            // `startlimit` a second time.
+            failed_healthcheck(p)
         }
     }
 }
 
-pub fn monitor_stopping(killed_at: Instant, p: &mut Process) -> Option<ProcessState> {
+pub fn monitor_stopping(p: &mut Process, stats: &procfs::Snapshot) -> Option<ProcessState> {
+    // The process is only stopped once every member of its group/tree exited. Until
+    // then it is not reaped either, which keeps its PID (and process group ID) from
+    // being reused.
+    if p.leader_exited() && p.has_running_members(stats) {
+        if p.escalate_stop() {
+            return Some(ProcessState::Stopped);
+        }
+        return None;
+    }
+
     match p.exited() {
         Ok(code) => {
             if p.config().exitcodes().contains(&code) {
@@ -226,19 +262,21 @@ pub fn monitor_stopping(killed_at: Instant, p: &mut Process) -> Option<ProcessSt
                 proc_warning!(&p, "exited, {e}");
                 Some(ProcessState::Stopped)
             }
-            _ => {
-                if killed_at.elapsed().as_secs() >= p.config().stoptime() as u64 {
-                    proc_info!(p, "will now be killed forcefully");
-                    let _ = p.kill_forcefully();
-                    Some(ProcessState::Stopped)
-                } else {
-                    None
-                }
-            }
+            _ => match p.escalate_stop() {
+                true => Some(ProcessState::Stopped),
+                false => None,
+            },
         },
     }
 }
 
+pub fn monitor_fatal(p: &mut Process) -> Option<ProcessState> {
+    // Reap the process, it is not touched again until the state is cleared.
+    let _ = p.exited();
+
+    None
+}
+
 pub fn monitor_stopped(p: &mut Process) -> Option<ProcessState> {
     // Clear failures to start again from 0 if the process gets restarted by the shell.
     p.clear_runtime_failures();
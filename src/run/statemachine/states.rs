@@ -4,12 +4,12 @@ use std::{
 };
 
 use crate::run::{
-    proc::Process,
+    proc::{Process, procfs},
     statemachine::{
         desired::{desire_healthy, desire_idle, desire_ready, desire_stopped},
         monitor::{
-            monitor_completed, monitor_failed, monitor_healthcheck, monitor_healthy, monitor_idle, monitor_ready, monitor_stopped, monitor_stopping,
-            monitor_waiting_for_retry,
+            monitor_completed, monitor_failed, monitor_fatal, monitor_healthcheck, monitor_healthy, monitor_idle, monitor_ready, monitor_stopped,
+            monitor_stopping, monitor_waiting_for_retry,
         },
     },
 };
@@ -26,6 +26,9 @@ pub enum ProcessState {
     Completed,
     Stopping(time::Instant),
     Stopped,
+    /// Failed more often than its `startlimit` allows, with the reason. Not restarted
+    /// automatically until cleared by `start` or `reset-failed`.
+    Fatal(String),
 }
 
 impl Display for ProcessState {
@@ -41,12 +44,13 @@ impl Display for ProcessState {
             ProcessState::Completed => write!(f, "completed successfully"),
             ProcessState::Stopping(_) => write!(f, "stopping"),
             ProcessState::Stopped => write!(f, "stopped"),
+            ProcessState::Fatal(reason) => write!(f, "fatal: {reason}"),
         }
     }
 }
 
 impl ProcessState {
-    pub async fn monitor(&mut self, proc: &mut Process) -> Option<ProcessState> {
+    pub async fn monitor(&mut self, proc: &mut Process, stats: &procfs::Snapshot) -> Option<ProcessState> {
         use ProcessState::*;
         match self {
             Idle => monitor_idle(),
@@ -56,8 +60,9 @@ impl ProcessState {
             Failed(_process_state) => monitor_failed(proc),
             WaitingForRetry(retry_at) => monitor_waiting_for_retry(retry_at, proc).await,
             Completed => monitor_completed(proc).await,
-            Stopping(killed_at) => monitor_stopping(*killed_at, proc),
+            Stopping(_) => monitor_stopping(proc, stats),
             Stopped => monitor_stopped(proc),
+            Fatal(_) => monitor_fatal(proc),
         }
     }
 
@@ -76,6 +81,7 @@ impl ProcessState {
             Completed => panic!("target ProcessState `Completed` doesn't make sense"),
             Failed(_) => panic!("target ProcessState `Failed` doesn't make sense"),
             WaitingForRetry(_) => panic!("target ProcessState `WaitingForRetry` doesn't make sense"),
+            Fatal(_) => panic!("target ProcessState `Fatal` doesn't make sense"),
         };
 
         if remove_desired_state {
@@ -12,12 +12,24 @@ pub fn help() -> String {
     help_text.push_str("  -s, --socketpath PATH      Path to taskmaster socket [default: $TASKMASTER_SOCKETPATH or /tmp/taskmaster.sock]\n\n");
 
     help_text.push_str("COMMANDS:\n");
-    help_text.push_str("  status [PROCESS]           Show status of all processes or a specific process\n");
+    help_text.push_str("  status [PROCESS] [--long]  Show status of all processes or a specific process, --long adds pid, uptime, restarts and last exit\n");
+    help_text.push_str("  describe PROCESS           Show everything known about the state of a process\n");
     help_text.push_str("  start PROCESS              Start a process\n");
     help_text.push_str("  restart PROCESS            Restart a process\n");
     help_text.push_str("  stop PROCESS               Stop a process\n");
+    help_text.push_str("  run PROCESS [--wait]       Start a oneshot job, --wait blocks until it finished and exits with its exit code\n");
+    help_text.push_str("  history PROCESS            Show the last runs of a oneshot job\n");
+    help_text.push_str("  rolling-restart PROGRAM    Restart the instances of a program one batch at a time, following the progress\n");
+    help_text.push_str("    --batch-size N           Instances restarted at once [default: 1]\n");
+    help_text.push_str("    --max-unavailable N      Instances allowed to be down at once [default: 1]\n");
+    help_text.push_str("    --abort-on-failure       Stop after the first instance which does not become healthy\n");
+    help_text.push_str("  scale PROGRAM N            Run N instances of a program, until the next reload\n");
     help_text.push_str("  attach PROCESS SUBCOMMAND  Attach to process output\n");
-    help_text.push_str("  reload                     Reload the configuration\n");
+    help_text.push_str("  reload [--dry-run]         Reload the configuration, or only show what would change\n");
+    help_text.push_str("  reset [PROCESS]            Forget start/stop overrides and failure counters kept across engine restarts\n");
+    help_text.push_str("  reset-failed [PROCESS]     Clear the fatal state of crash-looping processes, without starting them\n");
+    help_text.push_str("  rollback [N]               Re-apply the configuration active N reloads ago [default: 1]\n");
+    help_text.push_str("  info                       Show information about the engine, including failed reloads\n");
     help_text.push_str("  exit                       Exit the shell\n");
     help_text.push_str("  engine SUBCOMMAND          Control the taskmaster engine\n");
     help_text.push_str("    start CONFIG_PATH        Start the taskmaster engine with the given configuration\n");
@@ -56,14 +68,41 @@ impl TryFrom<Vec<String>> for EngineSubcommand {
     }
 }
 
+/// Options of `rolling-restart`, see `RequestType::new_rolling_restart`.
+#[derive(Debug, PartialEq)]
+pub struct RollingRestartOptions {
+    pub batch_size: usize,
+    pub max_unavailable: usize,
+    pub abort_on_failure: bool,
+}
+
+impl Default for RollingRestartOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 1,
+            max_unavailable: 1,
+            abort_on_failure: false,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum ShellCommand {
-    Status { process: Option<String> },
+    Status { process: Option<String>, long: bool },
+    Describe { process: String },
     Start { process: String },
     Restart { process: String },
     Stop { process: String },
+    Run { process: String, wait: bool },
+    History { process: String },
+    RollingRestart { process: String, options: RollingRestartOptions },
+    Scale { process: String, processes: u16 },
     Attach { process: String, fd: AttachFile },
-    Reload,
+    Reload { dry_run: bool },
+    Rollback { steps: usize },
+    Reset { process: Option<String> },
+    ResetFailed { process: Option<String> },
+    Info,
     Exit,
     Engine { subcommand: EngineSubcommand },
     Help,
@@ -78,13 +117,22 @@ impl TryFrom<Vec<String>> for ShellCommand {
         }
         match value[0].as_str() {
             "status" => {
-                if value.len() > 2 {
-                    return Err("status [PROCESS_NAME]".to_string());
+                let long = value[1..].iter().any(|arg| arg == "--long");
+                let args = value[1..].iter().filter(|arg| *arg != "--long").collect::<Vec<_>>();
+                if args.len() > 1 {
+                    return Err("status [PROCESS_NAME] [--long]".to_string());
                 }
                 Ok(Self::Status {
-                    process: value.get(1).map(String::to_owned),
+                    process: args.first().map(|process| process.to_string()),
+                    long,
                 })
             }
+            "describe" => {
+                if value.len() != 2 {
+                    return Err("describe PROCESS_NAME".to_string());
+                }
+                Ok(Self::Describe { process: value[1].to_owned() })
+            }
             "start" => {
                 if value.len() != 2 {
                     return Err("start PROCESS_NAME".to_string());
@@ -103,6 +151,56 @@ impl TryFrom<Vec<String>> for ShellCommand {
                 }
                 Ok(Self::Stop { process: value[1].to_owned() })
             }
+            "run" => match value.len() {
+                2 => Ok(Self::Run {
+                    process: value[1].to_owned(),
+                    wait: false,
+                }),
+                3 if value[2] == "--wait" => Ok(Self::Run {
+                    process: value[1].to_owned(),
+                    wait: true,
+                }),
+                _ => Err("run PROCESS_NAME [--wait]".to_string()),
+            },
+            "history" => {
+                if value.len() != 2 {
+                    return Err("history PROCESS_NAME".to_string());
+                }
+                Ok(Self::History { process: value[1].to_owned() })
+            }
+            "rolling-restart" => {
+                let usage = "rolling-restart PROGRAM [--batch-size N] [--max-unavailable N] [--abort-on-failure]".to_string();
+                if value.len() < 2 {
+                    return Err(usage);
+                }
+
+                let count = |n: Option<&String>| n.and_then(|n| n.parse::<usize>().ok()).filter(|n| *n > 0).ok_or(usage.clone());
+                let mut options = RollingRestartOptions::default();
+                let mut args = value[2..].iter();
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--batch-size" => options.batch_size = count(args.next())?,
+                        "--max-unavailable" => options.max_unavailable = count(args.next())?,
+                        "--abort-on-failure" => options.abort_on_failure = true,
+                        _ => return Err(usage),
+                    }
+                }
+
+                Ok(Self::RollingRestart {
+                    process: value[1].to_owned(),
+                    options,
+                })
+            }
+            "scale" => match value.len() {
+                3 => match value[2].parse::<u16>() {
+                    Ok(processes) if processes > 0 => Ok(Self::Scale {
+                        process: value[1].to_owned(),
+                        processes,
+                    }),
+                    _ => Err("scale PROGRAM N".to_string()),
+                },
+                _ => Err("scale PROGRAM N".to_string()),
+            },
             "attach" => {
                 if value.len() != 3 {
                     return Err("attach PROCESS_NAME {stdout | stderr}".to_string());
@@ -113,11 +211,38 @@ impl TryFrom<Vec<String>> for ShellCommand {
                     fd,
                 })
             }
-            "reload" => {
+            "reload" => match value.len() {
+                1 => Ok(Self::Reload { dry_run: false }),
+                2 if value[1] == "--dry-run" => Ok(Self::Reload { dry_run: true }),
+                _ => Err("reload [--dry-run]".to_string()),
+            },
+            "reset" => match value.len() {
+                1 => Ok(Self::Reset { process: None }),
+                2 => Ok(Self::Reset {
+                    process: Some(value[1].to_owned()),
+                }),
+                _ => Err("reset [PROCESS_NAME]".to_string()),
+            },
+            "reset-failed" => match value.len() {
+                1 => Ok(Self::ResetFailed { process: None }),
+                2 => Ok(Self::ResetFailed {
+                    process: Some(value[1].to_owned()),
+                }),
+                _ => Err("reset-failed [PROCESS_NAME]".to_string()),
+            },
+            "rollback" => match value.len() {
+                1 => Ok(Self::Rollback { steps: 1 }),
+                2 => match value[1].parse::<usize>() {
+                    Ok(steps) if steps > 0 => Ok(Self::Rollback { steps }),
+                    _ => Err("rollback [N]".to_string()),
+                },
+                _ => Err("rollback [N]".to_string()),
+            },
+            "info" => {
                 if value.len() != 1 {
-                    return Err("reload".to_string());
+                    return Err("info".to_string());
                 }
-                Ok(Self::Reload)
+                Ok(Self::Info)
             }
             "exit" => {
                 if value.len() != 1 {
@@ -312,4 +437,134 @@ mod tests {
 
         assert!(Args::try_from(command_line).is_err());
     }
+
+    #[test]
+    fn rollback() {
+        let command_line = "rollback".split_ascii_whitespace().map(String::from).collect::<Vec<String>>();
+        assert_eq!(Args::try_from(command_line).unwrap().command, ShellCommand::Rollback { steps: 1 });
+
+        let command_line = "rollback 3".split_ascii_whitespace().map(String::from).collect::<Vec<String>>();
+        assert_eq!(Args::try_from(command_line).unwrap().command, ShellCommand::Rollback { steps: 3 });
+
+        let command_line = "rollback 0".split_ascii_whitespace().map(String::from).collect::<Vec<String>>();
+        assert!(Args::try_from(command_line).is_err());
+    }
+
+    #[test]
+    fn rolling_restart() {
+        let command_line = "rolling-restart web"
+            .split_ascii_whitespace()
+            .map(String::from)
+            .collect::<Vec<String>>();
+        assert_eq!(
+            Args::try_from(command_line).unwrap().command,
+            ShellCommand::RollingRestart {
+                process: "web".to_string(),
+                options: RollingRestartOptions::default(),
+            }
+        );
+
+        let command_line = "rolling-restart web --batch-size 2 --abort-on-failure --max-unavailable 3"
+            .split_ascii_whitespace()
+            .map(String::from)
+            .collect::<Vec<String>>();
+        assert_eq!(
+            Args::try_from(command_line).unwrap().command,
+            ShellCommand::RollingRestart {
+                process: "web".to_string(),
+                options: RollingRestartOptions {
+                    batch_size: 2,
+                    max_unavailable: 3,
+                    abort_on_failure: true,
+                },
+            }
+        );
+
+        let command_line = "rolling-restart web --batch-size 0"
+            .split_ascii_whitespace()
+            .map(String::from)
+            .collect::<Vec<String>>();
+        assert!(Args::try_from(command_line).is_err());
+    }
+
+    #[test]
+    fn scale() {
+        let command_line = "scale web 3"
+            .split_ascii_whitespace()
+            .map(String::from)
+            .collect::<Vec<String>>();
+        assert_eq!(
+            Args::try_from(command_line).unwrap().command,
+            ShellCommand::Scale {
+                process: "web".to_string(),
+                processes: 3
+            }
+        );
+
+        let command_line = "scale web 0"
+            .split_ascii_whitespace()
+            .map(String::from)
+            .collect::<Vec<String>>();
+        assert!(Args::try_from(command_line).is_err());
+
+        let command_line = "scale web".split_ascii_whitespace().map(String::from).collect::<Vec<String>>();
+        assert!(Args::try_from(command_line).is_err());
+    }
+
+    #[test]
+    fn run() {
+        let command_line = "run backup".split_ascii_whitespace().map(String::from).collect::<Vec<String>>();
+        assert_eq!(
+            Args::try_from(command_line).unwrap().command,
+            ShellCommand::Run {
+                process: "backup".to_string(),
+                wait: false
+            }
+        );
+
+        let command_line = "run backup --wait"
+            .split_ascii_whitespace()
+            .map(String::from)
+            .collect::<Vec<String>>();
+        assert_eq!(
+            Args::try_from(command_line).unwrap().command,
+            ShellCommand::Run {
+                process: "backup".to_string(),
+                wait: true
+            }
+        );
+
+        let command_line = "run backup --later"
+            .split_ascii_whitespace()
+            .map(String::from)
+            .collect::<Vec<String>>();
+        assert!(Args::try_from(command_line).is_err());
+    }
+
+    #[test]
+    fn status_long() {
+        let command_line = "status --long"
+            .split_ascii_whitespace()
+            .map(String::from)
+            .collect::<Vec<String>>();
+        assert_eq!(Args::try_from(command_line).unwrap().command, ShellCommand::Status { process: None, long: true });
+
+        let command_line = "status web --long"
+            .split_ascii_whitespace()
+            .map(String::from)
+            .collect::<Vec<String>>();
+        assert_eq!(
+            Args::try_from(command_line).unwrap().command,
+            ShellCommand::Status {
+                process: Some("web".to_string()),
+                long: true
+            }
+        );
+
+        let command_line = "status web api"
+            .split_ascii_whitespace()
+            .map(String::from)
+            .collect::<Vec<String>>();
+        assert!(Args::try_from(command_line).is_err());
+    }
 }